@@ -0,0 +1,62 @@
+use std::{env, fs, process, time::Instant};
+
+use reconcile_text::{BuiltinTokenizer, DiffOptions, EditedText};
+
+/// Times diffing a document against a heavily rewritten copy of itself, with
+/// and without mapping the tokens to integers first. `EditedText::
+/// from_strings_with_tokenizer` compares the tokens themselves, while
+/// `EditedText::from_strings_with_options` interns them, which requires the
+/// tokens to be `Eq + Hash`.
+///
+/// Run it with:
+/// `cargo run --release --example diff-benchmark [file] [rewrite_every_nth_word]`
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let path = args
+        .get(1)
+        .map_or("tests/resources/pride_and_prejudice.txt", String::as_str);
+    let rewrite_every = args.get(2).map_or(Ok(10), |n| n.parse::<usize>());
+
+    let Ok(rewrite_every @ 1..) = rewrite_every else {
+        eprintln!("Usage: diff-benchmark [file] [rewrite_every_nth_word]");
+        process::exit(1);
+    };
+
+    let original = fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("Error reading {path}: {e}");
+        process::exit(1);
+    });
+
+    // Replace every nth word, so that the diff has many short edits
+    let updated = original
+        .split(' ')
+        .enumerate()
+        .map(|(index, word)| {
+            if index % rewrite_every == 0 {
+                "rewritten"
+            } else {
+                word
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let updated = updated.as_str().into();
+    let tokenizer = &*BuiltinTokenizer::Word;
+
+    let start = Instant::now();
+    let compared = EditedText::from_strings_with_tokenizer(original.as_str(), &updated, tokenizer);
+    let compared_elapsed = start.elapsed();
+
+    let start = Instant::now();
+    let interned = EditedText::from_strings_with_options(
+        original.as_str(),
+        &updated,
+        tokenizer,
+        &DiffOptions::default(),
+    );
+    let interned_elapsed = start.elapsed();
+
+    assert_eq!(compared.apply().text(), interned.apply().text());
+    println!("Comparing tokens: {compared_elapsed:.2?}");
+    println!("Interning tokens: {interned_elapsed:.2?}");
+}
@@ -0,0 +1,43 @@
+use std::{env, fs, process};
+
+use reconcile_text::{AnsiRenderer, BuiltinTokenizer, HtmlRenderer, reconcile};
+
+/// Merges three versions of a file like the `merge-file` example, but prints
+/// how the merge was resolved: with colors for the terminal, or as HTML with
+/// `--html`
+///
+/// Run it with:
+/// `cargo run --features render --example show-merge my.txt base.txt their.txt [--html]`
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let is_html = args.iter().any(|arg| arg == "--html");
+    let files: Vec<&String> = args.iter().skip(1).filter(|arg| *arg != "--html").collect();
+
+    if files.len() != 3 {
+        eprintln!("Usage: show-merge <mine> <base> <theirs> [--html]");
+        process::exit(1);
+    }
+
+    let [mine, base, theirs] = [files[0], files[1], files[2]].map(|file| {
+        fs::read_to_string(file).unwrap_or_else(|e| {
+            eprintln!("Error reading {file}: {e}");
+            process::exit(1);
+        })
+    });
+
+    let result = reconcile(
+        &base,
+        &mine.into(),
+        &theirs.into(),
+        &*BuiltinTokenizer::Word,
+    );
+
+    if is_html {
+        println!(
+            "<pre>{}</pre>",
+            result.apply_with_html(&HtmlRenderer::default())
+        );
+    } else {
+        print!("{}", result.apply_with_ansi(&AnsiRenderer::default()));
+    }
+}
@@ -101,8 +101,8 @@ function updateMergedText(): void {
     fragment.appendChild(createSelectionOverlay(selectionSide === 'left', isSelection));
   }
 
-  for (const { text, history } of results.history) {
-    const isDelete = history === 'RemovedFromLeft' || history === 'RemovedFromRight';
+  for (const { text, history, isConflicting } of results.history) {
+    const isDelete = history.startsWith('Removed');
     let spanChars: string[] = [];
     let currentClass = '';
 
@@ -117,7 +117,7 @@ function updateMergedText(): void {
     };
 
     for (const character of text) {
-      let className = history;
+      let className: string = isConflicting ? `${history} conflicting` : history;
       if (
         !isDelete &&
         selectionStart <= currentPosition &&
@@ -60,6 +60,14 @@ describe('reconcile', () => {
     expect(result.text).toEqual('Hi world');
     expect(result.history.length).toBeGreaterThan(0);
   });
+
+  it('flags conflicting spans', () => {
+    const result = reconcileWithHistory('Meet at 5pm', 'Meet at 6pm', 'Meet at 7pm');
+
+    expect(
+      result.history.filter((span) => span.isConflicting).map((span) => span.text)
+    ).toEqual([' 5pm', ' 6pm', ' 7pm']);
+  });
 });
 
 describe('test_diff_and_undiff_are_inverse', () => {
@@ -19,6 +19,10 @@ const HISTORY_VALUES = [
   'AddedFromRight',
   'RemovedFromLeft',
   'RemovedFromRight',
+  'AddedByBoth',
+  'RemovedByBoth',
+  'Added',
+  'Removed',
 ] as const;
 
 /**
@@ -31,7 +35,8 @@ export type BuiltinTokenizer = (typeof BUILTIN_TOKENIZERS)[number];
 /**
  * History classification for text spans in merge results.
  *
- * Indicates the origin of each text span in the merged document.
+ * Indicates the origin of each text span in the merged document. Two-way
+ * diffs label their spans `Unchanged`, `Added` or `Removed`.
  */
 export type History = (typeof HISTORY_VALUES)[number];
 
@@ -75,8 +80,8 @@ export interface TextWithOptionalCursors {
  * relative positions as text is inserted, deleted, or modified around them.
  */
 export interface CursorPosition {
-  /** Unique identifier for the cursor (can be any number, must be unique within the document) */
-  id: number;
+  /** Unique identifier for the cursor (a number or a string such as a session id, must be unique within the document) */
+  id: number | string;
 
   /** Character position in the text, 0-based index from the beginning of the document */
   position: number;
@@ -119,6 +124,15 @@ export interface SpanWithHistory {
 
   /** The origin of this text span in the merge result */
   history: History;
+
+  /** Start of the span in the merged text, as a JavaScript string index */
+  mergedStart: number;
+
+  /** End of the span in the merged text, equal to `mergedStart` for removed text */
+  mergedEnd: number;
+
+  /** Whether the span was added or removed in a region both sides edited differently */
+  isConflicting: boolean;
 }
 
 const UNSUPPORTED_TOKENIZER_ERROR = `Unsupported tokenizer, only ${BUILTIN_TOKENIZERS.join(
@@ -318,7 +332,9 @@ function toWasmTextWithCursors(
 }
 
 function toWasmCursorPosition({ id, position }: CursorPosition): wasmCursorPosition {
-  return new wasmCursorPosition(id, position);
+  return typeof id === 'string'
+    ? wasmCursorPosition.named(id, position)
+    : new wasmCursorPosition(id, position);
 }
 
 function toTextWithCursors(textWithCursor: wasmTextWithCursors): TextWithCursors {
@@ -336,7 +352,7 @@ function toTextWithCursors(textWithCursor: wasmTextWithCursors): TextWithCursors
 
 function toCursorPosition(cursor: wasmCursorPosition): CursorPosition {
   return {
-    id: cursor.id(),
+    id: cursor.id() as number | string,
     position: cursor.characterIndex(),
   };
 }
@@ -345,6 +361,9 @@ function toSpanWithHistory(span: wasmSpanWithHistory): SpanWithHistory {
   const result = {
     text: span.text(),
     history: span.history(),
+    mergedStart: span.mergedStart(),
+    mergedEnd: span.mergedEnd(),
+    isConflicting: span.isConflicting(),
   };
   span.free();
   return result;
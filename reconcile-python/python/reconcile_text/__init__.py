@@ -13,16 +13,24 @@ BuiltinTokenizer = Literal["Character", "Line", "Markdown", "Word"]
 """Tokenization strategy for text merging."""
 
 History = Literal[
-    "Unchanged", "AddedFromLeft", "AddedFromRight", "RemovedFromLeft", "RemovedFromRight"
+    "Unchanged",
+    "AddedFromLeft",
+    "AddedFromRight",
+    "RemovedFromLeft",
+    "RemovedFromRight",
+    "AddedByBoth",
+    "RemovedByBoth",
+    "Added",
+    "Removed",
 ]
-"""Provenance label for each span in a merge result."""
+"""Provenance label for each span in a merge result or two-way diff."""
 
 
 class CursorPosition(TypedDict):
     """A cursor position within a text document."""
 
-    id: int
-    """Unique identifier for the cursor."""
+    id: Union[int, str]
+    """Unique identifier for the cursor, a number or a string such as a session id."""
     position: int
     """Character position in the text (0-based)."""
 
@@ -43,6 +51,8 @@ class SpanWithHistory(TypedDict):
     """The text content of this span."""
     history: History
     """Which source this span came from."""
+    is_conflicting: bool
+    """Whether the span was added or removed in a region both sides edited differently."""
 
 
 class TextWithCursorsAndHistory(TypedDict):
@@ -1,7 +1,7 @@
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 use reconcile_text::{
-    BuiltinTokenizer, CursorPosition, EditedText, NumberOrText, TextWithCursors,
+    BuiltinTokenizer, CursorId, CursorPosition, EditedText, NumberOrText, TextWithCursors,
 };
 
 fn parse_tokenizer(tokenizer: &str) -> PyResult<BuiltinTokenizer> {
@@ -34,15 +34,17 @@ fn extract_text_with_cursors(input: &Bound<'_, PyAny>) -> PyResult<TextWithCurso
             let mut cursors = Vec::with_capacity(list.len());
             for item in list {
                 let cursor_dict = item.cast::<PyDict>()?;
-                let id: usize = cursor_dict
+                let id = cursor_dict
                     .get_item("id")?
-                    .ok_or_else(|| pyo3::exceptions::PyKeyError::new_err("id"))?
-                    .extract()?;
+                    .ok_or_else(|| pyo3::exceptions::PyKeyError::new_err("id"))?;
                 let position: usize = cursor_dict
                     .get_item("position")?
                     .ok_or_else(|| pyo3::exceptions::PyKeyError::new_err("position"))?
                     .extract()?;
-                cursors.push(CursorPosition::new(id, position));
+                cursors.push(match id.extract::<usize>() {
+                    Ok(id) => CursorPosition::new(id, position),
+                    Err(_) => CursorPosition::named(&id.extract::<String>()?, position),
+                });
             }
             cursors
         }
@@ -63,7 +65,10 @@ fn text_with_cursors_to_dict<'py>(
         py,
         twc.cursors().iter().map(|c| {
             let d = PyDict::new(py);
-            d.set_item("id", c.id()).unwrap();
+            match c.id() {
+                CursorId::Number(id) => d.set_item("id", id).unwrap(),
+                CursorId::Text(id) => d.set_item("id", id).unwrap(),
+            }
             d.set_item("position", c.char_index()).unwrap();
             d
         }),
@@ -114,7 +119,8 @@ fn reconcile<'py>(
 ///     tokenizer: Tokenization strategy - "Word" (default), "Character", "Line", or "Markdown".
 ///
 /// Returns:
-///     A dict with "text", "cursors", and "history" (list of dicts with "text" and "history" keys).
+///     A dict with "text", "cursors", and "history" (list of dicts with "text", "history",
+///     and "is_conflicting" keys).
 #[pyfunction]
 #[pyo3(signature = (parent, left, right, tokenizer = "Word"))]
 fn reconcile_with_history<'py>(
@@ -140,6 +146,8 @@ fn reconcile_with_history<'py>(
             d.set_item("text", span.text()).unwrap();
             d.set_item("history", format!("{:?}", span.history()))
                 .unwrap();
+            d.set_item("is_conflicting", span.is_conflicting())
+                .unwrap();
             d
         }),
     )?;
@@ -85,11 +85,20 @@ class TestReconcileWithHistory:
             "AddedFromRight",
             "RemovedFromLeft",
             "RemovedFromRight",
+            "AddedByBoth",
+            "RemovedByBoth",
+            "Added",
+            "Removed",
         }
         result = reconcile_with_history("Hello", "Hello world", "Hi")
         for span in result["history"]:
             assert span["history"] in valid_histories
 
+    def test_conflicting_spans(self) -> None:
+        result = reconcile_with_history("Meet at 5pm", "Meet at 6pm", "Meet at 7pm")
+        conflicting = [span["text"] for span in result["history"] if span["is_conflicting"]]
+        assert conflicting == [" 5pm", " 6pm", " 7pm"]
+
 
 class TestDiff:
     def test_basic_diff(self) -> None:
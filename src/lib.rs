@@ -118,6 +118,77 @@
 //! ```
 //! > The `cursors` list is sorted by character position (not IDs).
 //!
+//! Cursors can also be identified by strings, such as session ids, with
+//! `CursorPosition::named`. Selections (with an anchor and a head, identified
+//! the same way) can be attached with `TextWithCursors::with_selections`. Both
+//! cursors and selections have a `Gravity` deciding whether they stay before
+//! (`Gravity::Before`) or move after (`Gravity::After`) text another side
+//! inserts exactly at their position. The default, `Gravity::Legacy`, stays
+//! before it too, except at the very start of the text, where cursors move
+//! after the text inserted there as they always have.
+//!
+//! ## Merging more than two edits
+//!
+//! Any number of concurrent edits of the same parent can be merged in a
+//! single pass, so the result doesn't depend on the order of pairwise merges:
+//!
+//! ```
+//! use reconcile_text::{reconcile_many, BuiltinTokenizer};
+//!
+//! let parent = "Hello world";
+//! let forks = [
+//!     "Hello beautiful world".into(),
+//!     "Hi world".into(),
+//!     "Hello world!".into(),
+//! ];
+//!
+//! let result = reconcile_many(parent, &forks, &*BuiltinTokenizer::Word);
+//! assert_eq!(result.apply().text(), "Hi beautiful world!");
+//! ```
+//!
+//! ## Reviewing conflicting edits
+//!
+//! Regions which both sides edited are still merged automatically, but they
+//! are reported so that they can be flagged for review:
+//!
+//! ```
+//! use reconcile_text::{reconcile, BuiltinTokenizer};
+//!
+//! let parent = "The meeting is on Monday at noon.";
+//! let left = "The meeting is on Tuesday at noon.";
+//! let right = "The meeting is on Friday at noon!";
+//!
+//! let result = reconcile(parent, &left.into(), &right.into(), &*BuiltinTokenizer::Word);
+//!
+//! let conflicts = result.conflicts();
+//! assert_eq!(conflicts.len(), 1);
+//! assert_eq!(conflicts[0].base(), " Monday");
+//! assert_eq!(conflicts[0].left(), " Tuesday");
+//! assert_eq!(conflicts[0].right(), " Friday");
+//! ```
+//!
+//! How such regions are resolved can be changed with a `MergeStrategy`. The
+//! built-in `Interleave` (default), `Group`, `PreferLeft`, `PreferRight`, and
+//! `NewestWins` strategies are available, and any
+//! `Fn(&Conflict) -> Resolution` closure can be used as well:
+//!
+//! ```
+//! use reconcile_text::{reconcile_with_strategy, BuiltinTokenizer, PreferLeft};
+//!
+//! let parent = "The meeting is on Monday at noon.";
+//! let left = "The meeting is on Tuesday at noon.";
+//! let right = "The meeting is on Friday at noon!";
+//!
+//! let result = reconcile_with_strategy(
+//!     parent,
+//!     &left.into(),
+//!     &right.into(),
+//!     &*BuiltinTokenizer::Word,
+//!     &PreferLeft,
+//! );
+//! assert_eq!(result.apply().text(), "The meeting is on Tuesday at noon!");
+//! ```
+//!
 //! ## Change provenance
 //!
 //! Track which changes came from where:
@@ -136,20 +207,20 @@
 //!     &*BuiltinTokenizer::Word,
 //! );
 //!
-//! assert_eq!(
-//!     result.apply_with_history(),
-//!     vec![
-//!         SpanWithHistory::new("Merging text".to_string(), History::RemovedFromRight),
-//!         SpanWithHistory::new(
-//!             "With reconcile, merging documents".to_string(),
-//!             History::AddedFromRight
-//!         ),
-//!         SpanWithHistory::new(" ".to_string(), History::Unchanged),
-//!         SpanWithHistory::new("is".to_string(), History::Unchanged),
-//!         SpanWithHistory::new(" hard!".to_string(), History::RemovedFromLeft),
-//!         SpanWithHistory::new(" easy!".to_string(), History::AddedFromLeft),
-//!     ]
-//! );
+//! let expected = vec![
+//!     SpanWithHistory::new("Merging text".to_string(), History::RemovedFromRight),
+//!     SpanWithHistory::new(
+//!         "With reconcile, merging documents".to_string(),
+//!         History::AddedFromRight
+//!     ),
+//!     SpanWithHistory::new(" ".to_string(), History::Unchanged),
+//!     SpanWithHistory::new("is".to_string(), History::Unchanged),
+//!     SpanWithHistory::new(" hard!".to_string(), History::RemovedFromLeft),
+//!     SpanWithHistory::new(" easy!".to_string(), History::AddedFromLeft),
+//! ];
+//! let history = result.apply_with_history();
+//! assert_eq!(history.len(), expected.len());
+//! assert!(history.iter().zip(&expected).all(|(span, expected)| span.same_content(expected)));
 //! ```
 //!
 //! ## Compact change serialization
@@ -216,11 +287,34 @@ mod tokenizer;
 mod types;
 mod utils;
 
-pub use operation_transformation::{DiffError, EditedText, reconcile};
+pub use operation_transformation::{
+    DiffError, EditedText, Group, Interleave, MergeStrategy, NewestWins, PreferLeft, PreferRight,
+    Resolution, apply_diff_fuzzy, diff_with_history, reconcile, reconcile_chunked, reconcile_many,
+    reconcile_with_options, reconcile_with_strategy, word_diff,
+};
 pub use tokenizer::{BuiltinTokenizer, Tokenizer, token::Token};
+#[cfg(feature = "render")]
+pub use types::{ansi_renderer::AnsiRenderer, html_renderer::HtmlRenderer};
 pub use types::{
-    cursor_position::CursorPosition, history::History, number_or_text::NumberOrText, side::Side,
-    span_with_history::SpanWithHistory, text_with_cursors::TextWithCursors,
+    authorship::{AuthorSpan, Authorship},
+    conflict::Conflict,
+    conflict_markers::{ConflictMarkers, ConflictStyle},
+    cursor_id::CursorId,
+    cursor_position::CursorPosition,
+    diff_envelope::DiffEnvelope,
+    diff_options::{Degradation, DiffAlgorithm, DiffFallback, DiffOptions},
+    fuzzy_patch::{FuzzyPatch, HunkStatus, PatchHunk},
+    gravity::Gravity,
+    history::History,
+    invertible_change::InvertibleChange,
+    merge_report::{MergeReport, SideReport},
+    merge_trace::{CursorShift, MergeStep, MergeTrace},
+    number_or_text::NumberOrText,
+    selection::Selection,
+    side::{PeerIndex, Side},
+    span_with_history::SpanWithHistory,
+    text_with_cursors::TextWithCursors,
+    unified_diff::UnifiedDiff,
 };
 
 #[cfg(feature = "wasm")]
@@ -1,14 +1,27 @@
+mod apply_diff_fuzzy;
 mod diff_error;
+mod diff_with_history;
 mod edited_text;
+mod merge_strategy;
 mod operation;
+mod reconcile_chunked;
 mod utils;
-use std::fmt::Debug;
+use std::{fmt::Debug, hash::Hash};
 
+pub use apply_diff_fuzzy::apply_diff_fuzzy;
 pub use diff_error::DiffError;
+pub use diff_with_history::{diff_with_history, word_diff};
 pub use edited_text::EditedText;
+pub use merge_strategy::{
+    Group, Interleave, MergeStrategy, NewestWins, PreferLeft, PreferRight, Resolution,
+};
 pub use operation::Operation;
+pub use reconcile_chunked::reconcile_chunked;
 
-use crate::{Tokenizer, types::text_with_cursors::TextWithCursors};
+use crate::{
+    Tokenizer,
+    types::{diff_options::DiffOptions, text_with_cursors::TextWithCursors},
+};
 
 /// Given an `original` document and two concurrent edits to it,
 /// return a document containing all changes from both `left`
@@ -52,15 +65,152 @@ where
     left_operations.merge(right_operations)
 }
 
+/// Like `reconcile`, but regions which both `left` and `right` edited
+/// differently are resolved by `strategy` instead of interleaving the edits.
+///
+/// ```
+/// use reconcile_text::{reconcile, reconcile_with_strategy, BuiltinTokenizer, Group};
+///
+/// let parent = "the quick fox";
+/// let left = "the very quick brown fox";
+/// let right = "the slow fox";
+///
+/// // Interleaving keeps the insertions where they were made
+/// let result = reconcile(parent, &left.into(), &right.into(), &*BuiltinTokenizer::Word);
+/// assert_eq!(result.apply().text(), "the very slow brown fox");
+///
+/// // Grouping keeps each side's insertions together
+/// let result = reconcile_with_strategy(
+///     parent,
+///     &left.into(),
+///     &right.into(),
+///     &*BuiltinTokenizer::Word,
+///     &Group,
+/// );
+/// assert_eq!(result.apply().text(), "the very brown slow fox");
+/// ```
+#[must_use]
+pub fn reconcile_with_strategy<'a, T>(
+    original: &'a str,
+    left: &TextWithCursors,
+    right: &TextWithCursors,
+    tokenizer: &Tokenizer<T>,
+    strategy: &dyn MergeStrategy,
+) -> EditedText<'a, T>
+where
+    T: PartialEq + Clone + Debug,
+{
+    let left_operations = EditedText::from_strings_with_tokenizer(original, left, tokenizer);
+    let right_operations = EditedText::from_strings_with_tokenizer(original, right, tokenizer);
+
+    left_operations.merge_with_strategy(right_operations, tokenizer, strategy)
+}
+
+/// Like `reconcile`, but diffs `left` and `right` against `original` with the
+/// given `options`, for example, with a different `DiffAlgorithm` or a time
+/// budget. The shortcuts taken when a budget is exceeded are reported by
+/// `EditedText::degradations`.
+///
+/// The tokens have to be `Eq + Hash`, as they are mapped to integers before
+/// diffing. This also makes large merges faster than with `reconcile`, even
+/// with the default options.
+///
+/// ```
+/// use reconcile_text::{BuiltinTokenizer, DiffAlgorithm, DiffOptions, reconcile_with_options};
+///
+/// // Myers' algorithm would align the blank line and the `---` of the new
+/// // section with the existing ones, mangling the merge
+/// let parent = "# Log\n---\n---\nTODO\n\n## Day 1\n";
+/// let left = "# Log\nby Ann\n---\n---\nTODO\n\n## Day 0\n---\n## Day 1\n";
+/// let right = "# Log\n---\n---\n\n## Day 1\nRain\n";
+///
+/// let result = reconcile_with_options(
+///     parent,
+///     &left.into(),
+///     &right.into(),
+///     &*BuiltinTokenizer::Line,
+///     &DiffOptions::default().with_algorithm(DiffAlgorithm::Patience),
+/// );
+/// assert_eq!(
+///     result.apply().text(),
+///     "# Log\nby Ann\n---\n---\n\n## Day 0\n---\n## Day 1\nRain\n"
+/// );
+/// ```
+#[must_use]
+pub fn reconcile_with_options<'a, T>(
+    original: &'a str,
+    left: &TextWithCursors,
+    right: &TextWithCursors,
+    tokenizer: &Tokenizer<T>,
+    options: &DiffOptions,
+) -> EditedText<'a, T>
+where
+    T: Eq + Hash + Clone + Debug,
+{
+    let left_operations = EditedText::from_strings_with_options(original, left, tokenizer, options);
+    let right_operations =
+        EditedText::from_strings_with_options(original, right, tokenizer, options);
+
+    left_operations.merge(right_operations)
+}
+
+/// Given an `original` document and any number of concurrent edits to it,
+/// return a document containing the changes from all `forks`.
+///
+/// Unlike chaining `reconcile` calls, the operations of every fork are woven
+/// together in a single pass, so the result doesn't depend on which pair of
+/// forks gets merged first. The order of `forks` doesn't matter either, except
+/// when three or more forks insert different but overlapping text at the same
+/// place: which part of it is deduplicated can then depend on the order.
+/// Cursors from every fork are carried through.
+///
+/// The history labels the first fork's changes `*FromLeft` and those of every
+/// other fork `*FromRight`; `SpanWithHistory::peer` tells which fork it was.
+///
+/// ```
+/// use reconcile_text::{reconcile_many, BuiltinTokenizer};
+///
+/// let parent = "Buy milk";
+/// let forks = [
+///     "Buy oat milk".into(),
+///     "Buy milk and eggs".into(),
+///     "Please buy milk".into(),
+/// ];
+///
+/// let merged = reconcile_many(parent, &forks, &*BuiltinTokenizer::Word);
+/// assert_eq!(merged.apply().text(), "Please buy oat milk and eggs");
+/// ```
+#[must_use]
+pub fn reconcile_many<'a, T>(
+    original: &'a str,
+    forks: &[TextWithCursors],
+    tokenizer: &Tokenizer<T>,
+) -> EditedText<'a, T>
+where
+    T: PartialEq + Clone + Debug,
+{
+    let mut edits = forks
+        .iter()
+        .map(|fork| EditedText::from_strings_with_tokenizer(original, fork, tokenizer));
+
+    match edits.next() {
+        Some(first) => first.merge_many(edits.collect()),
+        None => EditedText::from_strings_with_tokenizer(original, &original.into(), tokenizer),
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use std::{fs, ops::Range, path::Path};
+    use std::{fs, ops::Range, path::Path, time::Duration};
 
     use pretty_assertions::assert_eq;
     use test_case::test_matrix;
 
     use super::*;
-    use crate::{BuiltinTokenizer, CursorPosition, types::text_with_cursors::TextWithCursors};
+    use crate::{
+        BuiltinTokenizer, Conflict, CursorPosition, Degradation, DiffAlgorithm, DiffFallback,
+        Gravity, History, Selection, Token, types::text_with_cursors::TextWithCursors,
+    };
 
     #[test]
     fn test_cursor_complex() {
@@ -68,27 +218,15 @@ mod test {
         let left = TextWithCursors::new(
             "this is really complex text for testing cursor positions".to_owned(),
             vec![
-                CursorPosition {
-                    id: 0,
-                    char_index: 8,
-                }, // after "this is "
-                CursorPosition {
-                    id: 1,
-                    char_index: 22,
-                }, // after "this is really complex text"
+                CursorPosition::new(0, 8),  // after "this is "
+                CursorPosition::new(1, 22), // after "this is really complex text"
             ],
         );
         let right = TextWithCursors::new(
             "that was some complex sample to test cursor movements".to_owned(),
             vec![
-                CursorPosition {
-                    id: 2,
-                    char_index: 5,
-                }, // after "that "
-                CursorPosition {
-                    id: 3,
-                    char_index: 29,
-                }, // after "some complex sample "
+                CursorPosition::new(2, 5),  // after "that "
+                CursorPosition::new(3, 29), // after "some complex sample "
             ],
         );
 
@@ -100,26 +238,876 @@ mod test {
         assert_eq!(
             merged.cursors(),
             vec![
-                CursorPosition {
-                    id: 2,
-                    char_index: 5
-                }, // unchanged
-                CursorPosition {
-                    id: 0,
-                    char_index: 9
-                }, // before "really"
-                CursorPosition {
-                    id: 1,
-                    char_index: 23
-                }, // inside of "s|ample" because "text" got replaced by "sample"
-                CursorPosition {
-                    id: 3,
-                    char_index: 30
-                }, // after "complex sample"
+                CursorPosition::new(2, 5),  // unchanged
+                CursorPosition::new(0, 9),  // before "really"
+                CursorPosition::new(1, 23), // inside of "s|ample" because "text" got replaced by "sample"
+                CursorPosition::new(3, 30), // after "complex sample"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_with_cursor_gravity() {
+        let cases = [
+            ("world", "hello world", 0, [0, 6, 6]),
+            ("one two", "one three two", 3, [3, 9, 3]),
+            ("one two", "one two three", 7, [7, 13, 7]),
+            ("one two", "one", 3, [3, 3, 3]),
+        ];
+
+        for (original, right, index, expected) in cases {
+            let gravities = [Gravity::Before, Gravity::After, Gravity::Legacy];
+            for (gravity, expected) in gravities.into_iter().zip(expected) {
+                let left = TextWithCursors::new(
+                    original.to_owned(),
+                    vec![CursorPosition::new(0, index).with_gravity(gravity)],
+                );
+
+                let merged =
+                    reconcile(original, &left, &right.into(), &*BuiltinTokenizer::Word).apply();
+                assert_eq!(
+                    merged.cursors(),
+                    vec![CursorPosition::new(0, expected).with_gravity(gravity)],
+                    "{gravity:?} cursor at {index} in {original:?} merged with {right:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reconcile_cursor_at_start_in_either_order() {
+        let original = "world";
+        let right = TextWithCursors::from("hello world");
+
+        for (gravity, expected) in [
+            (Gravity::Before, 0),
+            (Gravity::After, 6),
+            (Gravity::Legacy, 6),
+        ] {
+            let left = TextWithCursors::new(
+                original.to_owned(),
+                vec![CursorPosition::new(0, 0).with_gravity(gravity)],
+            );
+
+            for merged in [
+                reconcile(original, &left, &right, &*BuiltinTokenizer::Word).apply(),
+                reconcile(original, &right, &left, &*BuiltinTokenizer::Word).apply(),
+            ] {
+                assert_eq!(merged.text(), "hello world");
+                assert_eq!(
+                    merged.cursors(),
+                    vec![CursorPosition::new(0, expected).with_gravity(gravity)]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reconcile_default_cursors_keep_their_positions() {
+        // Pins where cursors without a gravity (`Gravity::Legacy`) end up, as
+        // they did before cursors had one
+        for (original, right, index, expected) in [
+            ("world", "hello world", 0, 6),
+            ("hello world", "hello dear world", 5, 5),
+            ("hello world", "hello world again", 11, 11),
+        ] {
+            let left =
+                TextWithCursors::new(original.to_owned(), vec![CursorPosition::new(0, index)]);
+
+            let merged =
+                reconcile(original, &left, &right.into(), &*BuiltinTokenizer::Word).apply();
+            assert_eq!(
+                merged.cursors(),
+                vec![CursorPosition::new(0, expected)],
+                "cursor at {index} in {original:?} merged with {right:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_reconcile_with_string_cursor_ids() {
+        let original = "Hello world";
+        let left = TextWithCursors::new(
+            "Hello beautiful world".to_owned(),
+            vec![
+                CursorPosition::named("alice", 6),
+                CursorPosition::new(usize::MAX, 21),
+            ],
+        )
+        .with_selections(vec![Selection::new("alice", 0, 5)]);
+        let right =
+            TextWithCursors::new("Hi world".to_owned(), vec![CursorPosition::named("bob", 2)]);
+
+        let merged = reconcile(original, &left, &right, &*BuiltinTokenizer::Word).apply();
+        assert_eq!(merged.text(), "Hi beautiful world");
+        assert_eq!(
+            merged.cursors(),
+            vec![
+                CursorPosition::named("bob", 2),
+                CursorPosition::named("alice", 3),
+                CursorPosition::new(usize::MAX, 18),
+            ]
+        );
+        assert_eq!(merged.selections(), vec![Selection::new("alice", 0, 0)]);
+    }
+
+    #[test]
+    fn test_reconcile_with_selections() {
+        let original = "The cat sat on the mat";
+        let left = TextWithCursors::new(
+            "The black cat sat on the mat".to_owned(),
+            vec![CursorPosition::new(0, 4)],
+        )
+        .with_selections(vec![Selection::new("left", 14, 10)]);
+        let right = TextWithCursors::from("The cat sat on the red mat").with_selections(vec![
+            Selection::new("right", 19, 26).with_gravity(Gravity::After),
+        ]);
+
+        let merged = reconcile(original, &left, &right, &*BuiltinTokenizer::Word).apply();
+        assert_eq!(merged.text(), "The black cat sat on the red mat");
+        assert_eq!(merged.cursors(), vec![CursorPosition::new(0, 4)]);
+        assert_eq!(
+            merged.selections(),
+            vec![
+                Selection::new("left", 14, 10),
+                Selection::new("right", 25, 32).with_gravity(Gravity::After),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_many_with_cursors() {
+        let original = "The cat sat on the mat";
+        let forks = [
+            TextWithCursors::new(
+                "The black cat sat on the mat".to_owned(),
+                vec![CursorPosition::new(0, 10)], // after "The black "
+            ),
+            TextWithCursors::new(
+                "The cat sat on the red mat".to_owned(),
+                vec![CursorPosition::new(1, 26)], // at the end
+            ),
+            TextWithCursors::new(
+                "The cat slept on the mat".to_owned(),
+                vec![CursorPosition::new(2, 0)], // at the beginning
+            ),
+        ];
+
+        let merged = reconcile_many(original, &forks, &*BuiltinTokenizer::Word).apply();
+        assert_eq!(merged.text(), "The black cat slept on the red mat");
+        assert_eq!(
+            merged.cursors(),
+            vec![
+                CursorPosition::new(2, 0),
+                CursorPosition::new(0, 10),
+                CursorPosition::new(1, 34),
             ]
         );
     }
 
+    #[test]
+    fn test_reconcile_many_with_forks_editing_the_same_words() {
+        let original = "The quick brown fox jumps over the dog";
+        let forks = [
+            TextWithCursors::new(
+                "The quick red fox jumps over the dog".to_owned(),
+                vec![CursorPosition::new(0, 13)], // after "The quick red"
+            ),
+            TextWithCursors::new(
+                "The very quick brown fox jumps over the dog".to_owned(),
+                vec![CursorPosition::new(1, 9)], // after "The very "
+            ),
+            TextWithCursors::new(
+                "The quick brown cat jumps over the dog".to_owned(),
+                vec![CursorPosition::new(2, 19)], // after "The quick brown cat"
+            ),
+            "The quick green fox leaps over the dog".into(),
+        ];
+
+        let (merged, history) =
+            reconcile_many(original, &forks, &*BuiltinTokenizer::Word).apply_with_all();
+        assert_eq!(
+            merged.text(),
+            "The very quick green red cat leaps over the dog"
+        );
+        assert_eq!(
+            merged.cursors(),
+            vec![
+                CursorPosition::new(1, 9),  // after "The very "
+                CursorPosition::new(0, 24), // after "The very quick green red"
+                CursorPosition::new(2, 28), // after "The very quick green red cat"
+            ]
+        );
+        assert_eq!(
+            history
+                .iter()
+                .filter(|span| span.history() != History::Unchanged)
+                .map(|span| (span.text(), span.history(), span.peer()))
+                .collect::<Vec<_>>(),
+            vec![
+                (" very".to_owned(), History::AddedFromRight, Some(1)),
+                (" brown".to_owned(), History::RemovedByBoth, None),
+                (" fox".to_owned(), History::RemovedFromRight, Some(2)),
+                (" green".to_owned(), History::AddedFromRight, Some(3)),
+                (" red".to_owned(), History::AddedFromLeft, Some(0)),
+                (" jumps".to_owned(), History::RemovedFromRight, Some(3)),
+                (" cat".to_owned(), History::AddedFromRight, Some(2)),
+                (" leaps".to_owned(), History::AddedFromRight, Some(3)),
+            ]
+        );
+
+        let mut reversed = forks.clone();
+        reversed.reverse();
+        assert_eq!(
+            reconcile_many(original, &reversed, &*BuiltinTokenizer::Word)
+                .apply()
+                .text(),
+            merged.text()
+        );
+    }
+
+    #[test]
+    fn test_reconcile_many_matches_reconcile_for_two_forks() {
+        let original = "Merging text is hard!";
+        let left = "Merging text is easy!".into();
+        let right = "With reconcile, merging documents is hard!".into();
+
+        let pairwise = reconcile(original, &left, &right, &*BuiltinTokenizer::Word);
+        let many = reconcile_many(original, &[left, right], &*BuiltinTokenizer::Word);
+
+        assert_eq!(many, pairwise);
+        assert_eq!(many.apply_with_history(), pairwise.apply_with_history());
+    }
+
+    #[test]
+    fn test_reconcile_many_is_independent_of_fork_order() {
+        const ORDERS: [[usize; 3]; 6] = [
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ];
+
+        let merge_in_every_order =
+            |original: &str, forks: [String; 3], tokenizer: BuiltinTokenizer| {
+                let results: Vec<String> = ORDERS
+                    .iter()
+                    .map(|order| {
+                        let permuted = order.map(|i| TextWithCursors::from(forks[i].as_str()));
+                        reconcile_many(original, &permuted, &*tokenizer)
+                            .apply()
+                            .text()
+                    })
+                    .collect();
+
+                assert!(
+                    results.iter().all(|result| *result == results[0]),
+                    "Merging {forks:?} into {original:?} with {tokenizer:?} depends on the order: \
+                 {results:?}"
+                );
+                results[0].clone()
+            };
+
+        assert_eq!(
+            merge_in_every_order(
+                "one two three four",
+                [
+                    "one 2 three four".into(),
+                    "one two three 4".into(),
+                    "zero one two three four".into(),
+                ],
+                BuiltinTokenizer::Word
+            ),
+            "zero one 2 three 4"
+        );
+        assert_eq!(
+            merge_in_every_order(
+                "lorem ipsumé  ",
+                ["c ipsumé a ".into(), "ipsum ".into(), "theipsumé a".into()],
+                BuiltinTokenizer::Word
+            ),
+            "cipsum theipsumé a "
+        );
+        assert_eq!(
+            merge_in_every_order(
+                "",
+                ["\n\n".into(), "\n".into(), String::new()],
+                BuiltinTokenizer::Markdown
+            ),
+            "\n\n"
+        );
+
+        // Short texts from a few overlapping words, so that the forks often
+        // insert the same text
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut random = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            usize::try_from(state % 64).unwrap()
+        };
+        let mut random_text = || {
+            let words = [" ", "a", "b", "ab", "a ", "\n", "# ", "\n\n"];
+            let length = random() % 7;
+            (0..length)
+                .map(|_| words[random() % words.len()])
+                .collect::<String>()
+        };
+
+        for tokenizer in [
+            BuiltinTokenizer::Character,
+            BuiltinTokenizer::Word,
+            BuiltinTokenizer::Line,
+            BuiltinTokenizer::Markdown,
+        ] {
+            for _ in 0..1000 {
+                let original = random_text();
+                let forks = [random_text(), random_text(), random_text()];
+                merge_in_every_order(&original, forks, tokenizer);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reconcile_markdown_newlines_in_either_order() {
+        let tokenizer = &*BuiltinTokenizer::Markdown;
+
+        for (original, left, right, expected) in [
+            ("", "a\nb\n", "a\n", "a\nb\n"),
+            ("", "\n\n", "\n", "\n\n"),
+            (" ", "\n ", "\n\n", "\n\n"),
+            ("", "\n\n", " \n\n", "\n \n\n"),
+        ] {
+            assert_eq!(
+                reconcile(original, &left.into(), &right.into(), tokenizer)
+                    .apply()
+                    .text(),
+                expected
+            );
+            assert_eq!(
+                reconcile(original, &right.into(), &left.into(), tokenizer)
+                    .apply()
+                    .text(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_reconcile_reports_conflicts() {
+        let parent = "árvíztűrő tükörfúrógép and more text here";
+        let left = "árvíztűrő fúrógép and more words here";
+        let right = "árvíztűrő gép and more text there";
+
+        let result = reconcile(
+            parent,
+            &left.into(),
+            &right.into(),
+            &*BuiltinTokenizer::Word,
+        );
+        let merged = result.apply().text();
+
+        let conflicts = result
+            .conflicts()
+            .iter()
+            .map(|conflict| {
+                (
+                    parent
+                        .chars()
+                        .skip(conflict.parent_range().start)
+                        .take(conflict.parent_range().len())
+                        .collect::<String>(),
+                    merged
+                        .chars()
+                        .skip(conflict.merged_range().start)
+                        .take(conflict.merged_range().len())
+                        .collect::<String>(),
+                    conflict.base().to_owned(),
+                    conflict.left().to_owned(),
+                    conflict.right().to_owned(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(merged, "árvíztűrő fúrógép gép and more words there");
+        assert_eq!(
+            conflicts,
+            vec![
+                (
+                    " tükörfúrógép".to_owned(),
+                    " fúrógép gép".to_owned(),
+                    " tükörfúrógép".to_owned(),
+                    " fúrógép".to_owned(),
+                    " gép".to_owned(),
+                ),
+                (
+                    " text here".to_owned(),
+                    " words there".to_owned(),
+                    " text here".to_owned(),
+                    " words here".to_owned(),
+                    " text there".to_owned(),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_many_without_forks() {
+        let merged = reconcile_many("unchanged", &[], &*BuiltinTokenizer::Word);
+        assert_eq!(merged.apply().text(), "unchanged");
+    }
+
+    #[test]
+    fn test_reconcile_with_partial_eq_tokens() {
+        // Neither `Eq` nor `Hash`
+        #[derive(Debug, Clone, PartialEq)]
+        struct Word(String);
+
+        let tokenizer = |text: &str| {
+            text.split_inclusive(' ')
+                .map(|word| Token::new(Word(word.to_owned()), word.to_owned(), false, false))
+                .collect::<Vec<_>>()
+        };
+
+        let merged = reconcile(
+            "the quick fox ",
+            &"the slow fox ".into(),
+            &"the quick red fox ".into(),
+            &tokenizer,
+        );
+        assert_eq!(merged.apply().text(), "the slow red fox ");
+
+        let forks = ["a quick fox ".into(), "the quick fox jumps ".into()];
+        let merged = reconcile_many("the quick fox ", &forks, &tokenizer);
+        assert_eq!(merged.apply().text(), "a quick fox jumps ");
+    }
+
+    #[test]
+    fn test_reconcile_compares_tokens_with_partial_eq() {
+        // Equal regardless of case, but formatted as is
+        #[derive(Debug, Clone)]
+        struct CaseInsensitive(String);
+
+        impl PartialEq for CaseInsensitive {
+            fn eq(&self, other: &Self) -> bool {
+                self.0.eq_ignore_ascii_case(&other.0)
+            }
+        }
+
+        let tokenizer = |text: &str| {
+            text.split_inclusive(' ')
+                .map(|word| {
+                    Token::new(
+                        CaseInsensitive(word.to_owned()),
+                        word.to_owned(),
+                        false,
+                        false,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let merged = reconcile(
+            "the quick fox ",
+            &"THE QUICK FOX jumps ".into(),
+            &"the slow fox ".into(),
+            &tokenizer,
+        );
+        assert_eq!(merged.apply().text(), "the slow fox jumps ");
+    }
+
+    #[test]
+    fn test_reconcile_with_strategy_moves_cursors() {
+        let original = "Hello world, how are you?";
+        let left = TextWithCursors::new(
+            "Hello brave new world, how are you?".to_owned(),
+            vec![CursorPosition::new(0, 11), CursorPosition::new(1, 35)],
+        );
+        let right = TextWithCursors::new(
+            "Hello everyone, how are you doing?".to_owned(),
+            vec![CursorPosition::new(2, 14), CursorPosition::new(3, 34)],
+        );
+
+        let text = |_: &Conflict| Resolution::Text(" there".to_owned());
+        let strategies: [(&dyn MergeStrategy, &str, [usize; 4]); 5] = [
+            (
+                &Interleave,
+                "Hello brave new everyone, how are you doing?",
+                [11, 33, 24, 44],
+            ),
+            (
+                &Group,
+                "Hello brave new everyone, how are you doing?",
+                [11, 33, 24, 44],
+            ),
+            (
+                &PreferLeft,
+                "Hello brave new world, how are you doing?",
+                [11, 30, 5, 41],
+            ),
+            (
+                &PreferRight,
+                "Hello everyone, how are you doing?",
+                [5, 23, 14, 34],
+            ),
+            (&text, "Hello there how are you doing?", [11, 19, 11, 30]),
+        ];
+
+        for (strategy, expected_text, expected_cursors) in strategies {
+            let merged = reconcile_with_strategy(
+                original,
+                &left,
+                &right,
+                &*BuiltinTokenizer::Word,
+                strategy,
+            )
+            .apply();
+
+            let mut cursors = merged.cursors();
+            cursors.sort_by(|a, b| a.id().cmp(b.id()));
+
+            assert_eq!(merged.text(), expected_text);
+            assert_eq!(
+                cursors
+                    .iter()
+                    .map(CursorPosition::char_index)
+                    .collect::<Vec<_>>(),
+                expected_cursors
+            );
+        }
+    }
+
+    #[test]
+    fn test_reconcile_with_strategy_keeps_conflicts() {
+        let original = "the quick fox";
+        let left = "the very quick brown fox";
+        let right = "the slow fox";
+
+        let merged = reconcile_with_strategy(
+            original,
+            &left.into(),
+            &right.into(),
+            &*BuiltinTokenizer::Word,
+            &Group,
+        );
+        let text = merged.apply().text();
+
+        assert_eq!(text, "the very brown slow fox");
+        assert_eq!(merged.conflicts().len(), 1);
+        assert_eq!(
+            &text[merged.conflicts()[0].merged_range()],
+            " very brown slow"
+        );
+    }
+
+    #[test]
+    fn test_merge_many_with_newest_wins() {
+        let original = "a b c d";
+        let forks = ["a x c d", "a y c d", "a b c z", "a z c d"];
+        let edits = forks
+            .iter()
+            .map(|fork| EditedText::from_strings(original, &(*fork).into()))
+            .collect::<Vec<_>>();
+
+        let mut edits = edits.into_iter();
+        let merged = edits.next().unwrap().merge_many_with_strategy(
+            edits.collect(),
+            &*BuiltinTokenizer::Word,
+            &NewestWins::new(vec![3, 5, 1, 4]),
+        );
+
+        assert_eq!(merged.apply().text(), "a y c z");
+
+        // The newest timestamp belongs to a side which isn't merged
+        let merged = reconcile_with_strategy(
+            original,
+            &forks[0].into(),
+            &forks[1].into(),
+            &*BuiltinTokenizer::Word,
+            &NewestWins::new(vec![3, 1, 5]),
+        );
+        assert_eq!(merged.apply().text(), "a x c d");
+    }
+
+    #[test]
+    fn test_reconcile_moved_paragraph_keeps_edits() {
+        let original = "# Intro\nWe meet every Monday at the office to plan the week.\n# Outro\nThat's \
+                        all for now.";
+        let left = TextWithCursors::new(
+            "# Outro\nThat's all for now.\n# Intro\nWe meet every Monday at the office to plan the \
+             week."
+                .to_owned(),
+            vec![CursorPosition::new(0, 5)],
+        );
+        let right = TextWithCursors::new(
+            "# Intro\nWe meet every Tuesday at the office to plan the week.\n# Outro\nThat's all \
+             for now."
+                .to_owned(),
+            vec![CursorPosition::new(1, 25)],
+        );
+
+        let expected = TextWithCursors::new(
+            "# Outro\nThat's all for now.\n# Intro\nWe meet every Tuesday at the office to plan \
+             the week."
+                .to_owned(),
+            vec![CursorPosition::new(0, 5), CursorPosition::new(1, 53)],
+        );
+
+        let merged = reconcile(original, &left, &right, &*BuiltinTokenizer::Word).apply();
+        assert_eq!(merged, expected);
+
+        let merged = reconcile(original, &right, &left, &*BuiltinTokenizer::Word).apply();
+        assert_eq!(merged, expected);
+    }
+
+    #[test]
+    fn test_reconcile_same_move_on_both_sides() {
+        let original = "First paragraph stays here.\nSecond paragraph gets moved to the end.\nThird \
+                        one.";
+        let moved = "First paragraph stays here.\nThird one.\nSecond paragraph gets moved to the \
+                     end.";
+        let edited_and_moved = "First paragraph stays here.\nThird one.\nSecond paragraph gets \
+                                moved to the very end.";
+
+        let merged = reconcile(
+            original,
+            &moved.into(),
+            &edited_and_moved.into(),
+            &*BuiltinTokenizer::Word,
+        );
+        assert_eq!(merged.apply().text(), edited_and_moved);
+    }
+
+    #[test_matrix(
+        [BuiltinTokenizer::Word, BuiltinTokenizer::Line],
+        [("Alpha", "NEW Alpha"), ("here.", "here. NEW")]
+    )]
+    fn test_reconcile_inserts_at_edges_of_moved_block(
+        tokenizer: BuiltinTokenizer,
+        (before, after): (&str, &str),
+    ) {
+        let alpha = "Alpha paragraph is the first one in here.\n";
+        let bravo = "Bravo paragraph is the second one in here.\n";
+        let charlie = "Charlie paragraph is the last one of them all.\n";
+
+        for (original, moved) in [
+            (
+                format!("{alpha}{bravo}{charlie}"),
+                format!("{bravo}{charlie}{alpha}"),
+            ),
+            (
+                format!("{bravo}{charlie}{alpha}"),
+                format!("{alpha}{bravo}{charlie}"),
+            ),
+        ] {
+            let edited = original.replace(before, after);
+            let expected = moved.replace(before, after);
+
+            for forks in [
+                [moved.as_str().into(), edited.as_str().into()],
+                [edited.as_str().into(), moved.as_str().into()],
+            ] {
+                let merged = reconcile_many(&original, &forks, &*tokenizer);
+                assert_eq!(merged.apply().text(), expected);
+            }
+        }
+    }
+
+    #[test_matrix(
+        [BuiltinTokenizer::Word, BuiltinTokenizer::Line],
+        [("moved", "shuffled"), ("last", "final")]
+    )]
+    fn test_reconcile_many_moving_same_block_twice(
+        tokenizer: BuiltinTokenizer,
+        (before, after): (&str, &str),
+    ) {
+        let alpha = "Alpha paragraph is the first one in here.\n";
+        let bravo = "Bravo paragraph is the one which gets moved around.\n";
+        let charlie = "Charlie paragraph is the last one of them all.\n";
+
+        let original = format!("{alpha}{bravo}{charlie}");
+        let edited = original.replace(before, after);
+
+        for moved in [
+            format!("{bravo}{alpha}{charlie}"),
+            format!("{alpha}{charlie}{bravo}"),
+        ] {
+            let expected = moved.replace(before, after);
+
+            for rotation in 0..3 {
+                let mut forks: [TextWithCursors; 3] = [
+                    moved.as_str().into(),
+                    moved.as_str().into(),
+                    edited.as_str().into(),
+                ];
+                forks.rotate_left(rotation);
+
+                let merged = reconcile_many(&original, &forks, &*tokenizer);
+                assert_eq!(merged.apply().text(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reconcile_with_diff_algorithms() {
+        let original = "# Log\n---\n---\nTODO\n\n## Day 1\n";
+        let left = "# Log\nby Ann\n---\n---\nTODO\n\n## Day 0\n---\n## Day 1\n";
+        let right = "# Log\n---\n---\n\n## Day 1\nRain\n";
+
+        for algorithm in [DiffAlgorithm::Patience, DiffAlgorithm::Histogram] {
+            let result = reconcile_with_options(
+                original,
+                &left.into(),
+                &right.into(),
+                &*BuiltinTokenizer::Line,
+                &DiffOptions::default().with_algorithm(algorithm),
+            );
+
+            assert_eq!(
+                result.apply().text(),
+                "# Log\nby Ann\n---\n---\n\n## Day 0\n---\n## Day 1\nRain\n",
+                "{algorithm:?}"
+            );
+        }
+    }
+
+    #[test_matrix(
+        [DiffAlgorithm::Myers, DiffAlgorithm::Patience, DiffAlgorithm::Histogram],
+        [BuiltinTokenizer::Word, BuiltinTokenizer::Line]
+    )]
+    fn test_diff_algorithms_reproduce_updated_text(
+        algorithm: DiffAlgorithm,
+        tokenizer: BuiltinTokenizer,
+    ) {
+        let text = fs::read_to_string("tests/resources/room_with_a_view.txt").unwrap();
+        let original = &text[..5_000];
+        let updated = text[1_000..6_000].replace("the", "a");
+
+        let edited = EditedText::from_strings_with_options(
+            original,
+            &updated.as_str().into(),
+            &*tokenizer,
+            &DiffOptions::default().with_algorithm(algorithm),
+        );
+        assert_eq!(edited.apply().text(), updated);
+    }
+
+    #[test_matrix(
+        [DiffAlgorithm::Myers, DiffAlgorithm::Patience, DiffAlgorithm::Histogram],
+        [DiffFallback::LineGranularity, DiffFallback::CapEditDistance]
+    )]
+    fn test_budgeted_diff_reproduces_updated_text(
+        algorithm: DiffAlgorithm,
+        fallback: DiffFallback,
+    ) {
+        let text = fs::read_to_string("tests/resources/room_with_a_view.txt").unwrap();
+        let original = &text[..5_000];
+        let updated = text[1_000..6_000].replace("the", "a");
+
+        let edited = EditedText::from_strings_with_options(
+            original,
+            &updated.as_str().into(),
+            &*BuiltinTokenizer::Word,
+            &DiffOptions::default()
+                .with_algorithm(algorithm)
+                .with_max_edit_distance(8)
+                .with_fallback(fallback),
+        );
+
+        assert_eq!(edited.apply().text(), updated);
+        assert_eq!(
+            edited.degradations(),
+            match fallback {
+                DiffFallback::LineGranularity => {
+                    [
+                        Degradation::LineGranularity,
+                        Degradation::CappedEditDistance,
+                    ]
+                    .as_slice()
+                }
+                DiffFallback::CapEditDistance => [Degradation::CappedEditDistance].as_slice(),
+            }
+        );
+    }
+
+    #[test_matrix([DiffAlgorithm::Patience, DiffAlgorithm::Histogram])]
+    fn test_aligning_diff_caps_edit_distance(algorithm: DiffAlgorithm) {
+        let text = fs::read_to_string("tests/resources/room_with_a_view.txt").unwrap();
+        let original = text.lines().take(1_000).collect::<Vec<_>>().join("\n");
+        let updated = original.lines().rev().collect::<Vec<_>>().join("\n");
+
+        let edited = EditedText::from_strings_with_options(
+            original.as_str(),
+            &updated.as_str().into(),
+            &*BuiltinTokenizer::Line,
+            &DiffOptions::default()
+                .with_algorithm(algorithm)
+                .with_max_edit_distance(10)
+                .with_fallback(DiffFallback::CapEditDistance),
+        );
+
+        assert_eq!(edited.apply().text(), updated);
+        assert_eq!(edited.degradations(), [Degradation::CappedEditDistance]);
+    }
+
+    #[test]
+    fn test_move_detection_stops_at_deadline() {
+        let original = "Alpha paragraph is the first one in here.\n\
+            Bravo paragraph is the second one in here.\n";
+        let updated = "Bravo paragraph is the second one in here.\n\
+            Alpha paragraph is the first one in here.\n";
+
+        let edited = EditedText::from_strings_with_options(
+            original,
+            &updated.into(),
+            &*BuiltinTokenizer::Word,
+            &DiffOptions::default().with_timeout(Duration::ZERO),
+        );
+
+        assert_eq!(edited.apply().text(), updated);
+        assert_eq!(
+            edited.degradations(),
+            [
+                Degradation::LineGranularity,
+                Degradation::CappedEditDistance,
+                Degradation::IncompleteMoveDetection,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_reports_degradations() {
+        let original = "The first line is here.\nThe second one.\nThe last line is here.\n";
+        let left = "The first row was here.\nThe second one.\nThe last row was here.\n";
+        let right = "The first line is here.\nThe other one.\nThe last line is here.\n";
+
+        let options = DiffOptions::default().with_max_edit_distance(4);
+        let result = reconcile_with_options(
+            original,
+            &left.into(),
+            &right.into(),
+            &*BuiltinTokenizer::Word,
+            &options,
+        );
+
+        assert_eq!(
+            result.apply().text(),
+            "The first row was here.\nThe other one.\nThe last row was here.\n"
+        );
+        assert_eq!(result.degradations(), [Degradation::LineGranularity]);
+
+        let result = reconcile_with_options(
+            original,
+            &left.into(),
+            &right.into(),
+            &*BuiltinTokenizer::Word,
+            &DiffOptions::default(),
+        );
+        assert!(result.degradations().is_empty());
+    }
+
     #[ignore = "expensive to run, only run in CI"]
     #[test_matrix( [
         "pride_and_prejudice.txt",
@@ -0,0 +1,414 @@
+use std::{fmt::Debug, iter, ops::Range};
+
+use crate::{
+    EditedText, InvertibleChange, Tokenizer,
+    operation_transformation::reconcile,
+    types::fuzzy_patch::{FuzzyPatch, HunkStatus, PatchHunk},
+};
+
+/// The maximum number of characters of the original text kept on each side of
+/// a hunk to find where it belongs
+const CONTEXT_LENGTH: usize = 16;
+
+/// Apply a `patch` (as returned by `EditedText::to_patch`) to `current`, which
+/// has drifted from the text the patch was made against, without needing that
+/// text. Like diff-match-patch, each hunk is located by searching `current`
+/// for the text it replaces and its context near where the previous hunks
+/// ended up.
+///
+/// Hunks found with their whole context apply cleanly. Hunks whose context
+/// has only partly survived are fuzzed: merged with the changes around them by
+/// the 3-way merge. Hunks replacing text which has since changed, or with
+/// less than half of their context left, are rejected and left out.
+///
+/// ```
+/// use reconcile_text::{BuiltinTokenizer, EditedText, apply_diff_fuzzy};
+///
+/// let base = "The quick brown fox jumps over the lazy dog.";
+/// let patch = EditedText::from_strings(base, &"The quick brown fox leaps over the lazy dog.".into())
+///     .to_patch();
+///
+/// // `from_diff` can't apply the diff to a shorter text
+/// let current = "The quick fox jumps over the dog.";
+/// let diff = EditedText::from_strings(base, &"The quick brown fox leaps over the lazy dog.".into())
+///     .to_diff()
+///     .unwrap();
+/// assert!(EditedText::from_diff(current, diff, &*BuiltinTokenizer::Word).is_err());
+///
+/// let patched = apply_diff_fuzzy(current, &patch, &*BuiltinTokenizer::Word);
+/// assert_eq!(patched.text(), "The quick fox leaps over the dog.");
+/// assert!(patched.is_complete());
+/// ```
+#[must_use]
+pub fn apply_diff_fuzzy<T>(
+    current: &str,
+    patch: &[PatchHunk],
+    tokenizer: &Tokenizer<T>,
+) -> FuzzyPatch
+where
+    T: PartialEq + Clone + Debug,
+{
+    let mut text = current.to_owned();
+    let mut statuses = Vec::with_capacity(patch.len());
+
+    // How many characters later the text after the last applied hunk is in
+    // `text` than in the base
+    let mut shift: isize = 0;
+
+    for hunk in patch {
+        let pattern = hunk.pattern();
+        let pattern_start = hunk.base_range.start - hunk.before.chars().count();
+        let expected = pattern_start
+            .saturating_add_signed(shift)
+            .min(text.chars().count());
+
+        // The replaced text and how much of the pattern follows it
+        let (status, replaced, replacement, trailing_length) = if let Some(start) =
+            find_nearest(&text, &pattern, expected)
+        {
+            let deleted_start = start + hunk.before.chars().count();
+            let deleted = deleted_start..deleted_start + hunk.deleted.chars().count();
+            let after_length = hunk.after.chars().count();
+            (
+                HunkStatus::Clean,
+                deleted,
+                hunk.inserted.clone(),
+                after_length,
+            )
+        } else if let Some(region) = locate_fuzzy(&text, hunk, expected, tokenizer) {
+            let updated = format!("{}{}{}", hunk.before, hunk.inserted, hunk.after);
+            let current_region: String =
+                text.chars().skip(region.start).take(region.len()).collect();
+            let merged = reconcile(&pattern, &updated.into(), &current_region.into(), tokenizer)
+                .apply()
+                .text();
+
+            (HunkStatus::Fuzzed, region, merged, 0)
+        } else {
+            statuses.push(HunkStatus::Rejected);
+            continue;
+        };
+
+        let pattern_end = hunk.base_range.end + hunk.after.chars().count();
+        let text_end = replaced.start + replacement.chars().count() + trailing_length;
+        shift = signed(text_end) - signed(pattern_end);
+
+        replace_chars(&mut text, replaced, &replacement);
+        statuses.push(status);
+    }
+
+    FuzzyPatch { text, statuses }
+}
+
+/// Split `diff` of `base` into hunks of the changes between retained text,
+/// each with up to `CONTEXT_LENGTH` characters of the retained text around
+/// it, see `EditedText::to_patch`
+pub(crate) fn split_into_hunks(base: &str, diff: Vec<InvertibleChange>) -> Vec<PatchHunk> {
+    let mut hunks: Vec<PatchHunk> = Vec::new();
+    let mut base_index = 0;
+    let mut is_in_hunk = false;
+
+    for change in diff {
+        if let InvertibleChange::Retain(length) = change {
+            base_index += length;
+            is_in_hunk = false;
+            continue;
+        }
+
+        if !is_in_hunk {
+            hunks.push(PatchHunk {
+                base_range: base_index..base_index,
+                before: String::new(),
+                deleted: String::new(),
+                inserted: String::new(),
+                after: String::new(),
+            });
+            is_in_hunk = true;
+        }
+
+        let hunk = hunks.last_mut().unwrap();
+        match change {
+            InvertibleChange::Insert(text) => hunk.inserted.push_str(&text),
+            InvertibleChange::Delete(text) => {
+                base_index += text.chars().count();
+                hunk.base_range.end = base_index;
+                hunk.deleted.push_str(&text);
+            }
+            InvertibleChange::Retain(..) => unreachable!(),
+        }
+    }
+
+    // The context stops at the neighbouring hunks, as they might not apply
+    let chars: Vec<char> = base.chars().collect();
+    let starts: Vec<usize> = hunks.iter().map(|hunk| hunk.base_range.start).collect();
+    let ends: Vec<usize> = hunks.iter().map(|hunk| hunk.base_range.end).collect();
+    for (index, hunk) in hunks.iter_mut().enumerate() {
+        let previous_end = index.checked_sub(1).map_or(0, |previous| ends[previous]);
+        let next_start = starts.get(index + 1).copied().unwrap_or(chars.len());
+
+        let before_start = hunk
+            .base_range
+            .start
+            .saturating_sub(CONTEXT_LENGTH)
+            .max(previous_end);
+        let after_end = (hunk.base_range.end + CONTEXT_LENGTH).min(next_start);
+        hunk.before = chars[before_start..hunk.base_range.start].iter().collect();
+        hunk.after = chars[hunk.base_range.end..after_end].iter().collect();
+    }
+
+    hunks
+}
+
+/// The character index of the occurrence of `pattern` in `text` closest to
+/// `expected`. Occurrences may overlap, unlike with `str::match_indices`.
+fn find_nearest(text: &str, pattern: &str, expected: usize) -> Option<usize> {
+    text.char_indices()
+        .map(|(byte_index, _)| byte_index)
+        .chain(iter::once(text.len()))
+        .enumerate()
+        .filter(|(_, byte_index)| text[*byte_index..].starts_with(pattern))
+        .map(|(char_index, _)| char_index)
+        .min_by_key(|start| start.abs_diff(expected))
+}
+
+/// The character range of `text` around `expected` which corresponds to the
+/// text `hunk` replaces together with its context, if the replaced text is
+/// there unchanged and at least half of the context is found around it
+fn locate_fuzzy<T>(
+    text: &str,
+    hunk: &PatchHunk,
+    expected: usize,
+    tokenizer: &Tokenizer<T>,
+) -> Option<Range<usize>>
+where
+    T: PartialEq + Clone + Debug,
+{
+    let before_length = hunk.before.chars().count();
+    let deleted_length = hunk.deleted.chars().count();
+    let context_length = before_length + hunk.after.chars().count();
+    let pattern = hunk.pattern();
+    let pattern_length = pattern.chars().count();
+
+    let window_start = expected.saturating_sub(pattern_length);
+    let window: String = text
+        .chars()
+        .skip(window_start)
+        .take(3 * pattern_length)
+        .collect();
+
+    // The position of each character of the pattern in the window, unless
+    // it's missing from there
+    let mut positions: Vec<Option<usize>> = Vec::with_capacity(pattern_length);
+    let mut window_index = 0;
+    let diff = EditedText::from_strings_with_tokenizer(pattern.as_str(), &window.into(), tokenizer)
+        .to_invertible_diff();
+    for change in diff {
+        match change {
+            InvertibleChange::Retain(length) => {
+                positions.extend((window_index..window_index + length).map(Some));
+                window_index += length;
+            }
+            InvertibleChange::Insert(text) => window_index += text.chars().count(),
+            InvertibleChange::Delete(text) => {
+                positions.extend(iter::repeat_n(None, text.chars().count()));
+            }
+        }
+    }
+
+    let deleted = &positions[before_length..before_length + deleted_length];
+    let is_deleted_intact = deleted.iter().all(Option::is_some)
+        && deleted
+            .windows(2)
+            .all(|pair| pair[1] == pair[0].map(|position| position + 1));
+
+    // An insertion needs a neighbouring character to be placed next to
+    let is_anchored = deleted_length > 0
+        || positions[..before_length]
+            .last()
+            .is_some_and(Option::is_some)
+        || positions.get(before_length).is_some_and(Option::is_some);
+
+    let found_context_length = positions[..before_length]
+        .iter()
+        .chain(&positions[before_length + deleted_length..])
+        .filter(|position| position.is_some())
+        .count();
+
+    if !is_deleted_intact || !is_anchored || 2 * found_context_length < context_length {
+        return None;
+    }
+
+    let first = positions.iter().flatten().next()?;
+    let last = positions.iter().flatten().next_back()?;
+    Some(window_start + first..window_start + last + 1)
+}
+
+fn replace_chars(text: &mut String, range: Range<usize>, replacement: &str) {
+    let byte_index = |index: usize| {
+        text.char_indices()
+            .nth(index)
+            .map_or(text.len(), |(byte_index, _)| byte_index)
+    };
+    let byte_range = byte_index(range.start)..byte_index(range.end);
+
+    text.replace_range(byte_range, replacement);
+}
+
+fn signed(value: usize) -> isize {
+    isize::try_from(value).expect("Text length fits into isize")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::BuiltinTokenizer;
+
+    fn apply(base: &str, updated: &str, current: &str) -> (String, Vec<HunkStatus>) {
+        let patch = EditedText::from_strings(base, &updated.into()).to_patch();
+        let patched = apply_diff_fuzzy(current, &patch, &*BuiltinTokenizer::Word);
+
+        (patched.text().to_owned(), patched.statuses().to_vec())
+    }
+
+    #[test]
+    fn test_split_into_hunks() {
+        let patch = EditedText::from_strings(
+            "one two three four five six seven eight nine ten",
+            &"one 2 three four five six seven eight nine 10".into(),
+        )
+        .to_patch();
+
+        assert_eq!(
+            patch
+                .iter()
+                .map(|hunk| (hunk.before(), hunk.deleted(), hunk.inserted(), hunk.after()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("one", " two", " 2", " three four five"),
+                ("seven eight nine", " ten", " 10", ""),
+            ]
+        );
+        assert_eq!(patch[1].base_range(), 44..48);
+    }
+
+    #[test]
+    fn test_apply_diff_fuzzy_clean() {
+        let text = "one two three four five six seven eight nine ten";
+        assert_eq!(
+            apply(
+                text,
+                "one two three 4 five six seven eight nine ten",
+                "one two three four five six seven eight nine ten!"
+            ),
+            (
+                "one two three 4 five six seven eight nine ten!".to_owned(),
+                vec![HunkStatus::Clean]
+            )
+        );
+    }
+
+    #[test]
+    fn test_apply_diff_fuzzy_shifted() {
+        let base = "one two three four five six seven eight nine ten";
+        assert_eq!(
+            apply(
+                base,
+                "one two three four five six seven 8 nine ten",
+                "zero one two three four five six seven eight nine ten"
+            ),
+            (
+                "zero one two three four five six seven 8 nine ten".to_owned(),
+                vec![HunkStatus::Clean]
+            )
+        );
+    }
+
+    #[test]
+    fn test_apply_diff_fuzzy_fuzzed() {
+        let base = "one two three four five six seven eight nine ten";
+        assert_eq!(
+            apply(
+                base,
+                "one two three four five 6 seven eight nine ten",
+                "one two three FOUR five six seven eight nine ten"
+            ),
+            (
+                "one two three FOUR five 6 seven eight nine ten".to_owned(),
+                vec![HunkStatus::Fuzzed]
+            )
+        );
+    }
+
+    #[test]
+    fn test_apply_diff_fuzzy_rejected() {
+        let base = "one two three four five six seven eight nine ten";
+        assert_eq!(
+            apply(
+                base,
+                "1 two three four five six seven eight nine 10",
+                "one two three four five six seven eight nine 9+1"
+            ),
+            (
+                "1 two three four five six seven eight nine 9+1".to_owned(),
+                vec![HunkStatus::Clean, HunkStatus::Rejected]
+            )
+        );
+
+        // Too little of the context is left to find where the hunk belongs
+        assert_eq!(
+            apply(
+                base,
+                "one two three four 5 six seven eight nine ten",
+                "five"
+            ),
+            ("five".to_owned(), vec![HunkStatus::Rejected])
+        );
+    }
+
+    #[test]
+    fn test_find_nearest_overlapping() {
+        assert_eq!(find_nearest("bbb", "bb", 1), Some(1));
+        assert_eq!(find_nearest("aaa", "", 3), Some(3));
+        assert_eq!(find_nearest("日本日本日", "日本日", 2), Some(2));
+    }
+
+    #[test]
+    fn test_apply_diff_fuzzy_to_base_reproduces_updated() {
+        let mut cases = vec![
+            ("👍bbdéx y".to_owned(), "bb日本x yb👍déy".to_owned()),
+            ("foobarb".to_owned(), "ffoobooa\n👍babbarb".to_owned()),
+        ];
+
+        // Random strings made of few characters, so that they have a lot of
+        // repeated (and overlapping) runs
+        let alphabet = ['a', 'b', 'é', '日', '👍', ' ', '\n'];
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut random_string = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let length = usize::try_from(state % 12).unwrap();
+            (0..length)
+                .map(|index| alphabet[usize::try_from((state >> (4 * index)) % 7).unwrap()])
+                .collect::<String>()
+        };
+        cases.extend((0..2_000).map(|_| (random_string(), random_string())));
+
+        for tokenizer in [BuiltinTokenizer::Character, BuiltinTokenizer::Word] {
+            for (base, updated) in &cases {
+                let patch = EditedText::from_strings_with_tokenizer(
+                    base.as_str(),
+                    &updated.as_str().into(),
+                    &*tokenizer,
+                )
+                .to_patch();
+                let patched = apply_diff_fuzzy(base, &patch, &*tokenizer);
+
+                assert_eq!(patched.text(), updated, "patching {base:?}");
+                assert!(patched.is_complete());
+            }
+        }
+    }
+}
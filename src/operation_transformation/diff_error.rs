@@ -23,4 +23,106 @@ pub enum DiffError {
         /// The value that caused the overflow
         value: usize,
     },
+
+    /// The second of the edits to compose doesn't start from the text the
+    /// first one results in
+    #[error(
+        "Cannot compose edits: the first one results in {expected} characters, but the second \
+         one starts from {actual} characters"
+    )]
+    NotConsecutive {
+        /// The length of the text the first edit results in
+        expected: usize,
+        /// The length of the original text of the second edit
+        actual: usize,
+    },
+
+    /// The text an invertible diff deletes doesn't match the original text
+    #[error(
+        "Invalid diff: expected to delete `{expected}` at position {position}, but the original \
+         text has `{actual}`"
+    )]
+    DeletedTextMismatch {
+        /// The position where the delete starts
+        position: usize,
+        /// The text the diff deletes
+        expected: String,
+        /// The text of the original at the position
+        actual: String,
+    },
+
+    /// The envelope of a diff has a format version this version of the
+    /// library doesn't know
+    #[error("Unknown diff format version {version}, expected {supported}")]
+    UnknownVersion {
+        /// The version of the envelope
+        version: u32,
+        /// The only version which is supported
+        supported: u32,
+    },
+
+    /// The diff was made with a different tokenizer than the one it's
+    /// applied with
+    #[error("The diff was made with the `{expected}` tokenizer, but is applied with `{actual}`")]
+    TokenizerMismatch {
+        /// The id of the tokenizer the diff was made with
+        expected: String,
+        /// The id of the tokenizer the diff is applied with
+        actual: String,
+    },
+
+    /// The diff was made against a different text than the one it's applied to
+    #[error(
+        "The diff was made against a text of {expected_length} characters with hash \
+         {expected_hash:016x}, but is applied to {actual_length} characters with hash \
+         {actual_hash:016x}"
+    )]
+    BaseMismatch {
+        /// The length of the original text the diff was made against
+        expected_length: usize,
+        /// The length of the text the diff is applied to
+        actual_length: usize,
+        /// The hash of the original text the diff was made against
+        expected_hash: u64,
+        /// The hash of the text the diff is applied to
+        actual_hash: u64,
+    },
+
+    /// The binary encoding of a diff ends in the middle of an item
+    #[error("Invalid diff bytes: unexpected end of input at byte {position}")]
+    TruncatedBytes {
+        /// The length of the input
+        position: usize,
+    },
+
+    /// The binary encoding of a diff holds an invalid item
+    #[error("Invalid diff bytes: {reason} at byte {position}")]
+    CorruptedBytes {
+        /// The byte offset of the invalid item
+        position: usize,
+        /// What is wrong with the item
+        reason: String,
+    },
+
+    /// A unified diff can't be parsed or doesn't match the original text
+    #[error("Invalid patch: {reason} at line {line}")]
+    InvalidPatch {
+        /// The 1-based line number in the patch
+        line: usize,
+        /// What is wrong with the line
+        reason: String,
+    },
+
+    /// The authorship given for the original text covers a different number
+    /// of characters than the text has
+    #[error(
+        "The authorship covers {actual_length} characters, but the original text has \
+         {expected_length}"
+    )]
+    AuthorshipMismatch {
+        /// The length of the original text in characters
+        expected_length: usize,
+        /// The number of characters the authorship covers
+        actual_length: usize,
+    },
 }
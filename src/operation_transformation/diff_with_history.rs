@@ -0,0 +1,153 @@
+use std::fmt::Debug;
+
+use crate::{EditedText, History, SpanWithHistory, Tokenizer};
+
+/// Diff `original` and `updated` without merging, returning the spans of
+/// both texts labelled `Unchanged`, `Added` or `Removed`, such as for showing
+/// what changed since the last sync. Removed spans come before the spans
+/// added in their place, and their `merged_range` is empty.
+///
+/// Supports UTF-8. Arguments are tokenized using the provided `tokenizer`.
+///
+/// ```
+/// use reconcile_text::{BuiltinTokenizer, History, diff_with_history};
+///
+/// let spans = diff_with_history(
+///     "The quick fox",
+///     "The slow fox",
+///     &*BuiltinTokenizer::Word,
+/// );
+///
+/// let history: Vec<_> = spans
+///     .iter()
+///     .map(|span| (span.text(), span.history()))
+///     .collect();
+/// assert_eq!(
+///     history,
+///     vec![
+///         ("The".to_owned(), History::Unchanged),
+///         (" quick".to_owned(), History::Removed),
+///         (" slow".to_owned(), History::Added),
+///         (" ".to_owned(), History::Unchanged),
+///         ("fox".to_owned(), History::Unchanged),
+///     ]
+/// );
+///
+/// assert_eq!(spans[1].parent_range(), 3..9);
+/// assert_eq!(spans[2].merged_range(), 3..8);
+/// ```
+#[must_use]
+pub fn diff_with_history<T>(
+    original: &str,
+    updated: &str,
+    tokenizer: &Tokenizer<T>,
+) -> Vec<SpanWithHistory>
+where
+    T: PartialEq + Clone + Debug,
+{
+    EditedText::from_strings_with_tokenizer(original, &updated.into(), tokenizer)
+        .history_spans()
+        .map(SpanWithHistory::into_two_way)
+        .collect()
+}
+
+/// Diff `original` and `updated` like `diff_with_history` and format the
+/// result like `git diff --word-diff`, with removed text wrapped in `[-` and
+/// `-]`, and added text in `{+` and `+}`
+///
+/// ```
+/// use reconcile_text::{BuiltinTokenizer, word_diff};
+///
+/// assert_eq!(
+///     word_diff(
+///         "The quick brown fox",
+///         "The very quick red fox",
+///         &*BuiltinTokenizer::Word,
+///     ),
+///     "The{+ very+} quick[- brown-]{+ red+} fox"
+/// );
+/// ```
+#[must_use]
+pub fn word_diff<T>(original: &str, updated: &str, tokenizer: &Tokenizer<T>) -> String
+where
+    T: PartialEq + Clone + Debug,
+{
+    let mut result = String::new();
+    let mut open: Option<History> = None;
+
+    for span in diff_with_history(original, updated, tokenizer) {
+        let history = span.history();
+        if open != Some(history) {
+            close_marker(open, &mut result);
+            match history {
+                History::Added => result.push_str("{+"),
+                History::Removed => result.push_str("[-"),
+                _ => {}
+            }
+            open = Some(history);
+        }
+
+        result.push_str(&span.text());
+    }
+    close_marker(open, &mut result);
+
+    result
+}
+
+fn close_marker(history: Option<History>, result: &mut String) {
+    match history {
+        Some(History::Added) => result.push_str("+}"),
+        Some(History::Removed) => result.push_str("-]"),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::BuiltinTokenizer;
+
+    #[test]
+    fn test_diff_with_history_ranges() {
+        let spans = diff_with_history("a b c", "a x b", &*BuiltinTokenizer::Word);
+
+        assert_eq!(
+            spans
+                .iter()
+                .map(|span| (
+                    span.text(),
+                    span.history(),
+                    span.peer(),
+                    span.parent_range(),
+                    span.merged_range()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("a".to_owned(), History::Unchanged, None, 0..1, 0..1),
+                (" x".to_owned(), History::Added, None, 1..1, 1..3),
+                (" ".to_owned(), History::Unchanged, None, 1..2, 3..4),
+                ("b".to_owned(), History::Unchanged, None, 2..3, 4..5),
+                (" c".to_owned(), History::Removed, None, 3..5, 5..5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_word_diff() {
+        assert_eq!(word_diff("", "", &*BuiltinTokenizer::Word), "");
+        assert_eq!(
+            word_diff("same text", "same text", &*BuiltinTokenizer::Word),
+            "same text"
+        );
+        assert_eq!(
+            word_diff("one\ntwo\n", "one\n2\n", &*BuiltinTokenizer::Line),
+            "one\n[-two-]{+2+}\n"
+        );
+        assert_eq!(
+            word_diff("héllo", "hállo", &*BuiltinTokenizer::Character),
+            "h[-é-]{+á+}llo"
+        );
+    }
+}
@@ -1,21 +1,65 @@
-use std::fmt::Debug;
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    fmt::{self, Debug},
+    hash::Hash,
+    io,
+    iter::{self, Peekable},
+    ops::Range,
+};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "render")]
+use crate::{AnsiRenderer, HtmlRenderer};
+
 use crate::{
     BuiltinTokenizer, CursorPosition, TextWithCursors, Token,
     operation_transformation::{
         DiffError, Operation,
-        utils::{cook_operations::cook_operations, elongate_operations::elongate_operations},
+        apply_diff_fuzzy::split_into_hunks,
+        merge_strategy::MergeStrategy,
+        utils::{
+            compose_operations::{compose_operations, transform_index},
+            cook_operations::cook_operations,
+            elongate_operations::elongate_operations,
+            find_conflicts::find_conflicts,
+            find_moves::{Move, find_moves},
+            project_operations::project_operations,
+            resolve_conflicts::{advancing_length, resolve_conflicts},
+            route_moved_edits::route_moved_edits,
+            split_operations::{operation_boundaries, split_equal_operations},
+        },
     },
     raw_operation::RawOperation,
     tokenizer::Tokenizer,
     types::{
-        history::History, number_or_text::NumberOrText, side::Side,
-        span_with_history::SpanWithHistory,
+        authorship::Authorship,
+        conflict::Conflict,
+        conflict_markers::ConflictMarkers,
+        diff_envelope::DiffEnvelope,
+        diff_options::{Degradation, DiffFallback, DiffOptions},
+        fuzzy_patch::PatchHunk,
+        gravity::Gravity,
+        history::History,
+        invertible_change::InvertibleChange,
+        merge_report::{MergeReport, SideReport},
+        merge_trace::{MergeStep, MergeTrace},
+        number_or_text::NumberOrText,
+        selection::{Selection, cursors_to_selections, selections_to_cursors},
+        side::Side,
+        span_with_history::{SpanWithHistory, TextOffset},
+        unified_diff::UnifiedDiff,
+    },
+    utils::{
+        diff_budget::DiffBudget,
+        diff_codec::{decode_diff, encode_diff},
+        fnv1a_hash::fnv1a_hash,
+        line_granularity_diff::line_granularity_diff,
+        split_at_ranges::split_at_ranges,
+        string_builder::StringBuilder,
     },
-    utils::string_builder::StringBuilder,
 };
 
 /// A text document with a sequence of operations derived from diffing it
@@ -26,26 +70,66 @@ use crate::{
 /// then merged with another `EditedText` and applied to get the reconciled
 /// text.
 ///
-/// Also tracks cursor positions from the updated text, repositioning them
-/// when operations are applied.
+/// Also tracks cursor positions and selections from the updated text,
+/// repositioning them when operations are applied.
+///
+/// The original text is borrowed when created from a `&str`, and owned when
+/// created from a `String`. Use `into_owned` to get an `EditedText<'static,
+/// T>` which can be stored or sent to other threads.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct EditedText<'a, T>
 where
     T: PartialEq + Clone + Debug,
 {
-    text: &'a str,
+    text: Cow<'a, str>,
     operations: Vec<Operation<T>>,
     operation_sides: Vec<Side>,
     cursors: Vec<CursorPosition>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    selections: Vec<Selection>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    conflicts: Vec<Conflict>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    moves: Vec<Move<T>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    degradations: Vec<Degradation>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    deduplicated_insertions: usize,
+
+    /// Character ranges of the result which more than one side inserted
+    #[cfg_attr(feature = "serde", serde(default))]
+    shared_insertions: Vec<Range<usize>>,
+
+    /// Character ranges of the original text which more than one side deleted
+    #[cfg_attr(feature = "serde", serde(default))]
+    shared_deletions: Vec<Range<usize>>,
+
+    /// Who wrote each character of the original text
+    #[cfg_attr(feature = "serde", serde(default))]
+    authorship: Option<Authorship>,
+
+    /// The author of each side's insertions, indexed by `Side::index`
+    #[cfg_attr(feature = "serde", serde(default))]
+    authors: Vec<Option<String>>,
+
+    /// Character index of each operation in its side's updated text, only
+    /// known for merged `EditedText`-s
+    #[cfg_attr(feature = "serde", serde(default))]
+    side_offsets: Vec<usize>,
 }
 
 impl<'a> EditedText<'a, String> {
     /// Create an `EditedText` from the given original and updated strings.
     /// Uses the default word tokenizer (splits on word boundaries).
     #[must_use]
-    pub fn from_strings(original: &'a str, updated: &TextWithCursors) -> Self {
-        Self::from_strings_with_tokenizer(original, updated, &*BuiltinTokenizer::Word)
+    pub fn from_strings(original: impl Into<Cow<'a, str>>, updated: &TextWithCursors) -> Self {
+        Self::from_strings_with_options(
+            original,
+            updated,
+            &*BuiltinTokenizer::Word,
+            &DiffOptions::default(),
+        )
     }
 }
 
@@ -55,32 +139,128 @@ where
 {
     /// Create an `EditedText` from the given original and updated strings
     /// using the provided tokenizer
+    ///
+    /// The tokens are only compared with `PartialEq`. For tokens which are
+    /// also `Eq + Hash`, `from_strings_with_options` maps them to integers
+    /// before diffing, which is several times faster on large documents
+    /// (see the `diff-benchmark` example).
     #[must_use]
     pub fn from_strings_with_tokenizer(
-        original: &'a str,
+        original: impl Into<Cow<'a, str>>,
         updated: &TextWithCursors,
         tokenizer: &Tokenizer<T>,
     ) -> Self {
-        let original_tokens = (tokenizer)(original);
+        let original = original.into();
+        let original_tokens = (tokenizer)(&original);
         let updated_tokens = (tokenizer)(&updated.text());
 
-        let diff: Vec<RawOperation<T>> = RawOperation::vec_from(&original_tokens, &updated_tokens);
-        let operations: Vec<Operation<T>> = cook_operations(elongate_operations(diff)).collect();
+        let diff: Vec<RawOperation<T>> = RawOperation::vec_from(original_tokens, updated_tokens);
+        Self::from_raw_operations(original, updated, diff, &DiffBudget::unlimited())
+    }
+
+    /// Create an `EditedText` from the given original and updated strings
+    /// using the provided tokenizer and diff options
+    ///
+    /// ```
+    /// use reconcile_text::{BuiltinTokenizer, DiffAlgorithm, DiffOptions, EditedText};
+    ///
+    /// let options = DiffOptions::default().with_algorithm(DiffAlgorithm::Histogram);
+    /// let edited = EditedText::from_strings_with_options(
+    ///     "a\n\nb\n",
+    ///     &"a\n\nc\n\nb\n".into(),
+    ///     &*BuiltinTokenizer::Line,
+    ///     &options,
+    /// );
+    /// assert_eq!(edited.apply().text(), "a\n\nc\n\nb\n");
+    /// ```
+    #[must_use]
+    pub fn from_strings_with_options(
+        original: impl Into<Cow<'a, str>>,
+        updated: &TextWithCursors,
+        tokenizer: &Tokenizer<T>,
+        options: &DiffOptions,
+    ) -> Self
+    where
+        T: Eq + Hash,
+    {
+        let original = original.into();
+        let updated_text = updated.text();
+        let original_tokens = (tokenizer)(&original);
+        let updated_tokens = (tokenizer)(&updated_text);
+
+        let mut budget = DiffBudget::new(options);
+        let mut diff: Vec<RawOperation<T>> = RawOperation::vec_from_with_algorithm(
+            original_tokens,
+            updated_tokens,
+            options.algorithm(),
+            &budget,
+        );
+
+        let mut degradations = Vec::new();
+        if budget.is_exceeded() {
+            if options.fallback() == DiffFallback::LineGranularity {
+                budget = DiffBudget::new(options);
+                diff = line_granularity_diff(
+                    &original,
+                    &updated_text,
+                    tokenizer,
+                    options.algorithm(),
+                    &budget,
+                );
+
+                degradations.push(Degradation::LineGranularity);
+                if budget.is_exceeded() {
+                    degradations.push(Degradation::CappedEditDistance);
+                }
+            } else {
+                degradations.push(Degradation::CappedEditDistance);
+            }
+        }
+
+        let move_budget = budget.with_same_deadline();
+        let edited_text = Self::from_raw_operations(original, updated, diff, &move_budget);
+        if move_budget.is_exceeded() {
+            degradations.push(Degradation::IncompleteMoveDetection);
+        }
+
+        Self {
+            degradations,
+            ..edited_text
+        }
+    }
+
+    /// Create an `EditedText` from the diff of the original and updated
+    /// strings, looking for moved blocks until the deadline of `budget`
+    fn from_raw_operations(
+        original: Cow<'a, str>,
+        updated: &TextWithCursors,
+        diff: Vec<RawOperation<T>>,
+        budget: &DiffBudget,
+    ) -> Self {
+        let elongated_diff = elongate_operations(diff);
+
+        // Cooking maps the operations one-to-one, so the indexes stay valid
+        let moves = find_moves(&elongated_diff, budget);
+        let operations: Vec<Operation<T>> = cook_operations(elongated_diff).collect();
         let operation_count = operations.len();
 
-        Self::new(
-            original,
-            operations,
-            vec![Side::Left; operation_count],
-            updated.cursors(),
-        )
+        Self {
+            moves,
+            selections: updated.selections(),
+            ..Self::new(
+                original,
+                operations,
+                vec![Side::Left; operation_count],
+                updated.cursors(),
+            )
+        }
     }
 
     /// Create a new `EditedText` with the given operations.
     /// The operations must be in the order in which they are meant to be
     /// applied. The operations must not overlap.
     fn new(
-        text: &'a str,
+        text: Cow<'a, str>,
         operations: Vec<Operation<T>>,
         operation_sides: Vec<Side>,
         mut cursors: Vec<CursorPosition>,
@@ -92,7 +272,89 @@ where
             operations,
             operation_sides,
             cursors,
+            selections: Vec::new(),
+            conflicts: Vec::new(),
+            moves: Vec::new(),
+            degradations: Vec::new(),
+            deduplicated_insertions: 0,
+            shared_insertions: Vec::new(),
+            shared_deletions: Vec::new(),
+            authorship: None,
+            authors: Vec::new(),
+            side_offsets: Vec::new(),
+        }
+    }
+
+    /// Take ownership of the original text (copying it if it's borrowed), so
+    /// that the result can outlive it
+    ///
+    /// ```
+    /// use reconcile_text::EditedText;
+    ///
+    /// let edited: EditedText<'static, String> = {
+    ///     let original = String::from("Hello world");
+    ///     EditedText::from_strings(&original, &"Hello there".into()).into_owned()
+    /// };
+    /// assert_eq!(edited.apply().text(), "Hello there");
+    /// ```
+    #[must_use]
+    pub fn into_owned(self) -> EditedText<'static, T> {
+        EditedText {
+            text: Cow::Owned(self.text.into_owned()),
+            operations: self.operations,
+            operation_sides: self.operation_sides,
+            cursors: self.cursors,
+            selections: self.selections,
+            conflicts: self.conflicts,
+            moves: self.moves,
+            degradations: self.degradations,
+            deduplicated_insertions: self.deduplicated_insertions,
+            shared_insertions: self.shared_insertions,
+            shared_deletions: self.shared_deletions,
+            authorship: self.authorship,
+            authors: self.authors,
+            side_offsets: self.side_offsets,
+        }
+    }
+
+    /// Attribute the text inserted by this edit to `author`, which
+    /// `authorship` and `apply_with_history` report
+    #[must_use]
+    pub fn with_author(self, author: impl Into<String>) -> Self {
+        let side_count = self
+            .operation_sides
+            .iter()
+            .map(|side| side.index() + 1)
+            .max()
+            .unwrap_or(1);
+
+        Self {
+            authors: vec![Some(author.into()); side_count],
+            ..self
+        }
+    }
+
+    /// Set who wrote each character of the original text, for example the
+    /// `authorship` of the previous merge result. It's enough to give it to
+    /// one of the merged `EditedText`-s.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DiffError::AuthorshipMismatch` if `authorship` doesn't cover
+    /// the original text exactly.
+    pub fn with_authorship(self, authorship: Authorship) -> Result<Self, DiffError> {
+        let expected_length = self.text.chars().count();
+        if authorship.len() != expected_length {
+            return Err(DiffError::AuthorshipMismatch {
+                expected_length,
+                actual_length: authorship.len(),
+            });
         }
+
+        Ok(Self {
+            authorship: Some(authorship),
+            ..self
+        })
     }
 
     /// Merge two `EditedText` instances. The two instances must be derived
@@ -105,51 +367,374 @@ where
     /// Panics if there's an integer overflow (in isize) when calculating new
     /// cursor positions.
     #[must_use]
-    #[allow(clippy::too_many_lines)]
     pub fn merge(self, other: Self) -> Self {
+        self.merge_many(vec![other])
+    }
+
+    /// Like `merge`, but also record every decision of the merge in a
+    /// `MergeTrace`, for explaining why the merged text came out the way it
+    /// did
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's an integer overflow (in isize) when calculating new
+    /// cursor positions.
+    #[must_use]
+    pub fn merge_with_trace(self, other: Self) -> (Self, MergeTrace) {
+        let mut trace = MergeTrace::default();
+        let merged = self.weave(vec![other], None, Some(&mut trace));
+
+        (merged, trace)
+    }
+
+    /// Merge any number of `EditedText` instances in a single pass. All
+    /// instances must be derived from the same original text. `self` becomes
+    /// the first side (`Side::Left`) and `others` follow in order, so the
+    /// result doesn't depend on chaining pairwise merges in some order.
+    ///
+    /// The cursors of every side are carried through to the merged result.
+    /// Regions which more than one side edited differently are recorded as
+    /// conflicts, see `apply_with_conflicts`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's an integer overflow (in isize) when calculating new
+    /// cursor positions.
+    #[must_use]
+    pub fn merge_many(self, others: Vec<Self>) -> Self {
+        self.weave(others, None, None)
+    }
+
+    /// Merge two `EditedText` instances like `merge`, but let `strategy`
+    /// decide how the regions both sides edited differently are resolved.
+    /// The `tokenizer` should be the one the instances were created with, it
+    /// is used for the text of the resolutions.
+    ///
+    /// ```
+    /// use reconcile_text::{BuiltinTokenizer, EditedText, PreferRight};
+    ///
+    /// let parent = "The cat sat on the mat";
+    /// let left = EditedText::from_strings(parent, &"The dog sat on the mat".into());
+    /// let right = EditedText::from_strings(parent, &"The bird sat on the rug".into());
+    ///
+    /// let merged = left.merge_with_strategy(right, &*BuiltinTokenizer::Word, &PreferRight);
+    /// assert_eq!(merged.apply().text(), "The bird sat on the rug");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's an integer overflow (in isize) when calculating new
+    /// cursor positions.
+    #[must_use]
+    pub fn merge_with_strategy(
+        self,
+        other: Self,
+        tokenizer: &Tokenizer<T>,
+        strategy: &dyn MergeStrategy,
+    ) -> Self {
+        self.merge_many_with_strategy(vec![other], tokenizer, strategy)
+    }
+
+    /// Merge any number of `EditedText` instances like `merge_many`, but let
+    /// `strategy` decide how the regions more than one side edited
+    /// differently are resolved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's an integer overflow (in isize) when calculating new
+    /// cursor positions.
+    #[must_use]
+    pub fn merge_many_with_strategy(
+        self,
+        others: Vec<Self>,
+        tokenizer: &Tokenizer<T>,
+        strategy: &dyn MergeStrategy,
+    ) -> Self {
+        self.weave(others, Some((tokenizer, strategy)), None)
+    }
+
+    /// Compose two consecutive edits: `self` turning A into B, and `next`
+    /// which was derived from B (turning it into C) into a single edit
+    /// turning A into C. The sides of the operations are kept, so the history
+    /// of the result attributes each change to the edit that made it.
+    ///
+    /// The cursors and selections of `next` are kept, and the ones only
+    /// `self` has are moved through `next`. Conflicts and moves describe a
+    /// single merge, so they are not carried over. The `tokenizer` should be
+    /// the one the instances were created with, it is used for the inserted
+    /// text which `next` only partially keeps.
+    ///
+    /// ```
+    /// use reconcile_text::{BuiltinTokenizer, EditedText};
+    ///
+    /// let first = EditedText::from_strings("Hello world", &"Hello dear world".into());
+    /// let second = EditedText::from_strings("Hello dear world", &"Hi dear world!".into());
+    ///
+    /// let composed = first.compose(second, &*BuiltinTokenizer::Word).unwrap();
+    /// assert_eq!(composed.apply().text(), "Hi dear world!");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `DiffError::NotConsecutive` if the original text of `next`
+    /// isn't as long as the text `self` results in.
+    pub fn compose(
+        self,
+        next: EditedText<'_, T>,
+        tokenizer: &Tokenizer<T>,
+    ) -> Result<Self, DiffError> {
+        let expected = advancing_length(&self.operations);
+        let actual = next.text.chars().count();
+        if expected != actual {
+            return Err(DiffError::NotConsecutive { expected, actual });
+        }
         debug_assert_eq!(
-            self.text, other.text,
-            "`EditedText`-s must be derived from the same text to be mergable"
+            self.apply().text(),
+            next.text,
+            "`next` must be derived from the result of `self`"
+        );
+
+        let (operations, operation_sides) = compose_operations(
+            &self.operations,
+            &self.operation_sides,
+            &next.operations,
+            &next.operation_sides,
+            tokenizer,
         );
 
-        let mut merged_cursors = Vec::with_capacity(self.cursors.len() + other.cursors.len());
-        let mut left_cursors = self.cursors.into_iter().peekable();
-        let mut right_cursors = other.cursors.into_iter().peekable();
+        let mut cursors = next.cursors;
+        let moved_cursors = self
+            .cursors
+            .into_iter()
+            .filter(|cursor| cursors.iter().all(|other| other.id != cursor.id))
+            .map(|cursor| {
+                let index = transform_index(&next.operations, cursor.char_index, cursor.gravity);
+                cursor.with_index(index)
+            })
+            .collect::<Vec<_>>();
+        cursors.extend(moved_cursors);
+
+        let mut selections = next.selections;
+        let moved_selections = self
+            .selections
+            .into_iter()
+            .filter(|selection| selections.iter().all(|other| other.id != selection.id))
+            .map(|selection| Selection {
+                anchor: transform_index(&next.operations, selection.anchor, selection.gravity),
+                head: transform_index(&next.operations, selection.head, selection.gravity),
+                ..selection
+            })
+            .collect::<Vec<_>>();
+        selections.extend(moved_selections);
+
+        let mut degradations = self.degradations;
+        degradations.extend(next.degradations);
+        degradations.sort_unstable();
+        degradations.dedup();
+
+        Ok(Self {
+            selections,
+            degradations,
+            ..Self::new(self.text, operations, operation_sides, cursors)
+        })
+    }
+
+    /// Rebase the operations onto `against`, which was derived from the same
+    /// original text: the result turns the text `against` results in into the
+    /// text merging the two (`against.merge(self)`) results in. The cursors
+    /// and selections are those of `self`.
+    ///
+    /// ```
+    /// use reconcile_text::EditedText;
+    ///
+    /// let parent = "Hello world";
+    /// let local = EditedText::from_strings(parent, &"Hello beautiful world".into());
+    /// let remote = EditedText::from_strings(parent, &"Hi world".into());
+    ///
+    /// let rebased = local.transform(&remote);
+    /// assert_eq!(rebased.to_diff().unwrap(), vec![2.into(), " beautiful".into(), 6.into()]);
+    /// assert_eq!(rebased.apply().text(), "Hi beautiful world");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's an integer overflow (in isize) when calculating new
+    /// cursor positions.
+    #[must_use]
+    pub fn transform(&self, against: &Self) -> EditedText<'static, T> {
+        self.transform_ordered(against, true)
+    }
+
+    /// Rebase a stack of consecutive changes (each derived from the result of
+    /// the previous one) onto `onto`, which was derived from the original
+    /// text of the first change, like `git rebase` does with commits. The
+    /// first rebased change starts from the text `onto` results in, and each
+    /// following one from the result of the previous.
+    ///
+    /// ```
+    /// use reconcile_text::EditedText;
+    ///
+    /// let parent = "Hello world";
+    /// let first = EditedText::from_strings(parent, &"Hello beautiful world".into());
+    /// let second = EditedText::from_strings("Hello beautiful world", &"Hello beautiful world!".into());
+    /// let remote = EditedText::from_strings(parent, &"Hi world".into());
+    ///
+    /// let rebased = EditedText::rebase(&[first, second], &remote);
+    /// assert_eq!(rebased[0].apply().text(), "Hi beautiful world");
+    /// assert_eq!(rebased[1].apply().text(), "Hi beautiful world!");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's an integer overflow (in isize) when calculating new
+    /// cursor positions.
+    #[must_use]
+    pub fn rebase(changes: &[Self], onto: &Self) -> Vec<EditedText<'static, T>> {
+        let mut onto = onto.clone();
+        let mut rebased = Vec::with_capacity(changes.len());
+
+        for change in changes {
+            debug_assert_eq!(
+                change.text, onto.text,
+                "The changes must follow each other and start from the original of `onto`"
+            );
 
-        let mut merged_operations: Vec<Operation<T>> =
-            Vec::with_capacity(self.operations.len() + other.operations.len());
-        let mut merged_operation_sides: Vec<Side> =
-            Vec::with_capacity(self.operations.len() + other.operations.len());
+            // Both are merged with `onto` as the first side, so they converge
+            rebased.push(change.transform_ordered(&onto, true));
+            onto = onto.transform_ordered(change, false);
+        }
 
-        let mut left_iter = self.operations.into_iter();
-        let mut right_iter = other.operations.into_iter();
+        rebased
+    }
 
-        let mut maybe_left_op = left_iter.next();
-        let mut maybe_right_op = right_iter.next();
+    /// Merge with `against` and express the result over the text `against`
+    /// results in. `against_first` decides which of the two is the first side
+    /// of the merge, which breaks the ties between concurrent insertions.
+    fn transform_ordered(&self, against: &Self, against_first: bool) -> EditedText<'static, T> {
+        let mut boundaries: Vec<usize> = operation_boundaries(&self.operations)
+            .chain(operation_boundaries(&against.operations))
+            .collect();
+        boundaries.sort_unstable();
+        boundaries.dedup();
+
+        let base = Self {
+            cursors: Vec::new(),
+            selections: Vec::new(),
+            ..against.split_equals_at(&boundaries)
+        };
+        let this = self.split_equals_at(&boundaries);
+        let merged = if against_first {
+            base.merge(this)
+        } else {
+            this.merge(base)
+        };
+
+        let (text, operations) = project_operations(
+            merged
+                .operations_with_original_text()
+                .map(|(operation, _, original)| (operation, original)),
+            &against.operations,
+        );
+        debug_assert_eq!(
+            text,
+            against.apply().text(),
+            "The rebased operations must start from the result of `against`"
+        );
 
-        let mut seen_left_length: usize = 0;
-        let mut seen_right_length: usize = 0;
-        let mut merged_length: usize = 0;
+        let operation_count = operations.len();
+        EditedText {
+            selections: merged.selections,
+            degradations: merged.degradations,
+            ..EditedText::new(
+                Cow::Owned(text),
+                operations,
+                vec![Side::Left; operation_count],
+                merged.cursors,
+            )
+        }
+    }
 
-        let mut last_left_op = None;
-        let mut last_right_op = None;
+    /// A copy with the retained spans split at `boundaries`, so that they line
+    /// up with the operations of another side
+    fn split_equals_at(&self, boundaries: &[usize]) -> Self {
+        let (operations, operation_sides, first_pieces) =
+            split_equal_operations(&self.operations, &self.operation_sides, boundaries);
 
-        loop {
-            let (side, operation) = match (maybe_left_op.as_ref(), maybe_right_op.as_ref()) {
-                (Some(left_op), Some(right_op)) => {
-                    if left_op.cmp_priority(seen_left_length, right_op, seen_right_length)
-                        == std::cmp::Ordering::Less
-                    {
-                        (Side::Left, maybe_left_op.take().unwrap())
-                    } else {
-                        (Side::Right, maybe_right_op.take().unwrap())
-                    }
-                }
+        Self {
+            operations,
+            operation_sides,
+            moves: self
+                .moves
+                .iter()
+                .map(|m| Move {
+                    delete_index: first_pieces[m.delete_index],
+                    insert_index: first_pieces[m.insert_index],
+                    source: m.source.clone(),
+                })
+                .collect(),
+            ..self.clone()
+        }
+    }
 
-                (Some(_), None) => (Side::Left, maybe_left_op.take().unwrap()),
-                (None, Some(_)) => (Side::Right, maybe_right_op.take().unwrap()),
-                (None, None) => break,
-            };
+    /// Merge the operations of all sides in a single pass, resolving the
+    /// conflicts with `strategy` first if given, and recording the decisions
+    /// in `trace` if given
+    #[allow(clippy::too_many_lines)]
+    fn weave(
+        self,
+        others: Vec<Self>,
+        strategy: Option<(&Tokenizer<T>, &dyn MergeStrategy)>,
+        mut trace: Option<&mut MergeTrace>,
+    ) -> Self {
+        let mut sides: Vec<Self> = iter::once(self).chain(others).collect();
+        debug_assert!(
+            sides.iter().all(|side| side.text == sides[0].text),
+            "`EditedText`-s must be derived from the same text to be mergable"
+        );
+        let text = std::mem::take(&mut sides[0].text);
+
+        let degradations = Self::merged_degradations(&sides);
+        let authorship = sides.iter_mut().find_map(|side| side.authorship.take());
+        let authors = sides
+            .iter_mut()
+            .map(|side| side.authors.drain(..).next().flatten())
+            .collect();
+        let mut deduplicated_insertions: usize =
+            sides.iter().map(|side| side.deduplicated_insertions).sum();
+
+        let mut selections = Self::selections_to_cursors(&mut sides);
+        let (mut conflicts, conflict_indices) = Self::prepare_sides(&text, &mut sides, strategy);
+        let mut conflict_merged_ranges: Vec<Option<Range<usize>>> = vec![None; conflicts.len()];
+
+        let operation_count = sides.iter().map(|side| side.operations.len()).sum();
+        let cursor_count = sides.iter().map(|side| side.cursors.len()).sum();
+
+        let side_count = sides.len();
+        let mut peers: Vec<Peer<T>> = sides
+            .into_iter()
+            .zip(conflict_indices)
+            .map(|(edited_text, conflict_indices)| {
+                Peer::new(edited_text, conflict_indices, side_count)
+            })
+            .collect();
+
+        let mut merged_cursors = Vec::with_capacity(cursor_count);
+        let mut merged_operations: Vec<Operation<T>> = Vec::with_capacity(operation_count);
+        let mut merged_operation_sides: Vec<Side> = Vec::with_capacity(operation_count);
+        let mut side_offsets: Vec<usize> = Vec::with_capacity(operation_count);
+
+        for peer in &mut peers {
+            peer.move_leading_cursors(&mut merged_cursors);
+        }
+
+        let mut merged_length: usize = 0;
+        let mut shared_insertions = Vec::new();
+        let mut shared_deletions = Vec::new();
+
+        while let Some(side) = Self::next_side(&peers) {
+            let step = trace.is_some().then(|| Self::start_step(&peers, side));
+            let (operation, conflict_index) = peers[side.index()].next_operation.take().unwrap();
+            let merged_start = merged_length;
 
             let is_advancing_operation = matches!(
                 operation,
@@ -157,119 +742,343 @@ where
             );
 
             let original_length = operation.len();
-            let (side, result) = match side {
-                Side::Left => {
-                    let result = operation.merge_operations(last_right_op.as_ref());
-
-                    if let ref op @ (Operation::Insert { .. } | Operation::Equal { .. }) = result {
-                        let merged_length_signed = isize::try_from(merged_length)
-                            .expect("merged_length must fit in isize");
-                        let seen_left_length_signed = isize::try_from(seen_left_length)
-                            .expect("seen_left_length must fit in isize");
-                        let op_len_signed =
-                            isize::try_from(op.len()).expect("op.len() must fit in isize");
-                        let original_length_signed = isize::try_from(original_length)
-                            .expect("original_length must fit in isize");
-
-                        let shift = merged_length_signed - seen_left_length_signed + op_len_signed
-                            - original_length_signed;
-
-                        while let Some(cursor) = left_cursors.next_if(|cursor| {
-                            cursor.char_index <= seen_left_length + original_length
-                        }) {
-                            merged_cursors.push(
-                                cursor.with_index(cursor.char_index.saturating_add_signed(shift)),
-                            );
-                        }
-                    }
-
-                    if is_advancing_operation {
-                        seen_left_length += original_length;
-                    }
+            let result = Self::trim_by_other_sides(
+                &mut peers,
+                side,
+                operation,
+                &mut shared_insertions,
+                &mut shared_deletions,
+            );
 
-                    maybe_left_op = left_iter.next();
-                    last_left_op = Some(result.clone());
+            if matches!(result, Operation::Insert { .. }) && result.len() < original_length {
+                deduplicated_insertions += 1;
+            }
 
-                    (Side::Left, result)
+            // Once the merged text moves on, the inserts before can't trim
+            // what the sides insert next
+            if is_advancing_operation && result.len() > 0 {
+                for peer in &mut peers {
+                    peer.last_insert = None;
                 }
-                Side::Right => {
-                    let result = operation.merge_operations(last_left_op.as_ref());
-
-                    if let ref op @ (Operation::Insert { .. } | Operation::Equal { .. }) = result {
-                        let merged_length_signed = isize::try_from(merged_length)
-                            .expect("merged_length must fit in isize");
-                        let seen_right_length_signed = isize::try_from(seen_right_length)
-                            .expect("seen_right_length must fit in isize");
-                        let op_len_signed =
-                            isize::try_from(op.len()).expect("op.len() must fit in isize");
-                        let original_length_signed = isize::try_from(original_length)
-                            .expect("original_length must fit in isize");
-
-                        let shift = merged_length_signed - seen_right_length_signed + op_len_signed
-                            - original_length_signed;
-
-                        while let Some(cursor) = right_cursors.next_if(|cursor| {
-                            cursor.char_index <= seen_right_length + original_length
-                        }) {
-                            merged_cursors.push(
-                                cursor.with_index(cursor.char_index.saturating_add_signed(shift)),
-                            );
-                        }
-                    }
+            }
 
-                    if is_advancing_operation {
-                        seen_right_length += original_length;
-                    }
+            let peer = &mut peers[side.index()];
+
+            let moved_cursors_start = merged_cursors.len();
+            let cursor_origins = if is_advancing_operation {
+                peer.move_cursors(
+                    merged_length,
+                    original_length,
+                    result.len(),
+                    &mut merged_cursors,
+                )
+            } else {
+                Vec::new()
+            };
 
-                    maybe_right_op = right_iter.next();
-                    last_right_op = Some(result.clone());
+            if let (Some(trace), Some(step)) = (trace.as_deref_mut(), step) {
+                let moved_cursors = &merged_cursors[moved_cursors_start..];
+                trace
+                    .steps
+                    .push(step.finish(&result, moved_cursors, cursor_origins));
+            }
 
-                    (Side::Right, result)
-                }
+            let side_offset = if is_advancing_operation {
+                peer.seen_length + original_length - result.len()
+            } else {
+                peer.seen_length
             };
 
-            if result.len() == 0 {
-                continue;
+            if is_advancing_operation {
+                peer.seen_length += original_length;
             }
 
-            if is_advancing_operation {
-                merged_length += result.len();
+            peer.next_operation = peer.operations.next();
+            if let Operation::Insert { .. } = result {
+                peer.last_insert = Some(result.clone());
+                peer.last_insert_start = merged_start;
+                peer.absorbed_tokens.fill(0);
+            }
+            peer.last_operation = Some(result.clone());
+
+            if result.len() > 0 {
+                if is_advancing_operation {
+                    merged_length += result.len();
+                }
+
+                merged_operations.push(result);
+                merged_operation_sides.push(side);
+                side_offsets.push(side_offset);
             }
 
-            merged_operations.push(result);
-            merged_operation_sides.push(side);
+            if let Some(index) = conflict_index {
+                let start = conflict_merged_ranges[index]
+                    .as_ref()
+                    .map_or(merged_start, |range| range.start);
+                conflict_merged_ranges[index] = Some(start..merged_length);
+            }
         }
 
-        for cursor in left_cursors.chain(right_cursors) {
-            merged_cursors.push(cursor.with_index(merged_length));
+        for peer in peers {
+            for cursor in peer.cursors {
+                merged_cursors.push(cursor.with_index(merged_length));
+            }
         }
 
         debug_assert_eq!(merged_operations.len(), merged_operation_sides.len());
 
-        Self::new(
-            self.text,
-            merged_operations,
-            merged_operation_sides,
-            merged_cursors,
-        )
+        for (conflict, merged_range) in conflicts.iter_mut().zip(conflict_merged_ranges) {
+            conflict.merged_range = merged_range.expect("Every conflict has operations");
+        }
+
+        cursors_to_selections(&mut merged_cursors, &mut selections);
+        shared_insertions.sort_unstable_by_key(|range: &Range<usize>| range.start);
+        shared_deletions.sort_unstable_by_key(|range: &Range<usize>| range.start);
+
+        Self {
+            selections,
+            conflicts,
+            degradations,
+            deduplicated_insertions,
+            shared_insertions,
+            shared_deletions,
+            authorship,
+            authors,
+            side_offsets,
+            ..Self::new(
+                text,
+                merged_operations,
+                merged_operation_sides,
+                merged_cursors,
+            )
+        }
+    }
+
+    /// Add the ends of every side's selections to its cursors, so that they
+    /// are moved the same way. Returns the selections of all the sides.
+    fn selections_to_cursors(sides: &mut [Self]) -> Vec<Selection> {
+        let mut selections = Vec::new();
+        for side in sides {
+            side.cursors
+                .extend(selections_to_cursors(&side.selections, selections.len()));
+            side.cursors.sort_by_key(|cursor| cursor.char_index);
+            selections.append(&mut side.selections);
+        }
+
+        selections
+    }
+
+    /// Rewrite the operations of the sides before weaving them together:
+    /// route edits within moved blocks, then find the conflicts and resolve
+    /// them with `strategy` if given
+    fn prepare_sides(
+        text: &str,
+        sides: &mut [Self],
+        strategy: Option<(&Tokenizer<T>, &dyn MergeStrategy)>,
+    ) -> (Vec<Conflict>, Vec<Vec<Option<usize>>>) {
+        let mut moves = sides
+            .iter_mut()
+            .map(|side| std::mem::take(&mut side.moves))
+            .collect::<Vec<_>>();
+        route_moved_edits(
+            text,
+            &mut sides
+                .iter_mut()
+                .map(|side| (&mut side.operations, &mut side.cursors))
+                .collect::<Vec<_>>(),
+            &mut moves,
+        );
+
+        let (conflicts, mut conflict_indices) = find_conflicts(
+            text,
+            &sides
+                .iter()
+                .map(|side| side.operations.as_slice())
+                .collect::<Vec<_>>(),
+        );
+        if let Some((tokenizer, strategy)) = strategy {
+            resolve_conflicts(
+                &conflicts,
+                &mut conflict_indices,
+                &mut sides
+                    .iter_mut()
+                    .map(|side| (&mut side.operations, &mut side.cursors))
+                    .collect::<Vec<_>>(),
+                strategy,
+                tokenizer,
+            );
+        }
+
+        (conflicts, conflict_indices)
+    }
+
+    /// The degradations of all sides, without duplicates
+    fn merged_degradations(sides: &[Self]) -> Vec<Degradation> {
+        let mut degradations: Vec<Degradation> = sides
+            .iter()
+            .flat_map(|side| side.degradations.iter().copied())
+            .collect();
+        degradations.sort_unstable();
+        degradations.dedup();
+
+        degradations
+    }
+
+    /// Trim what the other sides' last operations already inserted or
+    /// deleted from the operation of `side`, and record where the trimmed
+    /// text is in the result (for inserts) or the original text (for deletes).
+    /// The operation sharing the most text is trimmed by first, so that the
+    /// result doesn't depend on the order of the sides. Each token another
+    /// side inserted trims at most one token of `side`, so that text `side`
+    /// inserted more often than the other side (in separate operations) is
+    /// kept. The other sides' last inserts are only used until the merged
+    /// text moves on, so they only trim inserts at the same place.
+    fn trim_by_other_sides(
+        peers: &mut [Peer<T>],
+        side: Side,
+        mut operation: Operation<T>,
+        shared_insertions: &mut Vec<Range<usize>>,
+        shared_deletions: &mut Vec<Range<usize>>,
+    ) -> Operation<T> {
+        let mut last_operations: Vec<(usize, Cow<'_, Operation<T>>, usize)> = Vec::new();
+        for (index, other) in peers.iter().enumerate() {
+            if index == side.index() {
+                continue;
+            }
+
+            if let Some((last_insert, start)) = other.unabsorbed_insert(side) {
+                last_operations.push((index, last_insert, start));
+            }
+
+            if let Some(
+                last_operation @ (Operation::Delete { order, .. } | Operation::Equal { order, .. }),
+            ) = &other.last_operation
+            {
+                last_operations.push((index, Cow::Borrowed(last_operation), *order));
+            }
+        }
+
+        let mut absorbed_tokens = Vec::new();
+        while let Some((index, overlap, shared)) = last_operations
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (_, last_operation, start))| {
+                let overlap = operation.overlap_with(last_operation)?;
+                let shared = start + overlap.start..start + overlap.end;
+                Some((index, overlap, shared))
+            })
+            .max_by_key(|(_, _, shared)| (shared.len(), Reverse(shared.start)))
+        {
+            let (other, last_operation, _) = last_operations.swap_remove(index);
+            match last_operation.as_ref() {
+                Operation::Insert { text, .. } => {
+                    shared_insertions.push(shared);
+
+                    let mut length = 0;
+                    let tokens = text
+                        .iter()
+                        .take_while(|token| {
+                            length += token.get_original_length();
+                            length <= overlap.end
+                        })
+                        .count();
+                    absorbed_tokens.push((other, tokens));
+                }
+                Operation::Delete { .. } => shared_deletions.push(shared),
+                Operation::Equal { .. } => {}
+            }
+
+            operation = operation.merge_operations(Some(&last_operation));
+        }
+
+        // The remaining operations share no text, but the operation must
+        // still start after the text they deleted
+        let operation = last_operations
+            .into_iter()
+            .fold(operation, |operation, (_, last_operation, _)| {
+                operation.merge_operations(Some(&last_operation))
+            });
+
+        for (other, tokens) in absorbed_tokens {
+            peers[other].absorbed_tokens[side.index()] += tokens;
+        }
+
+        operation
+    }
+
+    /// Begin tracing the step merging the next operation of `side`
+    fn start_step(peers: &[Peer<T>], side: Side) -> MergeStep {
+        let next_operations = peers.iter().enumerate().filter_map(|(index, peer)| {
+            let (operation, _) = peer.next_operation.as_ref()?;
+            Some((Side::new(index), operation))
+        });
+
+        MergeStep::new(side, next_operations)
+    }
+
+    /// Pick the side whose next operation should be processed first. On a
+    /// tie, an insert another side just inserted wins, so that it's trimmed
+    /// the same way whichever side comes first, and then the later side.
+    fn next_side(peers: &[Peer<T>]) -> Option<Side> {
+        let mut best: Option<(usize, &Operation<T>)> = None;
+
+        for (index, peer) in peers.iter().enumerate() {
+            let Some((operation, _)) = peer.next_operation.as_ref() else {
+                continue;
+            };
+
+            let is_better = best.is_none_or(|(best_index, best_operation)| {
+                best_operation
+                    .cmp_priority(peers[best_index].seen_length, operation, peer.seen_length)
+                    .then_with(|| {
+                        Self::is_inserted_by_others(peers, index, operation).cmp(
+                            &Self::is_inserted_by_others(peers, best_index, best_operation),
+                        )
+                    })
+                    != std::cmp::Ordering::Less
+            });
+
+            if is_better {
+                best = Some((index, operation));
+            }
+        }
+
+        best.map(|(index, _)| Side::new(index))
+    }
+
+    /// Whether the last insert of another side would trim `operation` of the
+    /// side at `index`
+    fn is_inserted_by_others(peers: &[Peer<T>], index: usize, operation: &Operation<T>) -> bool {
+        let side = Side::new(index);
+
+        peers
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .filter_map(|(_, other)| other.unabsorbed_insert(side))
+            .any(|(last_insert, _)| operation.overlap_with(&last_insert).is_some())
     }
 
     /// Apply the operations to the text and return the resulting text
     #[must_use]
     pub fn apply(&self) -> TextWithCursors {
-        let mut builder: StringBuilder<'_> = StringBuilder::new(self.text);
+        let mut builder: StringBuilder<'_> = StringBuilder::new(&self.text);
 
         for operation in &self.operations {
             builder = operation.apply(builder);
         }
 
         TextWithCursors::new(builder.take(), self.cursors.clone())
+            .with_selections(self.selections.clone())
     }
 
     /// Apply the operations to the text and return the resulting text in chunks
     /// together with the provenance describing where each chunk came from.
     ///
     /// Returns all spans including deletions (not present in the merged text).
+    /// Text which more than one side changed the same way is labelled
+    /// `AddedByBoth` or `RemovedByBoth`, see `History`, and the spans of
+    /// conflicting regions are flagged by `SpanWithHistory::is_conflicting`.
     ///
     /// ```
     ///  use reconcile_text::{History, SpanWithHistory, BuiltinTokenizer, reconcile};
@@ -285,108 +1094,491 @@ where
     ///      &*BuiltinTokenizer::Word,
     ///  );
     ///
-    ///  assert_eq!(
-    ///      result.apply_with_history(),
-    ///      vec![
-    ///          SpanWithHistory::new("Merging text".to_string(), History::RemovedFromRight,),
-    ///          SpanWithHistory::new(
-    ///              "With reconcile, merging documents".to_string(),
-    ///              History::AddedFromRight,
-    ///          ),
-    ///          SpanWithHistory::new(" ".to_string(), History::Unchanged,),
-    ///          SpanWithHistory::new("is".to_string(), History::Unchanged,),
-    ///          SpanWithHistory::new(" hard!".to_string(), History::RemovedFromLeft,),
-    ///          SpanWithHistory::new(" easy!".to_string(), History::AddedFromLeft,),
-    ///      ]
-    ///  );
+    ///  let expected = vec![
+    ///      SpanWithHistory::new("Merging text".to_string(), History::RemovedFromRight),
+    ///      SpanWithHistory::new(
+    ///          "With reconcile, merging documents".to_string(),
+    ///          History::AddedFromRight,
+    ///      ),
+    ///      SpanWithHistory::new(" ".to_string(), History::Unchanged),
+    ///      SpanWithHistory::new("is".to_string(), History::Unchanged),
+    ///      SpanWithHistory::new(" hard!".to_string(), History::RemovedFromLeft),
+    ///      SpanWithHistory::new(" easy!".to_string(), History::AddedFromLeft),
+    ///  ];
+    ///  let history = result.apply_with_history();
+    ///  assert_eq!(history.len(), expected.len());
+    ///  assert!(history.iter().zip(&expected).all(|(span, expected)| span.same_content(expected)));
+    ///
+    ///  // " easy!" is at 15..21 in the left text and at 36..42 in the merged one
+    ///  let span = result.span_at(38).unwrap();
+    ///  assert_eq!(span.text(), " easy!");
+    ///  assert_eq!(span.merged_range(), 36..42);
+    ///  assert_eq!(span.side_range(), Some(15..21));
+    ///  assert_eq!(span.parent_range(), 21..21);
     /// ```
     #[must_use]
     pub fn apply_with_history(&self) -> Vec<SpanWithHistory> {
-        let chars: Vec<char> = self.text.chars().collect();
-        let mut builder: StringBuilder<'_> = StringBuilder::new(self.text);
+        self.history_spans().collect()
+    }
 
-        let mut history = Vec::with_capacity(self.operations.len());
+    /// Like `apply_with_history`, but produce the spans lazily, operation by
+    /// operation, so that they can be processed without collecting all of
+    /// them first
+    pub fn history_spans(&self) -> impl Iterator<Item = SpanWithHistory> + '_ {
+        let mut parent_index = 0;
+        let mut position = TextOffset::default();
+
+        self.operations_with_original_text().enumerate().flat_map(
+            move |(index, (operation, side, original))| {
+                let spans = self.operation_spans(
+                    index,
+                    (operation, side, original),
+                    parent_index,
+                    &mut position,
+                );
+                if !matches!(operation, Operation::Insert { .. }) {
+                    parent_index += operation.len();
+                }
 
-        for (operation, side) in self.operations.iter().zip(self.operation_sides.iter()) {
-            builder = operation.apply(builder);
+                spans
+            },
+        )
+    }
 
+    /// The span of the resulting text covering the character at `index`, for
+    /// telling where the text at a position in an editor came from. Returns
+    /// `None` if `index` is out of bounds.
+    ///
+    /// Only the spans of the operation covering `index` are built, the
+    /// operations before it are just measured.
+    #[must_use]
+    pub fn span_at(&self, index: usize) -> Option<SpanWithHistory> {
+        let mut parent_index = 0;
+        let mut position = TextOffset::default();
+
+        for (operation_index, (operation, side, original)) in
+            self.operations_with_original_text().enumerate()
+        {
             match operation {
-                Operation::Equal { .. } => {
-                    history.push(SpanWithHistory::new(builder.take(), History::Unchanged));
+                Operation::Equal { .. } | Operation::Insert { .. }
+                    if index < position.chars + operation.len() =>
+                {
+                    return self
+                        .operation_spans(
+                            operation_index,
+                            (operation, side, original),
+                            parent_index,
+                            &mut position,
+                        )
+                        .into_iter()
+                        .find(|span| span.merged_range().contains(&index));
                 }
-                Operation::Insert { .. } => {
-                    let h = match side {
-                        Side::Left => History::AddedFromLeft,
-                        Side::Right => History::AddedFromRight,
-                    };
-                    history.push(SpanWithHistory::new(builder.take(), h));
+                Operation::Equal { .. } => {
+                    position.advance(original);
+                    parent_index += operation.len();
                 }
-                Operation::Delete {
-                    deleted_character_count,
-                    order,
-                    ..
-                } => {
-                    let deleted: String = chars[*order..*order + *deleted_character_count]
-                        .iter()
-                        .collect();
-                    let h = match side {
-                        Side::Left => History::RemovedFromLeft,
-                        Side::Right => History::RemovedFromRight,
-                    };
-                    history.push(SpanWithHistory::new(deleted, h));
+                Operation::Insert { text, .. } => {
+                    for token in text {
+                        position.advance(token.original());
+                    }
                 }
+                Operation::Delete { .. } => parent_index += operation.len(),
             }
         }
 
-        history
+        None
     }
 
-    /// Apply the operations and return both the merged text with cursors and
-    /// the provenance history in a single pass
-    #[must_use]
-    pub fn apply_with_all(&self) -> (TextWithCursors, Vec<SpanWithHistory>) {
-        let chars: Vec<char> = self.text.chars().collect();
-        let mut builder: StringBuilder<'_> = StringBuilder::new(self.text);
-        let mut history = Vec::with_capacity(self.operations.len());
-        let mut full_text = String::new();
+    /// The spans of the `index`-th operation, which starts at `parent_start`
+    /// in the original text and at `position` in the result, moving
+    /// `position` past them
+    fn operation_spans(
+        &self,
+        index: usize,
+        (operation, side, original): (&Operation<T>, Side, &str),
+        parent_start: usize,
+        position: &mut TextOffset,
+    ) -> Vec<SpanWithHistory> {
+        let merged_start = position.chars;
+        let side_start = if self.side_offsets.len() == self.operations.len() {
+            self.side_offsets[index]
+        } else {
+            merged_start
+        };
+
+        let spans = self.operation_history(operation, side, original, parent_start, merged_start);
+        let spans: Vec<SpanWithHistory> = match (operation, &self.authorship) {
+            (Operation::Insert { .. }, _) => spans
+                .into_iter()
+                .map(|span| span.with_author(self.author(side)))
+                .collect(),
+            (_, Some(authorship)) => spans
+                .into_iter()
+                .flat_map(|span| span.split_by_authors(authorship))
+                .collect(),
+            (_, None) => spans,
+        };
+
+        let is_in_result = !matches!(operation, Operation::Delete { .. });
+        spans
+            .into_iter()
+            .map(|span| {
+                let side_offset = side_start + position.chars - merged_start;
+                span.with_merged_offsets(position, is_in_result, side_offset)
+            })
+            .collect()
+    }
 
-        for (operation, side) in self.operations.iter().zip(self.operation_sides.iter()) {
-            builder = operation.apply(builder);
+    /// Who wrote each character of the resulting text: the original authors
+    /// (see `with_authorship`) for the retained text and each side's author
+    /// (see `with_author`) for the text it inserted. Store it next to the
+    /// result to carry the authorship over to the next merge.
+    #[must_use]
+    pub fn authorship(&self) -> Authorship {
+        let mut authorship = Authorship::default();
+        let mut parent_index = 0;
 
+        for (operation, side) in self.operations.iter().zip(&self.operation_sides) {
             match operation {
                 Operation::Equal { .. } => {
-                    let span = builder.take();
-                    full_text.push_str(&span);
-                    history.push(SpanWithHistory::new(span, History::Unchanged));
+                    let end = parent_index + operation.len();
+                    match &self.authorship {
+                        Some(original) => authorship.extend_from(original, parent_index..end),
+                        None => authorship.push(None, operation.len()),
+                    }
+                    parent_index = end;
                 }
-                Operation::Insert { .. } => {
-                    let span = builder.take();
-                    full_text.push_str(&span);
-                    let h = match side {
-                        Side::Left => History::AddedFromLeft,
-                        Side::Right => History::AddedFromRight,
-                    };
-                    history.push(SpanWithHistory::new(span, h));
+                Operation::Insert { .. } => authorship.push(self.author(*side), operation.len()),
+                Operation::Delete { .. } => parent_index += operation.len(),
+            }
+        }
+
+        authorship
+    }
+
+    /// The author of the text inserted by `side`
+    fn author(&self, side: Side) -> Option<&str> {
+        self.authors.get(side.index()).and_then(Option::as_deref)
+    }
+
+    /// The spans of one operation, which starts at `parent_start` in the
+    /// original text and at `merged_start` in the result. Inserts and deletes
+    /// are split where more than one side made the same change.
+    fn operation_history(
+        &self,
+        operation: &Operation<T>,
+        side: Side,
+        original: &str,
+        parent_start: usize,
+        merged_start: usize,
+    ) -> Vec<SpanWithHistory> {
+        let label = |text: &str, is_shared: bool, is_conflicting: bool, history: History| {
+            let span = if is_shared {
+                SpanWithHistory::new(text.to_owned(), history)
+            } else {
+                SpanWithHistory::with_side(text.to_owned(), history, side)
+            };
+
+            span.with_conflicting(is_conflicting)
+        };
+
+        match operation {
+            Operation::Equal { .. } => vec![
+                SpanWithHistory::new(original.to_owned(), History::Unchanged)
+                    .with_parent_range(parent_start..parent_start + operation.len()),
+            ],
+            Operation::Insert { text, .. } => {
+                let text: String = text.iter().map(Token::original).collect();
+                let merged_end = merged_start + operation.len();
+                // Conflicts are sorted and don't overlap, so the last one
+                // starting before the insert is the only one which may
+                // contain it
+                let preceding = self
+                    .conflicts
+                    .partition_point(|conflict| conflict.merged_range.start <= merged_start);
+                let is_conflicting =
+                    preceding > 0 && merged_end <= self.conflicts[preceding - 1].merged_range.end;
+
+                split_at_ranges(&text, merged_start, &self.shared_insertions)
+                    .into_iter()
+                    .map(|(_, piece, is_shared)| {
+                        let history = if is_shared {
+                            History::AddedByBoth
+                        } else {
+                            History::added_by(side)
+                        };
+
+                        label(piece, is_shared, is_conflicting, history)
+                            .with_parent_range(parent_start..parent_start)
+                    })
+                    .collect()
+            }
+            Operation::Delete { .. } => {
+                split_at_ranges(original, parent_start, &self.shared_deletions)
+                    .into_iter()
+                    .map(|(range, piece, is_shared)| {
+                        let following = self
+                            .conflicts
+                            .partition_point(|conflict| conflict.parent_range.end <= range.start);
+                        let is_conflicting = self
+                            .conflicts
+                            .get(following)
+                            .is_some_and(|conflict| conflict.parent_range.start < range.end);
+                        let history = if is_shared {
+                            History::RemovedByBoth
+                        } else {
+                            History::removed_by(side)
+                        };
+
+                        label(piece, is_shared, is_conflicting, history).with_parent_range(range)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Apply the operations and write the resulting text to `writer` piece by
+    /// piece, without building it in memory first. The positions of the
+    /// cursors and selections are available via `cursors` and `selections`.
+    ///
+    /// ```
+    /// use reconcile_text::{BuiltinTokenizer, reconcile};
+    ///
+    /// let result = reconcile(
+    ///     "Merging text is hard!",
+    ///     &"Merging text is easy!".into(),
+    ///     &"With reconcile, merging documents is hard!".into(),
+    ///     &*BuiltinTokenizer::Word,
+    /// );
+    ///
+    /// let mut merged = String::new();
+    /// result.apply_to_writer(&mut merged).unwrap();
+    /// assert_eq!(merged, "With reconcile, merging documents is easy!");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of `writer`.
+    pub fn apply_to_writer(&self, writer: &mut impl fmt::Write) -> fmt::Result {
+        self.write_pieces(|piece| writer.write_str(piece))
+    }
+
+    /// Like `apply_to_writer`, but write UTF-8 bytes to an `io::Write`, such
+    /// as a file
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of `writer`.
+    pub fn apply_to_io_writer(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        self.write_pieces(|piece| writer.write_all(piece.as_bytes()))
+    }
+
+    /// The operations with their sides and the part of the original text they
+    /// retain or delete, which is empty for inserts
+    fn operations_with_original_text(&self) -> impl Iterator<Item = (&Operation<T>, Side, &str)> {
+        let mut remaining: &str = &self.text;
+
+        self.operations
+            .iter()
+            .zip(self.operation_sides.iter().copied())
+            .map(move |(operation, side)| {
+                let original = if matches!(operation, Operation::Insert { .. }) {
+                    ""
+                } else {
+                    let byte_length = remaining
+                        .char_indices()
+                        .nth(operation.len())
+                        .map_or(remaining.len(), |(index, _)| index);
+                    let (original, rest) = remaining.split_at(byte_length);
+                    remaining = rest;
+
+                    original
+                };
+
+                (operation, side, original)
+            })
+    }
+
+    /// Pass the pieces of the resulting text to `write` in order
+    fn write_pieces<E>(&self, mut write: impl FnMut(&str) -> Result<(), E>) -> Result<(), E> {
+        for (operation, _, original) in self.operations_with_original_text() {
+            match operation {
+                Operation::Equal { .. } => write(original)?,
+                Operation::Insert { text, .. } => {
+                    for token in text {
+                        write(token.original())?;
+                    }
+                }
+                Operation::Delete { .. } => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The cursors positioned in the resulting text, as returned by `apply`
+    #[must_use]
+    pub fn cursors(&self) -> &[CursorPosition] {
+        &self.cursors
+    }
+
+    /// The selections positioned in the resulting text, as returned by
+    /// `apply`
+    #[must_use]
+    pub fn selections(&self) -> &[Selection] {
+        &self.selections
+    }
+
+    /// Regions of the original text which more than one side edited
+    /// differently, in the order they appear in the text. Only merged
+    /// `EditedText`-s can have conflicts.
+    #[must_use]
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.conflicts
+    }
+
+    /// The shortcuts taken because diffing exceeded the budget set by
+    /// `DiffOptions`, in the order they were taken. Merged `EditedText`-s
+    /// have the degradations of all their sides, sorted and without
+    /// duplicates.
+    #[must_use]
+    pub fn degradations(&self) -> &[Degradation] {
+        &self.degradations
+    }
+
+    /// Count what each side contributed to the result, the conflicts, and the
+    /// deduplicated insertions, and estimate how risky the merge is, without
+    /// applying the operations. The `tokenizer` should be the one the instance
+    /// was created with, it is used to count the deleted tokens.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn merge_report(&self, tokenizer: &Tokenizer<T>) -> MergeReport {
+        let side_count = self
+            .operation_sides
+            .iter()
+            .map(|side| side.index() + 1)
+            .chain(
+                self.conflicts
+                    .iter()
+                    .map(|conflict| conflict.versions.len()),
+            )
+            .max()
+            .unwrap_or_default();
+        let mut sides = vec![SideReport::default(); side_count];
+
+        let mut token_start = 0;
+        let token_ranges: Vec<Range<usize>> = tokenizer(&self.text)
+            .iter()
+            .map(|token| {
+                let range = token_start..token_start + token.get_original_length();
+                token_start = range.end;
+                range
+            })
+            .collect();
+
+        for (operation, side) in self.operations.iter().zip(&self.operation_sides) {
+            let report = &mut sides[side.index()];
+            match operation {
+                Operation::Equal { .. } => {}
+                Operation::Insert { text, .. } => {
+                    report.inserted_characters += operation.len();
+                    report.inserted_tokens += text.len();
                 }
                 Operation::Delete {
-                    deleted_character_count,
                     order,
+                    deleted_character_count,
                     ..
                 } => {
-                    let deleted: String = chars[*order..*order + *deleted_character_count]
+                    report.deleted_characters += deleted_character_count;
+
+                    let end = order + deleted_character_count;
+                    let first = token_ranges.partition_point(|token| token.end <= *order);
+                    report.deleted_tokens += token_ranges[first..]
                         .iter()
-                        .collect();
-                    let h = match side {
-                        Side::Left => History::RemovedFromLeft,
-                        Side::Right => History::RemovedFromRight,
-                    };
-                    history.push(SpanWithHistory::new(deleted, h));
+                        .take_while(|token| token.start < end)
+                        .count();
                 }
             }
         }
 
+        let changed_characters: usize = sides
+            .iter()
+            .map(|side| side.inserted_characters + side.deleted_characters)
+            .sum();
+        let conflicting_characters: usize = self
+            .conflicts
+            .iter()
+            .map(|conflict| conflict.parent_range.len() + conflict.merged_range.len())
+            .sum();
+
+        let mut risk_score = if changed_characters == 0 {
+            0.0
+        } else {
+            (conflicting_characters as f64 / changed_characters as f64).min(1.0)
+        };
+        if !self.degradations.is_empty() {
+            risk_score = risk_score.max(0.5);
+        }
+
+        MergeReport {
+            sides,
+            conflict_count: self.conflicts.len(),
+            deduplicated_insertions: self.deduplicated_insertions,
+            risk_score,
+        }
+    }
+
+    /// Apply the operations and render the regions which more than one side
+    /// edited differently with git-style conflict markers instead of
+    /// interleaving the edits. Without such regions, this returns the same
+    /// text as `apply`.
+    ///
+    /// Conflict regions follow the token boundaries, so
+    /// `BuiltinTokenizer::Line` gives the closest results to `git merge`.
+    #[must_use]
+    pub fn apply_with_conflicts(&self, markers: &ConflictMarkers) -> String {
+        let merged = self.apply().text();
+
+        if self.conflicts.is_empty() {
+            return merged;
+        }
+
+        markers.render(&merged, &self.conflicts, self.history_spans())
+    }
+
+    /// Apply the operations and render the provenance of the result as HTML,
+    /// with the added and removed text marked up, see `HtmlRenderer`
+    #[cfg(feature = "render")]
+    #[must_use]
+    pub fn apply_with_html(&self, renderer: &HtmlRenderer) -> String {
+        renderer.render(self.history_spans())
+    }
+
+    /// Apply the operations and render the provenance of the result with ANSI
+    /// colors for terminals, see `AnsiRenderer`
+    #[cfg(feature = "render")]
+    #[must_use]
+    pub fn apply_with_ansi(&self, renderer: &AnsiRenderer) -> String {
+        renderer.render(self.history_spans())
+    }
+
+    /// Apply the operations and return both the merged text with cursors and
+    /// the provenance history in a single pass
+    #[must_use]
+    pub fn apply_with_all(&self) -> (TextWithCursors, Vec<SpanWithHistory>) {
+        let mut text = String::new();
+        let history = self
+            .history_spans()
+            .inspect(|span| {
+                if !span.merged_range().is_empty() {
+                    text.push_str(span.as_str());
+                }
+            })
+            .collect();
+
         (
-            TextWithCursors::new(full_text, self.cursors.clone()),
+            TextWithCursors::new(text, self.cursors.clone())
+                .with_selections(self.selections.clone()),
             history,
         )
     }
@@ -473,10 +1665,11 @@ where
     ///
     /// Panics if there's an integer overflow in i64.
     pub fn from_diff(
-        original_text: &'a str,
+        original_text: impl Into<Cow<'a, str>>,
         diff: Vec<NumberOrText>,
         tokenizer: &Tokenizer<T>,
     ) -> Result<EditedText<'a, T>, DiffError> {
+        let original_text = original_text.into();
         let mut operations: Vec<Operation<T>> = Vec::with_capacity(diff.len());
         let mut order = 0;
         let chars: Vec<char> = original_text.chars().collect();
@@ -502,9 +1695,16 @@ where
 
                         let original_tokens = tokenizer(&original_characters);
                         for token in original_tokens {
-                            operations
-                                .push(Operation::create_equal(order, token.get_original_length()));
-                            order += token.get_original_length();
+                            let length = token.get_original_length();
+                            operations.push(if cfg!(debug_assertions) {
+                                Operation::create_equal_with_text(
+                                    order,
+                                    token.original().to_owned(),
+                                )
+                            } else {
+                                Operation::create_equal(order, length)
+                            });
+                            order += length;
                         }
                     } else {
                         let length =
@@ -519,7 +1719,14 @@ where
                             });
                         }
 
-                        operations.push(Operation::create_delete(order, length));
+                        operations.push(if cfg!(debug_assertions) {
+                            Operation::create_delete_with_text(
+                                order,
+                                chars[order..order + length].iter().collect(),
+                            )
+                        } else {
+                            Operation::create_delete(order, length)
+                        });
                         order += length;
                     }
                 }
@@ -538,6 +1745,493 @@ where
             vec![],
         ))
     }
+
+    /// Render the changes line by line as a unified diff (the format of
+    /// `diff -u` and `git diff`) of the original text and the result, which
+    /// can be applied with `patch` or `git apply`. For a merge, this is the
+    /// diff of the parent and the merged text. Returns an empty string if the
+    /// text is unchanged.
+    #[must_use]
+    pub fn to_unified_diff(&self, options: &UnifiedDiff) -> String {
+        options.render(&self.text, &self.apply().text())
+    }
+
+    /// Reconstruct an `EditedText` from a unified diff (such as the output of
+    /// `diff -u` or `git diff` for a single file) and the original text. Only
+    /// the hunks are read, and their context and deleted lines must match the
+    /// original text.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DiffError::InvalidPatch` if the patch is malformed or its
+    /// context doesn't match, `DiffError::DeletedTextMismatch` if a deleted
+    /// line differs from the original, and the errors of `from_diff`.
+    pub fn from_unified_diff(
+        original_text: impl Into<Cow<'a, str>>,
+        patch: &str,
+        tokenizer: &Tokenizer<T>,
+    ) -> Result<EditedText<'a, T>, DiffError> {
+        let original_text = original_text.into();
+        let diff = UnifiedDiff::parse(&original_text, patch)?;
+
+        Self::from_diff(original_text, diff, tokenizer)
+    }
+
+    /// Like `to_diff`, but encode the diff into a compact binary format:
+    /// retained and deleted character counts are varints, and inserts are
+    /// length-prefixed UTF-8. `from_diff_bytes` decodes it.
+    ///
+    /// ```
+    /// use reconcile_text::{BuiltinTokenizer, EditedText};
+    ///
+    /// let edited = EditedText::from_strings("Hello world", &"Hello there".into());
+    /// let bytes = edited.to_diff_bytes().unwrap();
+    /// assert_eq!(bytes.len(), 9);
+    ///
+    /// let received =
+    ///     EditedText::from_diff_bytes("Hello world", &bytes, &*BuiltinTokenizer::Word).unwrap();
+    /// assert_eq!(received.apply().text(), "Hello there");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `DiffError::IntegerOverflow` if a character count exceeds
+    /// `i64::MAX`.
+    pub fn to_diff_bytes(&self) -> Result<Vec<u8>, DiffError> {
+        Ok(encode_diff(&self.to_diff()?))
+    }
+
+    /// Reconstruct an `EditedText` from the bytes of `to_diff_bytes` and the
+    /// original text.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DiffError::TruncatedBytes` if `bytes` end in the middle of an
+    /// item, `DiffError::CorruptedBytes` if an item is invalid, and the errors
+    /// of `from_diff`.
+    pub fn from_diff_bytes(
+        original_text: impl Into<Cow<'a, str>>,
+        bytes: &[u8],
+        tokenizer: &Tokenizer<T>,
+    ) -> Result<EditedText<'a, T>, DiffError> {
+        Self::from_diff(original_text, decode_diff(bytes)?, tokenizer)
+    }
+
+    /// Like `to_diff`, but wrap the diff in a `DiffEnvelope` recording the
+    /// format version, `tokenizer_id` (such as `BuiltinTokenizer::id`), and the
+    /// length and hash of the original text, so that `from_diff_envelope` can
+    /// detect when it's applied to a different text or with a different
+    /// tokenizer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DiffError::IntegerOverflow` if a character count exceeds
+    /// `i64::MAX`.
+    pub fn to_diff_envelope(&self, tokenizer_id: &str) -> Result<DiffEnvelope, DiffError> {
+        Ok(DiffEnvelope {
+            version: DiffEnvelope::VERSION,
+            tokenizer: tokenizer_id.to_owned(),
+            base_length: self.text.chars().count(),
+            base_hash: fnv1a_hash(&self.text),
+            diff: self.to_diff()?,
+        })
+    }
+
+    /// Reconstruct an `EditedText` from a `DiffEnvelope` and the original
+    /// text, checking that the envelope was made against the same text with
+    /// the same tokenizer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DiffError::UnknownVersion` if the envelope's format version
+    /// isn't `DiffEnvelope::VERSION`, `DiffError::TokenizerMismatch` if it was
+    /// made with a tokenizer other than `tokenizer_id`, `DiffError::BaseMismatch`
+    /// if the length or hash of `original_text` differ from the envelope's,
+    /// and the errors of `from_diff`.
+    pub fn from_diff_envelope(
+        original_text: impl Into<Cow<'a, str>>,
+        envelope: DiffEnvelope,
+        tokenizer: &Tokenizer<T>,
+        tokenizer_id: &str,
+    ) -> Result<EditedText<'a, T>, DiffError> {
+        if envelope.version != DiffEnvelope::VERSION {
+            return Err(DiffError::UnknownVersion {
+                version: envelope.version,
+                supported: DiffEnvelope::VERSION,
+            });
+        }
+
+        if envelope.tokenizer != tokenizer_id {
+            return Err(DiffError::TokenizerMismatch {
+                expected: envelope.tokenizer,
+                actual: tokenizer_id.to_owned(),
+            });
+        }
+
+        let original_text = original_text.into();
+        let actual_length = original_text.chars().count();
+        let actual_hash = fnv1a_hash(&original_text);
+        if envelope.base_length != actual_length || envelope.base_hash != actual_hash {
+            return Err(DiffError::BaseMismatch {
+                expected_length: envelope.base_length,
+                actual_length,
+                expected_hash: envelope.base_hash,
+                actual_hash,
+            });
+        }
+
+        Self::from_diff(original_text, envelope.diff, tokenizer)
+    }
+
+    /// Serialise the operations into an invertible diff, which also holds the
+    /// deleted text (unlike `to_diff`), so that it can be undone later without
+    /// the original text at hand.
+    #[must_use]
+    pub fn to_invertible_diff(&self) -> Vec<InvertibleChange> {
+        let mut result: Vec<InvertibleChange> = Vec::with_capacity(self.operations.len());
+
+        for (operation, _, original) in self.operations_with_original_text() {
+            match operation {
+                Operation::Equal { length, .. } => {
+                    if let Some(InvertibleChange::Retain(previous_length)) = result.last_mut() {
+                        *previous_length += length;
+                    } else {
+                        result.push(InvertibleChange::Retain(*length));
+                    }
+                }
+                Operation::Insert { text, .. } => {
+                    result.push(InvertibleChange::Insert(
+                        text.iter().map(Token::original).collect(),
+                    ));
+                }
+                Operation::Delete { .. } => {
+                    result.push(InvertibleChange::Delete(original.to_owned()));
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Split the operations into hunks of changes, each carrying up to 16
+    /// characters of the original text around it, so that `apply_diff_fuzzy`
+    /// can find where they belong in a text which has drifted from the
+    /// original.
+    #[must_use]
+    pub fn to_patch(&self) -> Vec<PatchHunk> {
+        split_into_hunks(&self.text, self.to_invertible_diff())
+    }
+
+    /// Reconstruct an `EditedText` from an invertible diff and the original
+    /// text, checking that the deleted text matches the original.
+    ///
+    /// ```
+    /// use reconcile_text::{BuiltinTokenizer, EditedText};
+    ///
+    /// let edited = EditedText::from_strings("Hello world", &"Hello there".into());
+    /// let diff = edited.to_invertible_diff();
+    ///
+    /// let reconstructed =
+    ///     EditedText::from_invertible_diff("Hello world", diff, &*BuiltinTokenizer::Word).unwrap();
+    /// assert_eq!(reconstructed.apply().text(), "Hello there");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `DiffError::LengthExceedsOriginal` if the diff references a
+    /// range that exceeds the original text length,
+    /// `DiffError::DeletedTextMismatch` if the deleted text differs from the
+    /// original, and `DiffError::IntegerOverflow` if a character count
+    /// exceeds `i64::MAX`.
+    pub fn from_invertible_diff(
+        original_text: impl Into<Cow<'a, str>>,
+        diff: Vec<InvertibleChange>,
+        tokenizer: &Tokenizer<T>,
+    ) -> Result<EditedText<'a, T>, DiffError> {
+        let original_text = original_text.into();
+        let mut remaining = original_text.chars();
+        let mut position = 0;
+
+        let to_number = |length: usize| {
+            i64::try_from(length).map_err(|_| DiffError::IntegerOverflow { value: length })
+        };
+
+        let mut compact_diff: Vec<NumberOrText> = Vec::with_capacity(diff.len());
+        for change in diff {
+            match change {
+                InvertibleChange::Retain(length) => {
+                    let available = remaining.by_ref().take(length).count();
+                    if available < length {
+                        return Err(DiffError::LengthExceedsOriginal {
+                            position,
+                            requested: length,
+                            available,
+                        });
+                    }
+
+                    position += length;
+                    compact_diff.push(NumberOrText::Number(to_number(length)?));
+                }
+                InvertibleChange::Insert(text) => compact_diff.push(NumberOrText::Text(text)),
+                InvertibleChange::Delete(text) => {
+                    let length = text.chars().count();
+                    let actual: String = remaining.by_ref().take(length).collect();
+                    let available = actual.chars().count();
+
+                    if available < length {
+                        return Err(DiffError::LengthExceedsOriginal {
+                            position,
+                            requested: length,
+                            available,
+                        });
+                    }
+                    if actual != text {
+                        return Err(DiffError::DeletedTextMismatch {
+                            position,
+                            expected: text,
+                            actual,
+                        });
+                    }
+
+                    position += length;
+                    compact_diff.push(NumberOrText::Number(-to_number(length)?));
+                }
+            }
+        }
+
+        Self::from_diff(original_text, compact_diff, tokenizer)
+    }
+
+    /// Create the `EditedText` undoing this one: it turns the result of the
+    /// operations back into the original text. Inserts become deletes,
+    /// deletes become inserts (tokenized with `tokenizer`), and the sides of
+    /// the operations are kept, so that a merged side's changes can be undone
+    /// by merging the inverse of its part.
+    ///
+    /// The cursors and selections are moved from the result back to the
+    /// original text.
+    ///
+    /// ```
+    /// use reconcile_text::{BuiltinTokenizer, EditedText};
+    ///
+    /// let edited = EditedText::from_strings("Hello world", &"Hello there".into());
+    /// let inverse = edited.invert(&*BuiltinTokenizer::Word);
+    ///
+    /// assert_eq!(inverse.apply().text(), "Hello world");
+    /// ```
+    #[must_use]
+    pub fn invert(&self, tokenizer: &Tokenizer<T>) -> EditedText<'static, T> {
+        let mut text = String::with_capacity(self.text.len());
+        let mut operations = Vec::with_capacity(self.operations.len());
+        let mut operation_sides = Vec::with_capacity(self.operations.len());
+
+        // The text deleted since the last retained span, which is inserted back
+        // after the text inserted in its place is deleted
+        let mut deleted: Vec<(&str, Side)> = Vec::new();
+        let mut order = 0;
+
+        for (operation, side, original) in self.operations_with_original_text() {
+            match operation {
+                Operation::Equal { length, .. } => {
+                    for (deleted_text, deleted_side) in deleted.drain(..) {
+                        operations.push(Operation::create_insert(order, tokenizer(deleted_text)));
+                        operation_sides.push(deleted_side);
+                    }
+
+                    operations.push(if cfg!(debug_assertions) {
+                        Operation::create_equal_with_text(order, original.to_owned())
+                    } else {
+                        Operation::create_equal(order, *length)
+                    });
+                    operation_sides.push(side);
+
+                    text.push_str(original);
+                    order += length;
+                }
+                Operation::Insert { text: tokens, .. } => {
+                    let inserted: String = tokens.iter().map(Token::original).collect();
+                    if inserted.is_empty() {
+                        continue;
+                    }
+
+                    let length = inserted.chars().count();
+                    text.push_str(&inserted);
+                    operations.push(if cfg!(debug_assertions) {
+                        Operation::create_delete_with_text(order, inserted)
+                    } else {
+                        Operation::create_delete(order, length)
+                    });
+                    operation_sides.push(side);
+
+                    order += length;
+                }
+                Operation::Delete { .. } => deleted.push((original, side)),
+            }
+        }
+
+        for (deleted_text, deleted_side) in deleted {
+            operations.push(Operation::create_insert(order, tokenizer(deleted_text)));
+            operation_sides.push(deleted_side);
+        }
+
+        let cursors = self
+            .cursors
+            .iter()
+            .map(|cursor| {
+                cursor.with_index(transform_index(
+                    &operations,
+                    cursor.char_index,
+                    cursor.gravity,
+                ))
+            })
+            .collect();
+        let selections = self
+            .selections
+            .iter()
+            .map(|selection| Selection {
+                anchor: transform_index(&operations, selection.anchor, selection.gravity),
+                head: transform_index(&operations, selection.head, selection.gravity),
+                ..selection.clone()
+            })
+            .collect();
+
+        EditedText {
+            selections,
+            degradations: self.degradations.clone(),
+            ..EditedText::new(Cow::Owned(text), operations, operation_sides, cursors)
+        }
+    }
+}
+
+/// Merging state of one side while its operations are being woven together
+/// with the other sides' operations
+struct Peer<T>
+where
+    T: PartialEq + Clone + Debug,
+{
+    /// The remaining operations with the index of the conflict they belong to
+    operations: iter::Zip<std::vec::IntoIter<Operation<T>>, std::vec::IntoIter<Option<usize>>>,
+    next_operation: Option<(Operation<T>, Option<usize>)>,
+    last_operation: Option<Operation<T>>,
+
+    /// The last insert, which can trim the inserts of the other sides until
+    /// the merged text moves on (deletes don't move it)
+    last_insert: Option<Operation<T>>,
+
+    /// Character index of `last_insert` in the merged text
+    last_insert_start: usize,
+
+    /// For each side, the number of tokens at the start of `last_insert`
+    /// which already trimmed the same text inserted by that side, so they
+    /// can't trim any more of it
+    absorbed_tokens: Vec<usize>,
+    cursors: Peekable<std::vec::IntoIter<CursorPosition>>,
+
+    /// Length of the side's updated text processed so far
+    seen_length: usize,
+}
+
+impl<T> Peer<T>
+where
+    T: PartialEq + Clone + Debug,
+{
+    fn new(
+        edited_text: EditedText<'_, T>,
+        conflict_indices: Vec<Option<usize>>,
+        side_count: usize,
+    ) -> Self {
+        let mut operations = edited_text.operations.into_iter().zip(conflict_indices);
+
+        Self {
+            next_operation: operations.next(),
+            operations,
+            last_operation: None,
+            last_insert: None,
+            last_insert_start: 0,
+            absorbed_tokens: vec![0; side_count],
+            cursors: edited_text.cursors.into_iter().peekable(),
+            seen_length: 0,
+        }
+    }
+
+    /// The part of `last_insert` which can still trim the inserts of `side`,
+    /// with its character index in the merged text
+    fn unabsorbed_insert(&self, side: Side) -> Option<(Cow<'_, Operation<T>>, usize)> {
+        let last_insert @ Operation::Insert { order, text } = self.last_insert.as_ref()? else {
+            return None;
+        };
+
+        let absorbed = &text[..self.absorbed_tokens[side.index()]];
+        let start = self.last_insert_start
+            + absorbed
+                .iter()
+                .map(Token::get_original_length)
+                .sum::<usize>();
+        let rest = if absorbed.is_empty() {
+            Cow::Borrowed(last_insert)
+        } else {
+            Cow::Owned(Operation::create_insert(
+                *order,
+                text[absorbed.len()..].to_vec(),
+            ))
+        };
+
+        Some((rest, start))
+    }
+
+    /// Move the cursors at the very start of this side's text to the start of
+    /// the merged text, unless they should follow the text inserted there
+    fn move_leading_cursors(&mut self, merged_cursors: &mut Vec<CursorPosition>) {
+        while let Some(cursor) = self
+            .cursors
+            .next_if(|cursor| cursor.char_index == 0 && cursor.gravity.stays_before_insertion_at(0))
+        {
+            merged_cursors.push(cursor);
+        }
+    }
+
+    /// Move the cursors within the next `original_length` characters of this
+    /// side to the merged text, given that the operation covering them became
+    /// `merged_operation_length` long and starts at `merged_length`
+    fn move_cursors(
+        &mut self,
+        merged_length: usize,
+        original_length: usize,
+        merged_operation_length: usize,
+        merged_cursors: &mut Vec<CursorPosition>,
+    ) -> Vec<usize> {
+        let merged_length_signed =
+            isize::try_from(merged_length).expect("merged_length must fit in isize");
+        let seen_length_signed =
+            isize::try_from(self.seen_length).expect("seen_length must fit in isize");
+        let op_len_signed = isize::try_from(merged_operation_length)
+            .expect("merged_operation_length must fit in isize");
+        let original_length_signed =
+            isize::try_from(original_length).expect("original_length must fit in isize");
+
+        let shift =
+            merged_length_signed - seen_length_signed + op_len_signed - original_length_signed;
+
+        let end = self.seen_length + original_length;
+        let mut origins = Vec::new();
+        while let Some(cursor) = self.cursors.next_if(|cursor| match cursor.gravity {
+            Gravity::Before | Gravity::Legacy => cursor.char_index <= end,
+            Gravity::After => cursor.char_index < end,
+        }) {
+            let mut index = cursor.char_index.saturating_add_signed(shift);
+
+            // Cursors sticking to the text after them mustn't move before it
+            // when another side deleted (some of) it
+            if cursor.gravity == Gravity::After {
+                index = index.max(merged_length);
+            }
+
+            origins.push(cursor.char_index);
+            merged_cursors.push(cursor.with_index(index));
+        }
+
+        origins
+    }
 }
 
 #[cfg(test)]
@@ -546,6 +2240,7 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::CursorId;
 
     #[test]
     fn test_calculate_operations() {
@@ -641,6 +2336,77 @@ mod tests {
         assert_eq!(serialized, expected);
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialise_edited_text_without_newer_fields() {
+        // Serialised before selections, conflicts, moves, authorship, etc.
+        // were tracked
+        let serialized = concat!(
+            "text: hello world\n",
+            "operations:\n",
+            "- !Delete\n",
+            "  order: 0\n",
+            "  deleted_character_count: 5\n",
+            "- !Insert\n",
+            "  order: 5\n",
+            "  text:\n",
+            "  - normalized: Hi\n",
+            "    original: Hi\n",
+            "    is_left_joinable: true\n",
+            "    is_right_joinable: true\n",
+            "- !Insert\n",
+            "  order: 5\n",
+            "  text:\n",
+            "  - normalized: ' big'\n",
+            "    original: ' big'\n",
+            "    is_left_joinable: true\n",
+            "    is_right_joinable: true\n",
+            "- !Equal\n",
+            "  order: 5\n",
+            "  length: 6\n",
+            "operation_sides:\n",
+            "- Right\n",
+            "- Right\n",
+            "- Left\n",
+            "- Right\n",
+            "cursors: []\n",
+        );
+
+        let edited_text: EditedText<'_, String> = serde_yaml::from_str(serialized).unwrap();
+        assert_eq!(edited_text.apply().text(), "Hi big world");
+        assert!(edited_text.conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_apply_with_all_matches_apply() {
+        let parent = "Grüße aus Köln, bis bald";
+        let left = TextWithCursors::new(
+            "Liebe Grüße aus Köln, bis bald".to_owned(),
+            vec![CursorPosition::new(0, 6)],
+        )
+        .with_selections(vec![Selection::new("left", 0, 5)]);
+        let right = TextWithCursors::new(
+            "Grüße aus Bonn, bis bald 👋".to_owned(),
+            vec![CursorPosition::new(1, 26)],
+        );
+
+        let merged = crate::reconcile(parent, &left, &right, &*BuiltinTokenizer::Word);
+        let (text, history) = merged.apply_with_all();
+
+        assert_eq!(text, merged.apply());
+        assert_eq!(history, merged.apply_with_history());
+        assert_eq!(
+            history
+                .iter()
+                .map(SpanWithHistory::merged_byte_range)
+                .collect::<Vec<_>>(),
+            merged
+                .history_spans()
+                .map(|span| span.merged_byte_range())
+                .collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn test_apply_with_history_utf8() {
         let parent = "こんにちは世界"; // "Hello World" in Japanese (7 chars, 21 bytes)
@@ -659,6 +2425,595 @@ mod tests {
         assert_eq!(result.apply().text(), "こんにちは宇宙");
     }
 
+    #[test]
+    fn test_owned_edited_text() {
+        let original = String::from("The cat sat on the mat");
+        let left = EditedText::from_strings(original.clone(), &"The dog sat on the mat".into());
+        let right =
+            EditedText::from_strings(&original, &"The cat sat on the rug".into()).into_owned();
+        drop(original);
+
+        let merged = std::thread::spawn(move || left.merge(right))
+            .join()
+            .unwrap();
+        assert_eq!(merged.apply().text(), "The dog sat on the rug");
+
+        let diff = merged.to_diff().unwrap();
+        let restored = EditedText::from_diff(
+            String::from("The cat sat on the mat"),
+            diff,
+            &*BuiltinTokenizer::Word,
+        )
+        .unwrap();
+        assert_eq!(restored.apply().text(), "The dog sat on the rug");
+    }
+
+    #[test]
+    fn test_apply_to_writer() {
+        let parent = "Grüße an alle, こんにちは世界!";
+        let left = "Grüße an euch alle, こんにちは世界!";
+        let right = "Liebe Grüße an alle, こんにちは宇宙!";
+
+        let result = crate::reconcile(
+            parent,
+            &left.into(),
+            &right.into(),
+            &*BuiltinTokenizer::Word,
+        );
+        let expected = result.apply().text();
+
+        let mut text = String::new();
+        result.apply_to_writer(&mut text).unwrap();
+        assert_eq!(text, expected);
+
+        let mut bytes = Vec::new();
+        result.apply_to_io_writer(&mut bytes).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), expected);
+
+        assert_eq!(
+            result
+                .history_spans()
+                .filter(|span| {
+                    !matches!(
+                        span.history(),
+                        History::RemovedFromLeft | History::RemovedFromRight
+                    )
+                })
+                .map(|span| span.text())
+                .collect::<String>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_apply_with_history_shared_changes() {
+        let parent = "one two three four five";
+        let left = EditedText::from_strings(parent, &"one five".into());
+        let right = EditedText::from_strings(parent, &"one two six five".into());
+
+        let history: Vec<_> = left
+            .merge(right)
+            .apply_with_history()
+            .into_iter()
+            .filter(|span| span.history() != History::Unchanged)
+            .map(|span| {
+                (
+                    span.text(),
+                    span.history(),
+                    span.peer(),
+                    span.parent_range(),
+                    span.is_conflicting(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            history,
+            vec![
+                (
+                    " two".to_owned(),
+                    History::RemovedFromLeft,
+                    Some(0),
+                    3..7,
+                    true
+                ),
+                (
+                    " three four".to_owned(),
+                    History::RemovedByBoth,
+                    None,
+                    7..18,
+                    true
+                ),
+                (
+                    " six".to_owned(),
+                    History::AddedFromRight,
+                    Some(1),
+                    18..18,
+                    true
+                ),
+            ]
+        );
+
+        let left = EditedText::from_strings("Hello world", &"Hello big world".into());
+        let right = EditedText::from_strings("Hello world", &"Well, Hello big world".into());
+
+        let history: Vec<_> = left
+            .merge(right)
+            .apply_with_history()
+            .into_iter()
+            .filter(|span| span.history() != History::Unchanged)
+            .map(|span| (span.text(), span.history(), span.parent_range()))
+            .collect();
+
+        assert_eq!(
+            history,
+            vec![
+                ("Well, ".to_owned(), History::AddedFromRight, 0..0),
+                (" big".to_owned(), History::AddedByBoth, 5..5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_authorship_across_merges() {
+        let parent = "The fox";
+        let left = EditedText::from_strings(parent, &"The quick fox".into())
+            .with_author("alice")
+            .with_authorship(Authorship::new("zoe", 7))
+            .unwrap();
+        let right = EditedText::from_strings(parent, &"The fox jumps".into()).with_author("bob");
+
+        let merged = left.merge(right);
+        let text = merged.apply().text();
+        let authorship = merged.authorship();
+        assert_eq!(text, "The quick fox jumps");
+
+        let left = EditedText::from_strings(&text, &"The quick fox jumps high".into())
+            .with_author("carol")
+            .with_authorship(authorship)
+            .unwrap();
+        let right = EditedText::from_strings(&text, &"The fox jumps".into()).with_author("dave");
+
+        let merged = left.merge(right);
+        assert_eq!(merged.apply().text(), "The fox jumps high");
+
+        let history: Vec<_> = merged
+            .apply_with_history()
+            .into_iter()
+            .map(|span| (span.text(), span.history(), span.author()))
+            .collect();
+        let author = |name: &str| Some(name.to_owned());
+
+        assert_eq!(
+            history,
+            vec![
+                ("The".to_owned(), History::Unchanged, author("zoe")),
+                (
+                    " quick".to_owned(),
+                    History::RemovedFromRight,
+                    author("alice")
+                ),
+                (" ".to_owned(), History::Unchanged, author("zoe")),
+                ("fox".to_owned(), History::Unchanged, author("zoe")),
+                (" ".to_owned(), History::Unchanged, author("bob")),
+                ("jumps".to_owned(), History::Unchanged, author("bob")),
+                (" high".to_owned(), History::AddedFromLeft, author("carol")),
+            ]
+        );
+
+        let authorship = merged.authorship();
+        let authors: Vec<_> = authorship
+            .spans()
+            .iter()
+            .map(|span| (span.author(), span.length()))
+            .collect();
+        assert_eq!(
+            authors,
+            vec![(Some("zoe"), 7), (Some("bob"), 6), (Some("carol"), 5)]
+        );
+
+        assert_eq!(
+            EditedText::from_strings("The fox", &"The dog".into())
+                .with_authorship(Authorship::new("zoe", 3)),
+            Err(DiffError::AuthorshipMismatch {
+                expected_length: 7,
+                actual_length: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_history_span_offsets() {
+        let parent = "Grüße aus Köln";
+        let left = EditedText::from_strings(parent, &"Liebe Grüße aus Köln 👋".into());
+        let right = EditedText::from_strings(parent, &"Grüße aus Bonn".into());
+
+        let merged = left.merge(right);
+        assert_eq!(merged.apply().text(), "Liebe Grüße aus Bonn 👋");
+
+        let offsets: Vec<_> = merged
+            .apply_with_history()
+            .into_iter()
+            .filter(|span| span.history() != History::Unchanged)
+            .map(|span| {
+                (
+                    span.text(),
+                    span.merged_range(),
+                    span.merged_byte_range(),
+                    span.merged_utf16_range(),
+                    span.side_range(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            offsets,
+            vec![
+                ("Liebe ".to_owned(), 0..6, 0..6, 0..6, Some(0..6)),
+                (" Köln".to_owned(), 15..15, 17..17, 15..15, Some(9..9)),
+                (" Bonn".to_owned(), 15..20, 17..22, 15..20, Some(9..14)),
+                (" 👋".to_owned(), 20..22, 22..27, 20..23, Some(20..22)),
+            ]
+        );
+
+        let span = merged.span_at(17).unwrap();
+        assert_eq!(span.text(), " Bonn");
+        assert_eq!(span.history(), History::AddedFromRight);
+        assert!(span.is_conflicting());
+        assert_eq!((span.merged_start(), span.merged_end()), (15, 20));
+
+        // Spans are only equal with the same offsets and flags, but the
+        // content can be compared on its own
+        let bare =
+            SpanWithHistory::with_side(" Bonn".to_owned(), History::AddedFromRight, Side::Right);
+        assert_ne!(span, bare);
+        assert!(span.same_content(&bare));
+        assert_eq!(merged.span_at(6).unwrap().text(), "Grüße");
+        assert_eq!(merged.span_at(22), None);
+    }
+
+    #[test]
+    fn test_apply_with_history_reports_peer_index() {
+        let original = "a b c";
+        let forks = ["x a b c", "a b c y", "a b z c"];
+        let edits = forks
+            .iter()
+            .map(|fork| EditedText::from_strings(original, &(*fork).into()))
+            .collect::<Vec<_>>();
+
+        let mut edits = edits.into_iter();
+        let merged = edits.next().unwrap().merge_many(edits.collect());
+
+        let added = merged
+            .apply_with_history()
+            .into_iter()
+            .filter(|span| span.history() != History::Unchanged)
+            .map(|span| (span.text(), span.history(), span.peer()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            added,
+            vec![
+                ("x ".to_owned(), History::AddedFromLeft, Some(0)),
+                (" z".to_owned(), History::AddedFromRight, Some(2)),
+                (" y".to_owned(), History::AddedFromRight, Some(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compose() {
+        let original = "a b c";
+        let left = EditedText::from_strings(
+            original,
+            &TextWithCursors::new("x a b c".to_owned(), vec![CursorPosition::new(0, 7)]),
+        );
+        let right = EditedText::from_strings(original, &"a b c y".into());
+        let merged = left.merge(right);
+
+        let next_text = "x a c y z".to_owned();
+        let next = EditedText::from_strings(
+            merged.apply().text(),
+            &TextWithCursors::new(next_text.clone(), vec![CursorPosition::new(1, 2)]),
+        );
+        let last = EditedText::from_diff(
+            next_text,
+            vec![4.into(), "d ".into(), (-2).into(), 3.into()],
+            &*BuiltinTokenizer::Word,
+        )
+        .unwrap();
+
+        let composed = merged
+            .compose(next, &*BuiltinTokenizer::Word)
+            .unwrap()
+            .compose(last, &*BuiltinTokenizer::Word)
+            .unwrap();
+
+        assert_eq!(
+            composed.apply(),
+            TextWithCursors::new(
+                "x a d y z".to_owned(),
+                vec![CursorPosition::new(1, 2), CursorPosition::new(0, 6)]
+            )
+        );
+        assert_eq!(
+            composed
+                .apply_with_history()
+                .into_iter()
+                .filter(|span| span.history() != History::Unchanged)
+                .map(|span| (span.text(), span.history()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("x ".to_owned(), History::AddedFromLeft),
+                (" b".to_owned(), History::RemovedFromLeft),
+                ("d ".to_owned(), History::AddedFromLeft),
+                ("c".to_owned(), History::RemovedFromLeft),
+                ("y".to_owned(), History::AddedFromRight),
+                (" z".to_owned(), History::AddedFromLeft),
+            ]
+        );
+
+        assert_eq!(
+            EditedText::from_strings("a", &"b".into()).compose(
+                EditedText::from_strings("bc", &"b".into()),
+                &*BuiltinTokenizer::Word
+            ),
+            Err(DiffError::NotConsecutive {
+                expected: 1,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_transform() {
+        let parent = "The quick brown fox";
+        let local = EditedText::from_strings(
+            parent,
+            &TextWithCursors::new(
+                "The very slow brown fox".to_owned(),
+                vec![CursorPosition::new(0, 14)],
+            ),
+        );
+        let remote = EditedText::from_strings(parent, &"The quick red fox jumps".into());
+
+        let rebased = local.transform(&remote);
+        assert_eq!(rebased.text, "The quick red fox jumps");
+        assert_eq!(rebased.apply(), remote.clone().merge(local.clone()).apply());
+        assert_eq!(rebased.cursors, vec![CursorPosition::new(0, 13)]);
+
+        // Both delete " brown", so only the rest of the local change is left
+        let local = EditedText::from_strings(parent, &"The slow fox".into());
+        let rebased = local.transform(&remote);
+        assert_eq!(
+            rebased.to_invertible_diff(),
+            vec![
+                InvertibleChange::Retain(3),
+                InvertibleChange::Delete(" quick".to_owned()),
+                InvertibleChange::Insert(" slow".to_owned()),
+                InvertibleChange::Retain(14),
+            ]
+        );
+        assert_eq!(rebased.apply().text(), "The slow red fox jumps");
+    }
+
+    #[test]
+    fn test_rebase() {
+        let parent = "a c";
+        let changes = [
+            EditedText::from_strings(parent, &"a b c".into()),
+            EditedText::from_strings("a b c", &"a b c d".into()),
+            EditedText::from_strings("a b c d", &"a b d".into()),
+        ];
+        let onto = EditedText::from_strings(parent, &"a x c e".into());
+
+        let rebased = EditedText::rebase(&changes, &onto);
+
+        assert_eq!(
+            rebased
+                .iter()
+                .map(|change| (change.text.to_string(), change.apply().text()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("a x c e".to_owned(), "a b x c e".to_owned()),
+                ("a b x c e".to_owned(), "a b x c d e".to_owned()),
+                ("a b x c d e".to_owned(), "a b x d e".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invert() {
+        let original = "The quick brown fox";
+        let left = EditedText::from_strings(
+            original,
+            &TextWithCursors::new(
+                "The slow brown fox".to_owned(),
+                vec![CursorPosition::new(0, 8)],
+            ),
+        );
+        let right = EditedText::from_strings(original, &"The quick brown fox jumps".into());
+        let merged = left.merge(right);
+        assert_eq!(merged.apply().text(), "The slow brown fox jumps");
+
+        let inverse = merged.invert(&*BuiltinTokenizer::Word);
+        assert_eq!(
+            inverse.apply(),
+            TextWithCursors::new(original.to_owned(), vec![CursorPosition::new(0, 3)])
+        );
+        assert_eq!(
+            inverse
+                .apply_with_history()
+                .into_iter()
+                .filter(|span| span.history() != History::Unchanged)
+                .map(|span| (span.text(), span.history()))
+                .collect::<Vec<_>>(),
+            vec![
+                (" slow".to_owned(), History::RemovedFromLeft),
+                (" quick".to_owned(), History::AddedFromLeft),
+                (" jumps".to_owned(), History::RemovedFromRight),
+            ]
+        );
+
+        let diff = merged.to_invertible_diff();
+        let undo = diff.into_iter().map(InvertibleChange::invert).collect();
+        let restored =
+            EditedText::from_invertible_diff(merged.apply().text(), undo, &*BuiltinTokenizer::Word)
+                .unwrap();
+        assert_eq!(restored.apply().text(), original);
+    }
+
+    #[test]
+    fn test_from_invertible_diff_mismatch() {
+        let diff = vec![
+            InvertibleChange::Retain(4),
+            InvertibleChange::Delete("slow".to_owned()),
+        ];
+
+        assert_eq!(
+            EditedText::from_invertible_diff("The quick", diff, &*BuiltinTokenizer::Word),
+            Err(DiffError::DeletedTextMismatch {
+                position: 4,
+                expected: "slow".to_owned(),
+                actual: "quic".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_diff_envelope_mismatch() {
+        let edited = EditedText::from_strings("The quick fox", &"The slow fox".into());
+        let envelope = edited.to_diff_envelope("Word").unwrap();
+        let from_envelope = |original, envelope: &DiffEnvelope, tokenizer_id| {
+            EditedText::from_diff_envelope(
+                original,
+                envelope.clone(),
+                &*BuiltinTokenizer::Word,
+                tokenizer_id,
+            )
+            .map(|edited| edited.apply().text())
+        };
+
+        assert_eq!(
+            from_envelope("The quick fox", &envelope, "Word"),
+            Ok("The slow fox".to_owned())
+        );
+        assert_eq!(
+            from_envelope("The quick fox", &envelope, "Character"),
+            Err(DiffError::TokenizerMismatch {
+                expected: "Word".to_owned(),
+                actual: "Character".to_owned(),
+            })
+        );
+        assert!(matches!(
+            from_envelope("The quick cat", &envelope, "Word"),
+            Err(DiffError::BaseMismatch {
+                expected_length: 13,
+                actual_length: 13,
+                ..
+            })
+        ));
+
+        let future = DiffEnvelope {
+            version: 2,
+            ..envelope
+        };
+        assert_eq!(
+            from_envelope("The quick fox", &future, "Word"),
+            Err(DiffError::UnknownVersion {
+                version: 2,
+                supported: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_report() {
+        let parent = "Buy milk and bread";
+        let left = EditedText::from_strings(parent, &"Buy oat milk and bread".into());
+        let right = EditedText::from_strings(parent, &"Buy oat milk and jam".into());
+
+        let report = left.merge(right).merge_report(&*BuiltinTokenizer::Word);
+
+        assert_eq!(
+            report.sides(),
+            &[
+                // The insertion of " oat" is deduplicated with the right side's
+                SideReport::default(),
+                SideReport {
+                    inserted_characters: 8,
+                    inserted_tokens: 4,
+                    deleted_characters: 6,
+                    deleted_tokens: 2,
+                },
+            ]
+        );
+        assert_eq!(report.conflict_count(), 0);
+        assert_eq!(report.deduplicated_insertions(), 1);
+        assert!(report.risk_score() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_merge_with_trace_cursor_shifts() {
+        let parent = "a b";
+        let left = EditedText::from_strings(parent, &"x a b".into());
+        let right = EditedText::from_strings(
+            parent,
+            &TextWithCursors::new("a b".to_owned(), vec![CursorPosition::new(3, 2)]),
+        );
+
+        let (merged, trace) = left.merge_with_trace(right);
+
+        assert_eq!(merged.apply().text(), "x a b");
+        let shifts: Vec<_> = trace
+            .steps()
+            .iter()
+            .flat_map(MergeStep::cursor_shifts)
+            .map(|shift| (shift.id().clone(), shift.from(), shift.to()))
+            .collect();
+        assert_eq!(shifts, vec![(CursorId::Number(3), 2, 4)]);
+    }
+
+    #[test]
+    fn test_apply_with_conflicts() {
+        let parent = "title\nthe quick brown fox\nend\n";
+        let left = "title\nthe quick red fox\nend\n";
+        let right = "title\nthe quick blue fox\nend!\n";
+
+        let merged =
+            EditedText::from_strings_with_tokenizer(parent, &left.into(), &*BuiltinTokenizer::Word)
+                .merge(EditedText::from_strings_with_tokenizer(
+                    parent,
+                    &right.into(),
+                    &*BuiltinTokenizer::Word,
+                ));
+
+        assert_eq!(
+            merged.apply_with_conflicts(&ConflictMarkers::default()),
+            concat!(
+                "title\n",
+                "<<<<<<<\n",
+                "the quick red fox\n",
+                "=======\n",
+                "the quick blue fox\n",
+                ">>>>>>>\n",
+                "end!\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_apply_with_conflicts_without_conflicts() {
+        let parent = "a b c";
+        let merged = EditedText::from_strings(parent, &"x a b c".into())
+            .merge(EditedText::from_strings(parent, &"a b c y".into()));
+
+        assert_eq!(
+            merged.apply_with_conflicts(&ConflictMarkers::default()),
+            merged.apply().text()
+        );
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_changes_serialization() {
@@ -0,0 +1,149 @@
+use std::fmt::Debug;
+
+use crate::types::{conflict::Conflict, side::Side};
+
+/// Decides how a region which more than one side edited differently (a
+/// `Conflict`) ends up in the merged text
+///
+/// Implemented by the built-in strategies below and by any closure taking a
+/// `&Conflict` and returning a `Resolution`:
+///
+/// ```
+/// use reconcile_text::{reconcile_with_strategy, BuiltinTokenizer, Conflict, Resolution, Side};
+///
+/// let parent = "colour = blue";
+/// let left = "colour = red";
+/// let right = "colour = green";
+///
+/// // Keep the longer replacement
+/// let longest = |conflict: &Conflict| {
+///     if conflict.left().len() >= conflict.right().len() {
+///         Resolution::Side(Side::Left)
+///     } else {
+///         Resolution::Side(Side::Right)
+///     }
+/// };
+///
+/// let result = reconcile_with_strategy(
+///     parent,
+///     &left.into(),
+///     &right.into(),
+///     &*BuiltinTokenizer::Word,
+///     &longest,
+/// );
+/// assert_eq!(result.apply().text(), "colour = green");
+/// ```
+pub trait MergeStrategy {
+    /// Choose the resolution of a single conflict
+    fn resolve(&self, conflict: &Conflict) -> Resolution;
+}
+
+/// The outcome chosen by a `MergeStrategy` for a conflict
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    /// Weave the edits of every side together token by token, the same way
+    /// as non-overlapping edits are merged (LRLR)
+    Interleave,
+
+    /// Apply every side's deletions and keep the text each side inserted
+    /// together, one side after the other (LLLRRR)
+    Group,
+
+    /// Keep the given side's version of the region and drop the others. The
+    /// side must be one of the merged sides: debug builds panic otherwise,
+    /// and release builds interleave the conflict instead.
+    Side(Side),
+
+    /// Replace the region with the given text
+    Text(String),
+}
+
+/// Today's default: interleave overlapping edits
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Interleave;
+
+impl MergeStrategy for Interleave {
+    fn resolve(&self, _conflict: &Conflict) -> Resolution {
+        Resolution::Interleave
+    }
+}
+
+/// Keep each side's edits of a conflict grouped together
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Group;
+
+impl MergeStrategy for Group {
+    fn resolve(&self, _conflict: &Conflict) -> Resolution {
+        Resolution::Group
+    }
+}
+
+/// Keep the left side's version of every conflict
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PreferLeft;
+
+impl MergeStrategy for PreferLeft {
+    fn resolve(&self, _conflict: &Conflict) -> Resolution {
+        Resolution::Side(Side::Left)
+    }
+}
+
+/// Keep the right side's version of every conflict
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PreferRight;
+
+impl MergeStrategy for PreferRight {
+    fn resolve(&self, _conflict: &Conflict) -> Resolution {
+        Resolution::Side(Side::Right)
+    }
+}
+
+/// Keep the version of the side edited most recently. The timestamps (in any
+/// unit) are indexed by `Side::index`; on a tie, the later side wins. The
+/// timestamps of sides which aren't merged are ignored.
+///
+/// ```
+/// use reconcile_text::{reconcile_with_strategy, BuiltinTokenizer, NewestWins};
+///
+/// let result = reconcile_with_strategy(
+///     "Status: draft",
+///     &"Status: review".into(),
+///     &"Status: published".into(),
+///     &*BuiltinTokenizer::Word,
+///     &NewestWins::new(vec![1_700_000_100, 1_700_000_000]),
+/// );
+/// assert_eq!(result.apply().text(), "Status: review");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NewestWins {
+    timestamps: Vec<u64>,
+}
+
+impl NewestWins {
+    #[must_use]
+    pub fn new(timestamps: Vec<u64>) -> Self {
+        Self { timestamps }
+    }
+}
+
+impl MergeStrategy for NewestWins {
+    fn resolve(&self, conflict: &Conflict) -> Resolution {
+        self.timestamps
+            .iter()
+            .take(conflict.versions().len())
+            .enumerate()
+            .max_by_key(|(_, timestamp)| **timestamp)
+            .map_or(Resolution::Interleave, |(index, _)| {
+                Resolution::Side(Side::new(index))
+            })
+    }
+}
+
+impl<F> MergeStrategy for F
+where
+    F: Fn(&Conflict) -> Resolution,
+{
+    fn resolve(&self, conflict: &Conflict) -> Resolution {
+        self(conflict)
+    }
+}
@@ -1,4 +1,7 @@
-use core::fmt::{Debug, Display};
+use core::{
+    fmt::{Debug, Display},
+    ops::Range,
+};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -96,7 +99,8 @@ where
         }
     }
 
-    fn order(&self) -> usize {
+    /// Index of the first character the operation affects in the original text
+    pub fn order(&self) -> usize {
         match self {
             Operation::Equal { order, .. }
             | Operation::Insert { order, .. }
@@ -104,6 +108,34 @@ where
         }
     }
 
+    /// Returns the operation that keeps the original text this operation
+    /// affects: equals are kept, deletes become equals, and inserts are
+    /// dropped
+    pub fn revert(&self) -> Option<Self> {
+        match self {
+            Operation::Equal { .. } => Some(self.clone()),
+            Operation::Insert { .. } => None,
+            Operation::Delete {
+                order,
+                deleted_character_count,
+
+                #[cfg(debug_assertions)]
+                deleted_text,
+            } => {
+                #[cfg(debug_assertions)]
+                let equal = deleted_text.as_ref().map_or_else(
+                    || Operation::create_equal(*order, *deleted_character_count),
+                    |text| Operation::create_equal_with_text(*order, text.clone()),
+                );
+
+                #[cfg(not(debug_assertions))]
+                let equal = Operation::create_equal(*order, *deleted_character_count);
+
+                Some(equal)
+            }
+        }
+    }
+
     fn type_priority(&self) -> u8 {
         match self {
             Operation::Delete { .. } => 1,
@@ -335,7 +367,10 @@ where
                     ..
                 }),
             ) => {
-                if operation.len() == *last_equal_length && *order == *last_equal_order {
+                if operation.len() > 0
+                    && operation.len() == *last_equal_length
+                    && *order == *last_equal_order
+                {
                     // Both sides retained the same span from the original text,
                     // so we deduplicate by zeroing one out. This is safe because
                     // both EditedTexts are derived from the same original, and
@@ -361,6 +396,64 @@ where
             (operation, _) => operation,
         }
     }
+
+    /// The characters of `previous_operation` which `merge_operations` trims
+    /// from this operation because they were already inserted or deleted,
+    /// relative to the start of `previous_operation`
+    pub fn overlap_with(&self, previous_operation: &Self) -> Option<Range<usize>> {
+        match (self, previous_operation) {
+            (
+                Operation::Insert { text, .. },
+                Operation::Insert {
+                    text: previous_inserted_text,
+                    ..
+                },
+            ) => {
+                let length = find_longest_prefix_contained_within(previous_inserted_text, text);
+                if length == 0 {
+                    return None;
+                }
+
+                let offset_in_tokens = previous_inserted_text
+                    .windows(length)
+                    .position(|window| window == &text[..length])?;
+
+                let start = previous_inserted_text[..offset_in_tokens]
+                    .iter()
+                    .map(Token::get_original_length)
+                    .sum::<usize>();
+                let length = text[..length]
+                    .iter()
+                    .map(Token::get_original_length)
+                    .sum::<usize>();
+
+                Some(start..start + length)
+            }
+
+            (
+                Operation::Delete {
+                    order,
+                    deleted_character_count,
+                    ..
+                },
+                Operation::Delete {
+                    order: last_delete_order,
+                    deleted_character_count: last_delete_deleted_character_count,
+                    ..
+                },
+            ) => {
+                let last_delete_end_index =
+                    *last_delete_order + *last_delete_deleted_character_count;
+
+                let start = (*order).max(*last_delete_order);
+                let end = (order + deleted_character_count).min(last_delete_end_index);
+
+                (start < end).then(|| start - last_delete_order..end - last_delete_order)
+            }
+
+            _ => None,
+        }
+    }
 }
 
 impl<T> Display for Operation<T>
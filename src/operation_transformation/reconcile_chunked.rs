@@ -0,0 +1,386 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    io::{self, BufRead},
+};
+
+use crate::{Tokenizer, operation_transformation::reconcile};
+
+/// The number of consecutive lines which must be the same in all three
+/// documents (and unique within each) to merge the documents before them
+/// separately
+const ANCHOR_LINE_COUNT: usize = 8;
+
+/// The number of lines read from each document at once
+const LINES_PER_READ: usize = 1024;
+
+/// The number of lines of a document which are searched for an anchor, after
+/// which the rest of the documents is merged at once
+const MAX_CHUNK_LINES: usize = 16 * LINES_PER_READ;
+
+/// Like `reconcile`, but read the documents and write the merged result
+/// incrementally. Lines which are the same in all documents are passed
+/// through, and the rest is split into chunks at runs of lines which are the
+/// same in all documents (and unique within each). The chunks are merged one
+/// by one, together with the line before and after them as context, so only
+/// the lines between such runs are kept in memory. When there is no such run
+/// in the next few thousand lines, the rest of the documents is read and
+/// merged at once.
+///
+/// Cursors aren't supported. Each chunk is diffed and merged on its own, so
+/// the result can differ from `reconcile` where diffing the whole documents
+/// would align an edit with lines of another chunk, and in the order of
+/// conflicting insertions at the ends of a chunk.
+///
+/// ```
+/// use reconcile_text::{BuiltinTokenizer, reconcile_chunked};
+///
+/// let parent = "Merging text is hard!";
+/// let left = "Merging text is easy!";
+/// let right = "With reconcile, merging documents is hard!";
+///
+/// let mut merged = Vec::new();
+/// reconcile_chunked(
+///     parent.as_bytes(),
+///     left.as_bytes(),
+///     right.as_bytes(),
+///     &*BuiltinTokenizer::Word,
+///     &mut merged,
+/// )
+/// .unwrap();
+/// assert_eq!(merged, b"With reconcile, merging documents is easy!");
+/// ```
+///
+/// # Errors
+///
+/// Returns the first error of reading the documents (including invalid
+/// UTF-8) or of writing to `writer`.
+pub fn reconcile_chunked<T>(
+    mut original: impl BufRead,
+    mut left: impl BufRead,
+    mut right: impl BufRead,
+    tokenizer: &Tokenizer<T>,
+    writer: &mut impl io::Write,
+) -> io::Result<()>
+where
+    T: PartialEq + Clone + Debug,
+{
+    let documents = [
+        Document::new(&mut original),
+        Document::new(&mut left),
+        Document::new(&mut right),
+    ];
+
+    merge_documents(documents, MAX_CHUNK_LINES, tokenizer, writer)
+}
+
+/// Merge the documents chunk by chunk, falling back to merging the rest at
+/// once when no anchor is found in the first `max_chunk_lines` lines
+fn merge_documents<T>(
+    mut documents: [Document<'_>; 3],
+    max_chunk_lines: usize,
+    tokenizer: &Tokenizer<T>,
+    writer: &mut impl io::Write,
+) -> io::Result<()>
+where
+    T: PartialEq + Clone + Debug,
+{
+    // The last line written which is the same in all documents
+    let mut previous_line = String::new();
+
+    loop {
+        for document in &mut documents {
+            document.read_lines(LINES_PER_READ)?;
+        }
+
+        loop {
+            let lines = documents
+                .each_ref()
+                .map(|document| document.lines.as_slice());
+            let common_line_count = lines[0]
+                .iter()
+                .zip(lines[1])
+                .zip(lines[2])
+                .take_while(|((original, left), right)| original == left && original == right)
+                .count();
+
+            let (chunk_end, anchor_length) = if common_line_count > 0 {
+                ([0; 3], common_line_count)
+            } else if let Some(anchor) = find_anchor(&lines) {
+                (anchor, ANCHOR_LINE_COUNT)
+            } else {
+                break;
+            };
+
+            let chunk = [0, 1, 2].map(|index| documents[index].take(chunk_end[index]));
+            let next_line = &documents[0].lines[0];
+            merge_chunk(&previous_line, &chunk, next_line, tokenizer, writer)?;
+
+            // The lines of the anchor are the same in all documents
+            previous_line.clone_from(&documents[0].lines[anchor_length - 1]);
+            let anchor = documents[0].take(anchor_length);
+            documents[1].take(anchor_length);
+            documents[2].take(anchor_length);
+            writer.write_all(anchor.as_bytes())?;
+        }
+
+        let is_exhausted = documents.iter().all(|document| document.is_exhausted);
+        let is_too_long = documents
+            .iter()
+            .any(|document| document.lines.len() >= max_chunk_lines);
+
+        if is_exhausted || is_too_long {
+            for document in &mut documents {
+                document.read_to_end()?;
+            }
+
+            let chunk = documents.map(|mut document| document.take_all());
+            return merge_chunk(&previous_line, &chunk, "", tokenizer, writer);
+        }
+    }
+}
+
+/// The lines of a document which are read but not yet merged
+struct Document<'r> {
+    reader: &'r mut dyn BufRead,
+    lines: Vec<String>,
+    is_exhausted: bool,
+}
+
+impl<'r> Document<'r> {
+    fn new(reader: &'r mut dyn BufRead) -> Self {
+        Self {
+            reader,
+            lines: Vec::new(),
+            is_exhausted: false,
+        }
+    }
+
+    fn read_lines(&mut self, count: usize) -> io::Result<()> {
+        for _ in 0..count {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                self.is_exhausted = true;
+                break;
+            }
+
+            self.lines.push(line);
+        }
+
+        Ok(())
+    }
+
+    fn read_to_end(&mut self) -> io::Result<()> {
+        while !self.is_exhausted {
+            self.read_lines(LINES_PER_READ)?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove the first `line_count` lines and return them joined
+    fn take(&mut self, line_count: usize) -> String {
+        self.lines.drain(..line_count).collect()
+    }
+
+    fn take_all(&mut self) -> String {
+        self.take(self.lines.len())
+    }
+}
+
+/// Merge the `original`, `left`, and `right` versions of a chunk and write the
+/// result. The unchanged lines before and after the chunk are merged with it,
+/// so that its edits are aligned the same way as in the whole documents, but
+/// aren't written.
+fn merge_chunk<T>(
+    before: &str,
+    [original, left, right]: &[String; 3],
+    after: &str,
+    tokenizer: &Tokenizer<T>,
+    writer: &mut impl io::Write,
+) -> io::Result<()>
+where
+    T: PartialEq + Clone + Debug,
+{
+    if original == left && original == right {
+        return writer.write_all(original.as_bytes());
+    }
+
+    let with_context = |text: &str| format!("{before}{text}{after}");
+    let merged = reconcile(
+        &with_context(original),
+        &with_context(left).into(),
+        &with_context(right).into(),
+        tokenizer,
+    )
+    .apply()
+    .text();
+
+    // The context is unchanged, so it's almost always kept as is
+    match merged
+        .strip_prefix(before)
+        .and_then(|merged| merged.strip_suffix(after))
+    {
+        Some(merged) => writer.write_all(merged.as_bytes()),
+        None => {
+            reconcile(original, &left.into(), &right.into(), tokenizer).apply_to_io_writer(writer)
+        }
+    }
+}
+
+/// The index of the first line of the earliest (in the original) run of
+/// `ANCHOR_LINE_COUNT` lines which appears exactly once in each document
+fn find_anchor(documents: &[&[String]; 3]) -> Option<[usize; 3]> {
+    // The number of occurrences and the position of each run of lines
+    let occurrences = documents.map(|lines| {
+        let mut occurrences: HashMap<&[String], (usize, usize)> = HashMap::new();
+        for (index, window) in lines.windows(ANCHOR_LINE_COUNT).enumerate() {
+            occurrences.entry(window).or_insert((0, index)).0 += 1;
+        }
+
+        occurrences
+    });
+
+    documents[0].windows(ANCHOR_LINE_COUNT).find_map(|window| {
+        let mut anchor = [0; 3];
+        for (index, occurrences) in occurrences.iter().enumerate() {
+            match occurrences.get(window) {
+                Some((1, position)) => anchor[index] = *position,
+                _ => return None,
+            }
+        }
+
+        Some(anchor)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use std::fmt::Write;
+
+    use super::*;
+    use crate::BuiltinTokenizer;
+
+    #[test]
+    fn test_find_anchor() {
+        let lines = |text: &str| {
+            text.split_inclusive('\n')
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        };
+        let original = lines("x\na\nb\nc\nd\ne\nf\ng\nh\ny\n");
+        let left = lines("a\nb\nc\nd\ne\nf\ng\nh\n");
+        let right = lines("z\nz\nx\na\nb\nc\nd\ne\nf\ng\nh\n");
+
+        assert_eq!(find_anchor(&[&original, &left, &right]), Some([1, 0, 3]));
+        assert_eq!(find_anchor(&[&original, &left, &left[1..]]), None);
+    }
+
+    #[test]
+    fn test_reconcile_chunked_aligns_edits_with_context() {
+        let lines = |skipped: &[usize]| {
+            (0..10).filter(|index| !skipped.contains(index)).fold(
+                String::new(),
+                |mut lines, index| {
+                    writeln!(lines, "l{index}").unwrap();
+                    lines
+                },
+            )
+        };
+        let (original, left, right) = (lines(&[]), lines(&[1, 6]), lines(&[2, 4]));
+
+        let mut merged = Vec::new();
+        reconcile_chunked(
+            original.as_bytes(),
+            left.as_bytes(),
+            right.as_bytes(),
+            &*BuiltinTokenizer::Word,
+            &mut merged,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(merged).unwrap(),
+            "l0\nl3\nl5\nl7\nl8\nl9\n"
+        );
+        assert_eq!(
+            reconcile(
+                &original,
+                &left.as_str().into(),
+                &right.as_str().into(),
+                &*BuiltinTokenizer::Word
+            )
+            .apply()
+            .text(),
+            "l0\nl3\nl5\nl7\nl8\nl9\n"
+        );
+    }
+
+    #[test]
+    fn test_reconcile_chunked_without_anchor() {
+        // Every run of lines is repeated, so there is no anchor
+        let part = (0..300).fold(String::new(), |mut part, index| {
+            writeln!(part, "line {index}").unwrap();
+            part
+        });
+        let original = part.repeat(8);
+        let left = format!("first\n{}", &original[7..]);
+        let right = format!("{}last\n", &original[..original.len() - 10]);
+
+        let mut readers = [&original, &left, &right].map(String::as_bytes);
+        let [original_reader, left_reader, right_reader] = &mut readers;
+        let documents = [
+            Document::new(original_reader),
+            Document::new(left_reader),
+            Document::new(right_reader),
+        ];
+
+        let mut merged = Vec::new();
+        merge_documents(
+            documents,
+            LINES_PER_READ,
+            &*BuiltinTokenizer::Line,
+            &mut merged,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(merged).unwrap(),
+            format!("first\n{}last\n", &original[7..original.len() - 10])
+        );
+    }
+
+    #[test]
+    fn test_reconcile_chunked() {
+        let log = (0..1_500).fold(String::new(), |mut log, index| {
+            writeln!(log, "{index:04} event number {index}").unwrap();
+            log
+        });
+        let edit_left = |text: &str| {
+            text.replace("0100 event", "0100 edited event")
+                .replace("1200 event number 1200\n", "")
+                + "appended by left\n"
+        };
+        let edit_right = |text: &str| {
+            text.replace("0600 event number", "0600 changed event number")
+                .replace("1201 event", "1201 late event")
+        };
+
+        let mut merged = Vec::new();
+        reconcile_chunked(
+            log.as_bytes(),
+            edit_left(&log).as_bytes(),
+            edit_right(&log).as_bytes(),
+            &*BuiltinTokenizer::Word,
+            &mut merged,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(merged).unwrap(),
+            edit_left(&edit_right(&log))
+        );
+    }
+}
@@ -1,2 +1,9 @@
+pub mod compose_operations;
 pub mod cook_operations;
 pub mod elongate_operations;
+pub mod find_conflicts;
+pub mod find_moves;
+pub mod project_operations;
+pub mod resolve_conflicts;
+pub mod route_moved_edits;
+pub mod split_operations;
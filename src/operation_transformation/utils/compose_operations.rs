@@ -0,0 +1,339 @@
+use std::fmt::Debug;
+
+use crate::{
+    Token, Tokenizer,
+    operation_transformation::Operation,
+    types::{gravity::Gravity, side::Side},
+};
+
+/// Combine the operations turning A into B (`first`) with the ones turning B
+/// into C (`second`) into operations turning A into C. Text which is kept or
+/// deleted by the result is attributed to the side of the operation which
+/// last touched it.
+///
+/// Inserted tokens of `first` which `second` only partially keeps are
+/// re-tokenized with `tokenizer`.
+pub fn compose_operations<T>(
+    first: &[Operation<T>],
+    first_sides: &[Side],
+    second: &[Operation<T>],
+    second_sides: &[Side],
+    tokenizer: &Tokenizer<T>,
+) -> (Vec<Operation<T>>, Vec<Side>)
+where
+    T: PartialEq + Clone + Debug,
+{
+    let mut composition = Composition {
+        operations: Vec::with_capacity(first.len() + second.len()),
+        sides: Vec::with_capacity(first.len() + second.len()),
+        original_index: 0,
+    };
+    let mut first_operations = first.iter().zip(first_sides.iter().copied()).peekable();
+
+    // Characters of the current operation of `first` which are already consumed
+    let mut offset = 0;
+
+    for (operation, side) in second.iter().zip(second_sides.iter().copied()) {
+        if let Operation::Insert { text, .. } = operation {
+            let order = composition.original_index;
+            composition.push(Operation::create_insert(order, text.clone()), side);
+            continue;
+        }
+
+        let is_kept = matches!(operation, Operation::Equal { .. });
+        let mut remaining = operation.len();
+
+        while remaining > 0 {
+            let Some(&(first_operation, first_side)) = first_operations.peek() else {
+                break;
+            };
+            let order = composition.original_index;
+
+            let taken = (first_operation.len() - offset).min(remaining);
+            match first_operation {
+                Operation::Delete {
+                    deleted_character_count,
+                    ..
+                } => {
+                    composition.push(
+                        Operation::create_delete(order, *deleted_character_count),
+                        first_side,
+                    );
+                    first_operations.next();
+                    continue;
+                }
+                Operation::Equal { .. } if is_kept => {
+                    composition.push(Operation::create_equal(order, taken), first_side);
+                }
+                Operation::Equal { .. } => {
+                    composition.push(Operation::create_delete(order, taken), side);
+                }
+                Operation::Insert { text, .. } if is_kept => {
+                    let tokens = slice_tokens(text, offset, taken, tokenizer);
+                    composition.push(Operation::create_insert(order, tokens), first_side);
+                }
+                Operation::Insert { .. } => {}
+            }
+
+            remaining -= taken;
+            offset += taken;
+            if offset == first_operation.len() {
+                first_operations.next();
+                offset = 0;
+            }
+        }
+    }
+
+    // The deletes right after the text `second` ends with
+    for (operation, side) in first_operations {
+        if let Operation::Delete {
+            deleted_character_count,
+            ..
+        } = operation
+        {
+            let order = composition.original_index;
+            composition.push(
+                Operation::create_delete(order, *deleted_character_count),
+                side,
+            );
+        }
+    }
+
+    (composition.operations, composition.sides)
+}
+
+/// Map a character index in the original text of `operations` to the index
+/// in the text they produce. Text deleted around the index collapses it, and
+/// `gravity` decides whether it stays before or moves after text inserted at
+/// it.
+pub fn transform_index<T>(operations: &[Operation<T>], index: usize, gravity: Gravity) -> usize
+where
+    T: PartialEq + Clone + Debug,
+{
+    let (mut old_index, mut new_index) = (0, 0);
+
+    for operation in operations {
+        match operation {
+            Operation::Equal { length, .. } => {
+                if index < old_index + length {
+                    return new_index + index - old_index;
+                }
+                old_index += length;
+                new_index += length;
+            }
+            Operation::Delete {
+                deleted_character_count,
+                ..
+            } => {
+                if index < old_index + deleted_character_count {
+                    return new_index;
+                }
+                old_index += deleted_character_count;
+            }
+            Operation::Insert { .. } => {
+                if index == old_index && gravity.stays_before_insertion_at(index) {
+                    return new_index;
+                }
+                new_index += operation.len();
+            }
+        }
+    }
+
+    new_index + index.saturating_sub(old_index)
+}
+
+/// The composed operations being built, with adjacent operations of the same
+/// kind and side joined
+struct Composition<T>
+where
+    T: PartialEq + Clone + Debug,
+{
+    operations: Vec<Operation<T>>,
+    sides: Vec<Side>,
+
+    /// Index in the original text of `first` where the next operation starts
+    original_index: usize,
+}
+
+impl<T> Composition<T>
+where
+    T: PartialEq + Clone + Debug,
+{
+    fn push(&mut self, operation: Operation<T>, side: Side) {
+        if operation.len() == 0 {
+            return;
+        }
+
+        if !matches!(operation, Operation::Insert { .. }) {
+            self.original_index += operation.len();
+        }
+
+        let joined = match (self.operations.last(), self.sides.last(), &operation) {
+            (
+                Some(Operation::Equal { order, length, .. }),
+                Some(last_side),
+                Operation::Equal { .. },
+            ) if *last_side == side => {
+                Some(Operation::create_equal(*order, length + operation.len()))
+            }
+            (
+                Some(Operation::Delete {
+                    order,
+                    deleted_character_count,
+                    ..
+                }),
+                Some(last_side),
+                Operation::Delete { .. },
+            ) if *last_side == side => Some(Operation::create_delete(
+                *order,
+                deleted_character_count + operation.len(),
+            )),
+            (
+                Some(Operation::Insert { order, text }),
+                Some(last_side),
+                Operation::Insert {
+                    order: next_order,
+                    text: next_text,
+                },
+            ) if *last_side == side && order == next_order => Some(Operation::create_insert(
+                *order,
+                text.iter().chain(next_text).cloned().collect(),
+            )),
+            _ => None,
+        };
+
+        if let Some(joined) = joined {
+            *self.operations.last_mut().unwrap() = joined;
+        } else {
+            self.operations.push(operation);
+            self.sides.push(side);
+        }
+    }
+}
+
+/// The tokens covering `length` characters from `offset` of `tokens`, with
+/// the cut tokens at the ends re-tokenized
+fn slice_tokens<T>(
+    tokens: &[Token<T>],
+    offset: usize,
+    length: usize,
+    tokenizer: &Tokenizer<T>,
+) -> Vec<Token<T>>
+where
+    T: PartialEq + Clone + Debug,
+{
+    let mut result = Vec::new();
+    let mut token_start = 0;
+
+    for token in tokens {
+        let token_length = token.get_original_length();
+        let from = offset.max(token_start);
+        let to = (offset + length).min(token_start + token_length);
+
+        if from == token_start && to == token_start + token_length {
+            result.push(token.clone());
+        } else if from < to {
+            let text: String = token
+                .original()
+                .chars()
+                .skip(from - token_start)
+                .take(to - from)
+                .collect();
+            result.extend(tokenizer(&text));
+        }
+
+        token_start += token_length;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::BuiltinTokenizer;
+
+    fn tokens(text: &str) -> Vec<Token<String>> {
+        (*BuiltinTokenizer::Word)(text)
+    }
+
+    #[test]
+    fn test_compose_operations() {
+        // "abcdef" -> "aXYdef" -> "aXdeZ"
+        let first: Vec<Operation<String>> = vec![
+            Operation::create_equal(0, 1),
+            Operation::create_delete(1, 2),
+            Operation::create_insert(3, tokens("XY")),
+            Operation::create_equal(3, 3),
+        ];
+        let second: Vec<Operation<String>> = vec![
+            Operation::create_equal(0, 2),
+            Operation::create_delete(2, 1),
+            Operation::create_equal(3, 2),
+            Operation::create_delete(5, 1),
+            Operation::create_insert(6, tokens("Z")),
+        ];
+
+        let (operations, sides) = compose_operations(
+            &first,
+            &[Side::Left; 4],
+            &second,
+            &[Side::Right; 5],
+            &*BuiltinTokenizer::Word,
+        );
+
+        assert_eq!(
+            operations,
+            vec![
+                Operation::create_equal(0, 1),
+                Operation::create_delete(1, 2),
+                Operation::create_insert(3, tokens("X")),
+                Operation::create_equal(3, 2),
+                Operation::create_delete(5, 1),
+                Operation::create_insert(6, tokens("Z")),
+            ]
+        );
+        assert_eq!(
+            sides,
+            vec![
+                Side::Left,
+                Side::Left,
+                Side::Left,
+                Side::Left,
+                Side::Right,
+                Side::Right
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transform_index() {
+        // "abcdef" -> "aXdef"
+        let operations: Vec<Operation<String>> = vec![
+            Operation::create_equal(0, 1),
+            Operation::create_delete(1, 2),
+            Operation::create_insert(3, tokens("X")),
+            Operation::create_equal(3, 3),
+        ];
+
+        let transformed = |index, gravity| transform_index(&operations, index, gravity);
+        assert_eq!(transformed(0, Gravity::Before), 0);
+        assert_eq!(transformed(2, Gravity::Before), 1);
+        assert_eq!(transformed(3, Gravity::Before), 1);
+        assert_eq!(transformed(3, Gravity::After), 2);
+        assert_eq!(transformed(6, Gravity::Before), 5);
+
+        // "abc" -> "Xabc"
+        let operations: Vec<Operation<String>> = vec![
+            Operation::create_insert(0, tokens("X")),
+            Operation::create_equal(0, 3),
+        ];
+
+        let transformed = |index, gravity| transform_index(&operations, index, gravity);
+        assert_eq!(transformed(0, Gravity::Before), 0);
+        assert_eq!(transformed(0, Gravity::After), 1);
+        assert_eq!(transformed(0, Gravity::Legacy), 1);
+    }
+}
@@ -0,0 +1,251 @@
+use std::{fmt::Debug, ops::Range};
+
+use crate::{Token, operation_transformation::Operation, types::conflict::Conflict};
+
+/// Consecutive inserts and deletes of a single side without any retained text
+/// between them
+#[derive(Debug)]
+struct Hunk {
+    side: usize,
+    parent_range: Range<usize>,
+    operations: Range<usize>,
+}
+
+/// Find the regions of `text` which more than one side changed in
+/// overlapping or touching spans, similarly to how `diff3` finds conflicting
+/// hunks. Regions where all sides made the same change aren't conflicts.
+///
+/// Returns the conflicts with empty merged ranges (these are only known once
+/// the operations are merged) and, for each side, the index of the conflict
+/// each of its operations belongs to.
+pub fn find_conflicts<T>(
+    text: &str,
+    sides: &[&[Operation<T>]],
+) -> (Vec<Conflict>, Vec<Vec<Option<usize>>>)
+where
+    T: PartialEq + Clone + Debug,
+{
+    let mut conflict_indices: Vec<Vec<Option<usize>>> = sides
+        .iter()
+        .map(|operations| vec![None; operations.len()])
+        .collect();
+
+    let mut hunks = sides
+        .iter()
+        .enumerate()
+        .flat_map(|(side, operations)| find_hunks(side, operations))
+        .collect::<Vec<_>>();
+    hunks.sort_by_key(|hunk| (hunk.parent_range.start, hunk.side));
+
+    let mut clusters: Vec<(Range<usize>, Vec<Hunk>)> = Vec::new();
+    for hunk in hunks {
+        if let Some((range, cluster)) = clusters.last_mut()
+            && hunk.parent_range.start <= range.end
+        {
+            range.end = range.end.max(hunk.parent_range.end);
+            cluster.push(hunk);
+        } else {
+            clusters.push((hunk.parent_range.clone(), vec![hunk]));
+        }
+    }
+
+    let mut chars: Option<Vec<char>> = None;
+    let mut conflicts = Vec::new();
+
+    for (range, cluster) in clusters {
+        if cluster.iter().all(|hunk| hunk.side == cluster[0].side) {
+            continue;
+        }
+
+        let chars = chars.get_or_insert_with(|| text.chars().collect());
+        let versions = (0..sides.len())
+            .map(|side| {
+                side_version(
+                    chars,
+                    range.clone(),
+                    sides[side],
+                    cluster.iter().filter(|hunk| hunk.side == side),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if versions.iter().all(|version| *version == versions[0]) {
+            continue;
+        }
+
+        for hunk in &cluster {
+            for index in hunk.operations.clone() {
+                conflict_indices[hunk.side][index] = Some(conflicts.len());
+            }
+        }
+
+        conflicts.push(Conflict {
+            base: chars[range.clone()].iter().collect(),
+            parent_range: range,
+            merged_range: 0..0,
+            versions,
+        });
+    }
+
+    (conflicts, conflict_indices)
+}
+
+fn find_hunks<T>(side: usize, operations: &[Operation<T>]) -> Vec<Hunk>
+where
+    T: PartialEq + Clone + Debug,
+{
+    let mut hunks = Vec::new();
+    let mut current: Option<Hunk> = None;
+
+    for (index, operation) in operations.iter().enumerate() {
+        let (start, end) = match operation {
+            Operation::Equal { .. } => {
+                hunks.extend(current.take());
+                continue;
+            }
+            Operation::Insert { order, .. } => (*order, *order),
+            Operation::Delete {
+                order,
+                deleted_character_count,
+                ..
+            } => (*order, order + deleted_character_count),
+        };
+
+        match current.as_mut() {
+            Some(hunk) => {
+                hunk.parent_range.end = hunk.parent_range.end.max(end);
+                hunk.operations.end = index + 1;
+            }
+            None => {
+                current = Some(Hunk {
+                    side,
+                    parent_range: start..end,
+                    operations: index..index + 1,
+                });
+            }
+        }
+    }
+
+    hunks.extend(current);
+    hunks
+}
+
+/// Apply the changes of one side's hunks to the `range` of the original text
+fn side_version<'a, T>(
+    chars: &[char],
+    range: Range<usize>,
+    operations: &[Operation<T>],
+    hunks: impl Iterator<Item = &'a Hunk>,
+) -> String
+where
+    T: PartialEq + Clone + Debug,
+{
+    let mut version = String::new();
+    let mut position = range.start;
+
+    for hunk in hunks {
+        for operation in &operations[hunk.operations.clone()] {
+            match operation {
+                Operation::Insert { order, text } => {
+                    version.extend(&chars[position..position.max(*order)]);
+                    position = position.max(*order);
+                    version.extend(text.iter().map(Token::original));
+                }
+                Operation::Delete {
+                    order,
+                    deleted_character_count,
+                    ..
+                } => {
+                    version.extend(&chars[position..position.max(*order)]);
+                    position = order + deleted_character_count;
+                }
+                Operation::Equal { .. } => unreachable!("Hunks only contain inserts and deletes"),
+            }
+        }
+    }
+
+    version.extend(&chars[position..range.end]);
+    version
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn insert(order: usize, text: &str) -> Operation<String> {
+        Operation::create_insert(order, vec![text.into()])
+    }
+
+    #[test]
+    fn test_overlapping_hunks_conflict() {
+        let text = "a b c";
+        let left = [
+            Operation::create_equal(0, 2),
+            Operation::create_delete(2, 1),
+            insert(3, "x"),
+            Operation::create_equal(3, 2),
+        ];
+        let right = [
+            Operation::create_equal(0, 2),
+            Operation::create_delete(2, 1),
+            insert(3, "y"),
+            Operation::create_equal(3, 2),
+        ];
+
+        let (conflicts, indices) = find_conflicts(text, &[&left, &right]);
+
+        assert_eq!(
+            conflicts,
+            vec![Conflict {
+                parent_range: 2..3,
+                merged_range: 0..0,
+                base: "b".to_owned(),
+                versions: vec!["x".to_owned(), "y".to_owned()],
+            }]
+        );
+        assert_eq!(indices[0], vec![None, Some(0), Some(0), None]);
+        assert_eq!(indices[1], vec![None, Some(0), Some(0), None]);
+    }
+
+    #[test]
+    fn test_identical_and_distant_changes_do_not_conflict() {
+        let text = "a b c";
+        let left = [
+            Operation::create_delete(0, 1),
+            Operation::create_equal(1, 4),
+        ];
+        let right = [
+            Operation::create_delete(0, 1),
+            Operation::create_equal(1, 3),
+            insert(4, "!"),
+            Operation::create_equal(4, 1),
+        ];
+
+        let (conflicts, indices) = find_conflicts(text, &[&left, &right]);
+
+        assert!(conflicts.is_empty());
+        assert!(indices.iter().flatten().all(Option::is_none));
+    }
+
+    #[test]
+    fn test_touching_hunks_conflict() {
+        let text = "ab";
+        let left = [
+            Operation::create_delete(0, 1),
+            Operation::create_equal(1, 1),
+        ];
+        let right = [
+            Operation::create_equal(0, 1),
+            insert(1, "x"),
+            Operation::create_equal(1, 1),
+        ];
+
+        let (conflicts, _) = find_conflicts(text, &[&left, &right]);
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].parent_range, 0..1);
+        assert_eq!(conflicts[0].versions, vec!["", "ax"]);
+    }
+}
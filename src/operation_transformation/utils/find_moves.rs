@@ -0,0 +1,349 @@
+use std::{collections::HashMap, fmt::Debug, iter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Token,
+    raw_operation::RawOperation,
+    utils::{diff_budget::DiffBudget, edit::Edit, myers_diff::myers_diff_with_budget},
+};
+
+/// Deletes shorter than this (in characters) are not considered to be moved,
+/// because short runs of tokens often reappear elsewhere by chance
+const MIN_MOVED_CHARACTER_COUNT: usize = 32;
+
+/// The minimum share of the characters a delete and an insert must have in
+/// common to be considered a move, so that the moved block can be edited too
+const MIN_MOVE_SIMILARITY: f64 = 0.8;
+
+/// The number of inserts closest in length to a delete which are diffed
+/// against it. Exact copies are found among any number of inserts.
+const MAX_MOVE_CANDIDATES: usize = 16;
+
+/// A block of text which one side deleted and inserted (possibly slightly
+/// edited) elsewhere
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Move<T>
+where
+    T: PartialEq + Clone + Debug,
+{
+    /// Index of the delete operation removing the block from its source
+    pub delete_index: usize,
+
+    /// Index of the insert operation adding the block at its target
+    pub insert_index: usize,
+
+    /// The deleted tokens of the block
+    pub source: Vec<Token<T>>,
+}
+
+/// Pair large deletes with inserts of the same (or a near-identical) run of
+/// tokens elsewhere in the text. Myers' algorithm represents moves as a
+/// separate delete and insert, this recovers them so that concurrent edits
+/// within a moved block can follow it.
+///
+/// Stops looking once the deadline of `budget` has passed, which the budget
+/// then reports as exceeded.
+///
+/// Expects elongated operations, and the indexes of the returned moves refer to
+/// them.
+pub fn find_moves<T>(operations: &[RawOperation<T>], budget: &DiffBudget) -> Vec<Move<T>>
+where
+    T: PartialEq + Clone + Debug,
+{
+    // The number of retained runs before each operation, so that whether
+    // there's retained text between two operations takes constant time
+    let equals_before = iter::once(0)
+        .chain(operations.iter().scan(0, |count, operation| {
+            *count += usize::from(matches!(operation, RawOperation::Equal(..)));
+            Some(*count)
+        }))
+        .collect::<Vec<_>>();
+
+    let mut inserts_by_length = Vec::new();
+    let mut inserts_by_text: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, operation) in operations.iter().enumerate() {
+        if matches!(operation, RawOperation::Insert(..)) {
+            inserts_by_length.push((operation.original_text_length(), index));
+            inserts_by_text
+                .entry(operation.get_original_text())
+                .or_default()
+                .push(index);
+        }
+    }
+    inserts_by_length.sort_unstable();
+
+    let mut is_taken = vec![false; operations.len()];
+    let mut moves: Vec<Move<T>> = Vec::new();
+
+    for (delete_index, delete) in operations.iter().enumerate() {
+        let RawOperation::Delete(source) = delete else {
+            continue;
+        };
+
+        let source_length = delete.original_text_length();
+        if source_length < MIN_MOVED_CHARACTER_COUNT {
+            continue;
+        }
+
+        if !budget.allows(0) {
+            break;
+        }
+
+        // Whether there's retained text between the operations, otherwise,
+        // the insert is just the replacement of the deleted text
+        let is_candidate = |insert_index: &usize| {
+            !is_taken[*insert_index]
+                && equals_before[delete_index.max(*insert_index)]
+                    != equals_before[delete_index.min(*insert_index)]
+        };
+
+        let exact_copy = inserts_by_text
+            .get(&delete.get_original_text())
+            .and_then(|indexes| indexes.iter().copied().find(is_candidate));
+
+        let best_match = exact_copy.or_else(|| {
+            nearest_in_length(&inserts_by_length, source_length)
+                .filter(is_candidate)
+                .take(MAX_MOVE_CANDIDATES)
+                .map(|insert_index| {
+                    let target = operations[insert_index].tokens();
+                    (
+                        insert_index,
+                        similarity(source, source_length, target, budget),
+                    )
+                })
+                .filter(|(_, similarity)| *similarity >= MIN_MOVE_SIMILARITY)
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(insert_index, _)| insert_index)
+        });
+
+        if let Some(insert_index) = best_match {
+            is_taken[insert_index] = true;
+            moves.push(Move {
+                delete_index,
+                insert_index,
+                source: source.clone(),
+            });
+        }
+    }
+
+    moves
+}
+
+/// Indexes of the inserts (sorted by their length) which are long enough and
+/// short enough to be similar to a delete of `length` characters, closest in
+/// length first
+fn nearest_in_length(
+    inserts_by_length: &[(usize, usize)],
+    length: usize,
+) -> impl Iterator<Item = usize> + '_ {
+    let middle = inserts_by_length.partition_point(|(insert_length, _)| *insert_length < length);
+
+    // Runs outside these bounds have less than `MIN_MOVE_SIMILARITY` in common
+    let mut shorter = inserts_by_length[..middle]
+        .iter()
+        .rev()
+        .take_while(move |(insert_length, _)| 3 * insert_length >= 2 * length)
+        .peekable();
+    let mut longer = inserts_by_length[middle..]
+        .iter()
+        .take_while(move |(insert_length, _)| 2 * insert_length <= 3 * length)
+        .peekable();
+
+    iter::from_fn(move || {
+        let is_shorter_closer = match (shorter.peek(), longer.peek()) {
+            (Some((shorter_length, _)), Some((longer_length, _))) => {
+                length - shorter_length <= longer_length - length
+            }
+            (shorter, _) => shorter.is_some(),
+        };
+
+        let next = if is_shorter_closer {
+            shorter.next()
+        } else {
+            longer.next()
+        };
+
+        next.map(|(_, index)| *index)
+    })
+}
+
+/// Share of the characters of the two token runs which are in common
+#[allow(clippy::cast_precision_loss)]
+fn similarity<T>(
+    source: &[Token<T>],
+    source_length: usize,
+    target: &[Token<T>],
+    budget: &DiffBudget,
+) -> f64
+where
+    T: PartialEq + Clone + Debug,
+{
+    let target_length: usize = target.iter().map(Token::get_original_length).sum();
+    let total_length = (source_length + target_length) as f64;
+
+    // Runs needing many more edits can't be similar enough, so there's no
+    // need to find their shortest edit script
+    let budget = budget.with_max_edit_distance((source.len() + target.len()) / 5 + 1);
+
+    let source_edits = align_moved_block(source, target, &budget)
+        .into_iter()
+        .filter(|edit| *edit != Edit::Insert);
+    let common_length: usize = source
+        .iter()
+        .zip(source_edits)
+        .filter(|(_, edit)| *edit == Edit::Equal)
+        .map(|(token, _)| token.get_original_length())
+        .sum();
+
+    2.0 * common_length as f64 / total_length
+}
+
+/// Diff the tokens of a moved block and its target. The whitespace at the
+/// edges is diffed on its own, as the separator of a block often ends up at
+/// its other end (like "\nBlock" becoming "Block\n"), and Myers' algorithm
+/// could align the separators instead of the content.
+pub fn align_moved_block<T>(
+    source: &[Token<T>],
+    target: &[Token<T>],
+    budget: &DiffBudget,
+) -> Vec<Edit>
+where
+    T: PartialEq + Clone + Debug,
+{
+    split_whitespace_edges(source)
+        .into_iter()
+        .zip(split_whitespace_edges(target))
+        .flat_map(|(source, target)| myers_diff_with_budget(source, target, budget))
+        .collect()
+}
+
+/// Split the tokens into the leading whitespace, the rest, and the trailing
+/// whitespace
+fn split_whitespace_edges<T>(tokens: &[Token<T>]) -> [&[Token<T>]; 3]
+where
+    T: PartialEq + Clone + Debug,
+{
+    let is_content = |token: &Token<T>| !token.original().chars().all(char::is_whitespace);
+    let start = tokens.iter().position(is_content).unwrap_or(tokens.len());
+    let end = tokens
+        .iter()
+        .rposition(is_content)
+        .map_or(start, |index| index + 1);
+
+    [&tokens[..start], &tokens[start..end], &tokens[end..]]
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        BuiltinTokenizer, operation_transformation::utils::elongate_operations::elongate_operations,
+    };
+
+    fn moves_between(original: &str, updated: &str) -> Vec<(String, String)> {
+        moves_between_with_tokenizer(original, updated, BuiltinTokenizer::Word)
+    }
+
+    fn moves_between_with_tokenizer(
+        original: &str,
+        updated: &str,
+        tokenizer: BuiltinTokenizer,
+    ) -> Vec<(String, String)> {
+        let operations = elongate_operations(RawOperation::vec_from(
+            (*tokenizer)(original),
+            (*tokenizer)(updated),
+        ));
+
+        find_moves(&operations, &DiffBudget::unlimited())
+            .into_iter()
+            .map(|m| {
+                (
+                    operations[m.delete_index].get_original_text(),
+                    operations[m.insert_index].get_original_text(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_exact_move() {
+        assert_eq!(
+            moves_between(
+                "Title\nThis paragraph is about to be moved.\nThe last paragraph is longer than the \
+                 moved one.",
+                "Title\nThe last paragraph is longer than the moved one.\nThis paragraph is about \
+                 to be moved.",
+            ),
+            vec![(
+                "\nThis paragraph is about to be moved.".to_owned(),
+                "\nThis paragraph is about to be moved.".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_find_edited_move() {
+        assert_eq!(
+            moves_between(
+                "Intro. The quick brown fox jumps over the lazy dog. It is a long day in the \
+                 countryside for both of them.",
+                "Intro. It is a long day in the countryside for both of them. The quick brown fox \
+                 leaps over the lazy dog.",
+            )
+            .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_ignore_replacements_and_short_moves() {
+        assert!(
+            moves_between(
+                "Keep this. The quick brown fox jumps over the lazy dog.",
+                "Keep this. The quick brown fox jumped over the lazy dog!",
+            )
+            .is_empty()
+        );
+        assert!(moves_between("a b c d", "b c d a").is_empty());
+    }
+
+    #[test]
+    fn test_find_move_with_separator_at_other_end() {
+        assert_eq!(
+            moves_between_with_tokenizer(
+                "Alpha is the first line.\nBravo is the line which gets moved.\nCharlie.\n",
+                "Bravo is the line which gets moved.\nAlpha is the first line.\nCharlie.\n",
+                BuiltinTokenizer::Line,
+            ),
+            vec![(
+                "\nBravo is the line which gets moved.".to_owned(),
+                "Bravo is the line which gets moved.\n".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_nearest_in_length() {
+        let inserts = [
+            (10, 0),
+            (20, 1),
+            (29, 2),
+            (30, 3),
+            (32, 4),
+            (45, 5),
+            (46, 6),
+        ];
+
+        assert_eq!(
+            nearest_in_length(&inserts, 30).collect::<Vec<_>>(),
+            vec![3, 2, 4, 1, 5]
+        );
+        assert_eq!(nearest_in_length(&inserts, 100).count(), 0);
+    }
+}
@@ -0,0 +1,302 @@
+use std::{collections::BTreeMap, fmt::Debug, ops::Range};
+
+use crate::{
+    Token,
+    operation_transformation::Operation,
+    utils::{edit::Edit, myers_diff::myers_diff},
+};
+
+/// Express the operations of a merge over the text which one of the merged
+/// sides (`base`) results in, instead of the original text. What `base`
+/// inserted is retained, what it deleted is left out, and the changes of the
+/// other sides remain.
+///
+/// `merged` holds the merged operations with the part of the original text
+/// they retain or delete. Returns the text `base` results in and the
+/// operations turning it into the merged text.
+pub fn project_operations<'a, T>(
+    merged: impl Iterator<Item = (&'a Operation<T>, &'a str)>,
+    base: &[Operation<T>],
+) -> (String, Vec<Operation<T>>)
+where
+    T: PartialEq + Clone + Debug + 'a,
+{
+    let mut projection = Projection::new(base);
+    let mut original_index = 0;
+
+    for (operation, original) in merged {
+        if let Operation::Insert { order, text } = operation {
+            projection.insert(*order, text);
+            continue;
+        }
+
+        projection.flush_insertions();
+        match operation {
+            Operation::Equal { .. } => {
+                projection.flush_base_insertions(original_index + 1);
+                projection.flush_deleted(usize::MAX);
+                projection.push_original(original, true);
+            }
+            Operation::Delete { .. } => projection.delete(original_index, original),
+            Operation::Insert { .. } => unreachable!(),
+        }
+
+        original_index += operation.len();
+    }
+
+    projection.flush_insertions();
+    projection.flush_base_insertions(usize::MAX);
+    projection.flush_deleted(usize::MAX);
+
+    (projection.text, projection.operations)
+}
+
+/// The projected operations being built
+struct Projection<'a, T>
+where
+    T: PartialEq + Clone + Debug,
+{
+    text: String,
+    operations: Vec<Operation<T>>,
+
+    /// Character index in `text` where the next operation starts
+    order: usize,
+
+    /// The ranges of the original text which `base` deletes
+    base_deletes: Vec<Range<usize>>,
+
+    /// The tokens `base` inserts at each index of the original text
+    base_insertions: BTreeMap<usize, Vec<&'a Token<T>>>,
+
+    /// The merged insertions at `insertion_order` which aren't added yet
+    insertions: Vec<&'a Token<T>>,
+    insertion_order: usize,
+
+    /// The characters only the other sides delete with their index in the
+    /// original text. They are added after the insertions of `base` at their
+    /// index, as they follow those in the text `base` results in.
+    deleted: Vec<(usize, char)>,
+}
+
+impl<'a, T> Projection<'a, T>
+where
+    T: PartialEq + Clone + Debug,
+{
+    fn new(base: &'a [Operation<T>]) -> Self {
+        let mut base_deletes = Vec::new();
+        let mut base_insertions: BTreeMap<usize, Vec<&Token<T>>> = BTreeMap::new();
+        let mut original_index = 0;
+
+        for operation in base {
+            match operation {
+                Operation::Equal { length, .. } => original_index += length,
+                Operation::Insert { order, text } => {
+                    base_insertions.entry(*order).or_default().extend(text);
+                }
+                Operation::Delete {
+                    deleted_character_count,
+                    ..
+                } => {
+                    base_deletes.push(original_index..original_index + deleted_character_count);
+                    original_index += deleted_character_count;
+                }
+            }
+        }
+
+        Self {
+            text: String::new(),
+            operations: Vec::new(),
+            order: 0,
+            base_deletes,
+            base_insertions,
+            insertions: Vec::new(),
+            insertion_order: 0,
+            deleted: Vec::new(),
+        }
+    }
+
+    fn insert(&mut self, order: usize, tokens: &'a [Token<T>]) {
+        if order != self.insertion_order {
+            self.flush_insertions();
+            self.insertion_order = order;
+        }
+
+        self.insertions.extend(tokens);
+    }
+
+    fn delete(&mut self, original_index: usize, original: &str) {
+        let is_deleted_by_base = |index: usize| {
+            let range_index = self
+                .base_deletes
+                .partition_point(|range| range.end <= index);
+            self.base_deletes
+                .get(range_index)
+                .is_some_and(|range| range.contains(&index))
+        };
+
+        let deleted = original
+            .chars()
+            .enumerate()
+            .map(|(offset, character)| (original_index + offset, character))
+            .filter(|(index, _)| !is_deleted_by_base(*index))
+            .collect::<Vec<_>>();
+        self.deleted.extend(deleted);
+    }
+
+    /// Add the pending insertions at `insertion_order`. Merging can drop or
+    /// reorder the duplicates of insertions, so the insertions of `base` are
+    /// aligned with the merged ones instead of relying on the sides of the
+    /// merged operations.
+    fn flush_insertions(&mut self) {
+        if self.insertions.is_empty() {
+            return;
+        }
+
+        self.flush_base_insertions(self.insertion_order);
+        self.flush_deleted(self.insertion_order);
+
+        let expected = self
+            .base_insertions
+            .remove(&self.insertion_order)
+            .unwrap_or_default();
+        let insertions = std::mem::take(&mut self.insertions);
+
+        let mut run_kind = Edit::Equal;
+        let mut run_text = String::new();
+        let mut run_tokens = Vec::new();
+        let (mut expected_index, mut insertion_index) = (0, 0);
+
+        for edit in myers_diff(&expected, &insertions) {
+            if edit != run_kind {
+                self.push_run(run_kind, &mut run_text, &mut run_tokens);
+                run_kind = edit;
+            }
+
+            match edit {
+                Edit::Equal => {
+                    run_text.push_str(insertions[insertion_index].original());
+                    expected_index += 1;
+                    insertion_index += 1;
+                }
+                Edit::Delete => {
+                    run_text.push_str(expected[expected_index].original());
+                    expected_index += 1;
+                }
+                Edit::Insert => {
+                    run_tokens.push(insertions[insertion_index].clone());
+                    insertion_index += 1;
+                }
+            }
+        }
+
+        self.push_run(run_kind, &mut run_text, &mut run_tokens);
+    }
+
+    /// Delete the insertions of `base` before `before` in the original text
+    /// which didn't make it into the merge
+    fn flush_base_insertions(&mut self, before: usize) {
+        while let Some(entry) = self.base_insertions.first_entry()
+            && *entry.key() < before
+        {
+            let (order, tokens) = entry.remove_entry();
+            self.flush_deleted(order);
+
+            let text: String = tokens.into_iter().map(Token::original).collect();
+            self.push_original(&text, false);
+        }
+    }
+
+    fn push_run(&mut self, kind: Edit, text: &mut String, tokens: &mut Vec<Token<T>>) {
+        match kind {
+            Edit::Equal => self.push_original(text, true),
+            Edit::Delete => self.push_original(text, false),
+            Edit::Insert => self.push_insert(std::mem::take(tokens)),
+        }
+
+        text.clear();
+    }
+
+    /// Add the pending deleted characters before `before` in the original
+    fn flush_deleted(&mut self, before: usize) {
+        let split = self.deleted.partition_point(|(index, _)| *index < before);
+        let deleted: String = self
+            .deleted
+            .drain(..split)
+            .map(|(_, character)| character)
+            .collect();
+
+        self.push_original(&deleted, false);
+    }
+
+    fn push_insert(&mut self, tokens: Vec<Token<T>>) {
+        if !tokens.is_empty() {
+            self.operations
+                .push(Operation::create_insert(self.order, tokens));
+        }
+    }
+
+    /// Add an operation keeping or deleting `original`, which is part of the
+    /// text `base` results in
+    fn push_original(&mut self, original: &str, is_kept: bool) {
+        if original.is_empty() {
+            return;
+        }
+
+        let length = original.chars().count();
+        self.operations
+            .push(match (is_kept, cfg!(debug_assertions)) {
+                (true, true) => Operation::create_equal_with_text(self.order, original.to_owned()),
+                (true, false) => Operation::create_equal(self.order, length),
+                (false, true) => {
+                    Operation::create_delete_with_text(self.order, original.to_owned())
+                }
+                (false, false) => Operation::create_delete(self.order, length),
+            });
+
+        self.text.push_str(original);
+        self.order += length;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn tokens(text: &str) -> Vec<Token<String>> {
+        text.split_inclusive(' ').map(Token::from).collect()
+    }
+
+    #[test]
+    fn test_project_operations() {
+        // "a b c " merged from `base` turning it into "a x c " and another side
+        // deleting "b " and inserting "x " (which is deduplicated) and "y "
+        let base: Vec<Operation<String>> = vec![
+            Operation::create_equal(0, 2),
+            Operation::create_delete(2, 2),
+            Operation::create_insert(4, tokens("x ")),
+            Operation::create_equal(4, 2),
+        ];
+        let merged: Vec<Operation<String>> = vec![
+            Operation::create_equal(0, 2),
+            Operation::create_delete(2, 2),
+            Operation::create_insert(4, tokens("x y ")),
+            Operation::create_equal(4, 2),
+        ];
+        let originals = ["a ", "b ", "", "c "];
+
+        let (text, operations) = project_operations(merged.iter().zip(originals), &base);
+
+        assert_eq!(text, "a x c ");
+        assert_eq!(
+            operations,
+            vec![
+                Operation::create_equal_with_text(0, "a ".to_owned()),
+                Operation::create_equal_with_text(2, "x ".to_owned()),
+                Operation::create_insert(4, tokens("y ")),
+                Operation::create_equal_with_text(4, "c ".to_owned()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use std::{fmt::Debug, ops::Range};
+
+use crate::{
+    CursorPosition, Token, Tokenizer,
+    operation_transformation::{
+        Operation,
+        merge_strategy::{MergeStrategy, Resolution},
+    },
+    types::conflict::Conflict,
+    utils::find_longest_prefix_contained_within::find_longest_prefix_contained_within,
+};
+
+/// The operations and cursors of one side, which can be rewritten before the
+/// sides are merged
+pub type SideParts<'a, T> = (&'a mut Vec<Operation<T>>, &'a mut Vec<CursorPosition>);
+
+/// New operations for the region of a conflict on one side
+struct Replacement<T>
+where
+    T: PartialEq + Clone + Debug,
+{
+    side: usize,
+    operations: Vec<Operation<T>>,
+
+    /// Character range of the text inserted on the first involved side where
+    /// this side's cursors within the region are moved to, if the side's
+    /// text in the region is moved there
+    cursor_target: Option<Range<usize>>,
+}
+
+/// Rewrite the operations of the sides involved in each conflict, so that
+/// merging them yields the resolution chosen by `strategy`. Interleaving is
+/// what merging does anyway, so those conflicts are left untouched.
+///
+/// `conflict_indices` (the conflict each operation belongs to) is kept in sync
+/// with the rewritten operations. Cursors within rewritten regions follow
+/// their side's text if it's kept, otherwise, they move to the region's start.
+pub fn resolve_conflicts<T>(
+    conflicts: &[Conflict],
+    conflict_indices: &mut [Vec<Option<usize>>],
+    sides: &mut [SideParts<'_, T>],
+    strategy: &dyn MergeStrategy,
+    tokenizer: &Tokenizer<T>,
+) where
+    T: PartialEq + Clone + Debug,
+{
+    // Going backwards keeps the operation indexes of the earlier conflicts valid
+    for (index, conflict) in conflicts.iter().enumerate().rev() {
+        let involved_sides = (0..sides.len())
+            .filter(|side| conflict_indices[*side].contains(&Some(index)))
+            .collect::<Vec<_>>();
+
+        let replacements = match strategy.resolve(conflict) {
+            Resolution::Interleave => continue,
+            Resolution::Side(winner) => {
+                debug_assert!(
+                    winner.index() < sides.len(),
+                    "The strategy picked {winner}, but only {} sides are merged",
+                    sides.len()
+                );
+                // A side which isn't merged is a bug in the strategy, the
+                // conflict is interleaved in release builds
+                let Some((winner_operations, _)) = sides.get(winner.index()) else {
+                    continue;
+                };
+
+                let region = find_region(winner_operations, &conflict.parent_range);
+                let reverted = winner_operations[region]
+                    .iter()
+                    .filter_map(Operation::revert)
+                    .collect::<Vec<_>>();
+
+                involved_sides
+                    .iter()
+                    .filter(|side| **side != winner.index())
+                    .map(|side| Replacement {
+                        side: *side,
+                        operations: reverted.clone(),
+                        cursor_target: None,
+                    })
+                    .collect()
+            }
+            Resolution::Group => {
+                let (tokens, runs) = group_insertions(involved_sides.iter().map(|side| {
+                    let operations = &sides[*side].0;
+                    inserted_tokens(&operations[find_region(operations, &conflict.parent_range)])
+                }));
+
+                replace_with_tokens(&involved_sides, conflict, tokens, runs)
+            }
+            Resolution::Text(text) => {
+                let length = text.chars().count();
+                let runs = vec![0..length; involved_sides.len()];
+
+                replace_with_tokens(&involved_sides, conflict, tokenizer(&text), runs)
+            }
+        };
+
+        let mut moved_cursors = Vec::new();
+        for replacement in replacements {
+            let (operations, cursors) = &mut sides[replacement.side];
+            let region = find_region(operations, &conflict.parent_range);
+
+            move_cursors(
+                cursors,
+                advancing_length(&operations[..region.start]),
+                advancing_length(&operations[region.clone()]),
+                advancing_length(&replacement.operations),
+                replacement.cursor_target.as_ref(),
+                &mut moved_cursors,
+            );
+
+            conflict_indices[replacement.side].splice(
+                region.clone(),
+                vec![Some(index); replacement.operations.len()],
+            );
+            operations.splice(region, replacement.operations);
+        }
+
+        if let Some(first_side) = involved_sides.first()
+            && !moved_cursors.is_empty()
+        {
+            let (operations, cursors) = &mut sides[*first_side];
+            let region_start = advancing_length(
+                &operations[..find_region(operations, &conflict.parent_range).start],
+            );
+
+            cursors.extend(
+                moved_cursors
+                    .into_iter()
+                    .map(|(cursor, offset)| cursor.with_index(region_start + offset)),
+            );
+            cursors.sort_by_key(|cursor| cursor.char_index);
+        }
+    }
+}
+
+/// Indexes of the operations which affect `parent_range`, including the
+/// inserts at its boundaries. Operations are aligned to the tokens of the
+/// original text on every side, so the region starts and ends at operation
+/// boundaries.
+fn find_region<T>(operations: &[Operation<T>], parent_range: &Range<usize>) -> Range<usize>
+where
+    T: PartialEq + Clone + Debug,
+{
+    let start = operations
+        .iter()
+        .position(|operation| operation.order() >= parent_range.start)
+        .unwrap_or(operations.len());
+
+    let end = operations[start..]
+        .iter()
+        .position(|operation| {
+            matches!(operation, Operation::Equal { .. }) && operation.order() >= parent_range.end
+        })
+        .map_or(operations.len(), |offset| start + offset);
+
+    start..end
+}
+
+/// Replace the region with `tokens` on the first involved side and delete it
+/// on the rest. The duplicate deletes cancel out when merging.
+fn replace_with_tokens<T>(
+    involved_sides: &[usize],
+    conflict: &Conflict,
+    tokens: Vec<Token<T>>,
+    cursor_targets: Vec<Range<usize>>,
+) -> Vec<Replacement<T>>
+where
+    T: PartialEq + Clone + Debug,
+{
+    let parent_range = &conflict.parent_range;
+
+    let delete = (!parent_range.is_empty()).then(|| {
+        if cfg!(debug_assertions) {
+            Operation::create_delete_with_text(parent_range.start, conflict.base.clone())
+        } else {
+            Operation::create_delete(parent_range.start, parent_range.len())
+        }
+    });
+    let insert = (!tokens.is_empty()).then(|| Operation::create_insert(parent_range.end, tokens));
+
+    involved_sides
+        .iter()
+        .zip(cursor_targets)
+        .enumerate()
+        .map(|(index, (side, cursor_target))| Replacement {
+            side: *side,
+            operations: delete
+                .iter()
+                .cloned()
+                .chain(if index == 0 { insert.clone() } else { None })
+                .collect(),
+            cursor_target: Some(cursor_target),
+        })
+        .collect()
+}
+
+/// The tokens the operations insert, in order
+fn inserted_tokens<T>(operations: &[Operation<T>]) -> Vec<Token<T>>
+where
+    T: PartialEq + Clone + Debug,
+{
+    operations
+        .iter()
+        .filter_map(|operation| match operation {
+            Operation::Insert { text, .. } => Some(text.iter().cloned()),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+/// Join each side's insertions one after the other. Like when merging
+/// inserts, the prefix of an insertion which already appears in the previous
+/// ones is dropped to avoid duplicating text.
+///
+/// Returns the joined tokens and the character range each insertion ended up
+/// at.
+fn group_insertions<T>(
+    insertions: impl Iterator<Item = Vec<Token<T>>>,
+) -> (Vec<Token<T>>, Vec<Range<usize>>)
+where
+    T: PartialEq + Clone + Debug,
+{
+    let mut result = Vec::new();
+    let mut ranges = Vec::new();
+    let mut length = 0;
+
+    for insertion in insertions {
+        let offset = find_longest_prefix_contained_within(&result, &insertion);
+        let appended = &insertion[offset..];
+        let appended_length: usize = appended.iter().map(Token::get_original_length).sum();
+
+        ranges.push(length..length + appended_length);
+        length += appended_length;
+        result.extend_from_slice(appended);
+    }
+
+    (result, ranges)
+}
+
+/// Length of the text that `operations` produce
+pub fn advancing_length<T>(operations: &[Operation<T>]) -> usize
+where
+    T: PartialEq + Clone + Debug,
+{
+    operations
+        .iter()
+        .filter(|operation| !matches!(operation, Operation::Delete { .. }))
+        .map(Operation::len)
+        .sum()
+}
+
+/// Move the cursors after a region of a side's text which changes from
+/// `old_length` to `new_length` characters. The ones inside of it are either
+/// taken out into `moved_cursors` with their offset within `target`, or moved
+/// to the start of the region.
+pub fn move_cursors(
+    cursors: &mut Vec<CursorPosition>,
+    region_start: usize,
+    old_length: usize,
+    new_length: usize,
+    target: Option<&Range<usize>>,
+    moved_cursors: &mut Vec<(CursorPosition, usize)>,
+) {
+    cursors.retain_mut(|cursor| {
+        if cursor.char_index <= region_start {
+            return true;
+        }
+
+        if cursor.char_index >= region_start + old_length {
+            cursor.char_index = cursor.char_index - old_length + new_length;
+            return true;
+        }
+
+        if let Some(target) = target {
+            let offset = cursor.char_index - region_start;
+            moved_cursors.push((cursor.clone(), target.start + offset.min(target.len())));
+            return false;
+        }
+
+        cursor.char_index = region_start;
+        true
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::CursorId;
+
+    #[test]
+    fn test_find_region() {
+        let operations: Vec<Operation<String>> = vec![
+            Operation::create_equal(0, 2),
+            Operation::create_delete(2, 3),
+            Operation::create_insert(5, vec!["x".into()]),
+            Operation::create_equal(5, 1),
+            Operation::create_insert(6, vec!["y".into()]),
+            Operation::create_equal(6, 2),
+        ];
+
+        assert_eq!(find_region(&operations, &(2..6)), 1..5);
+        assert_eq!(find_region(&operations, &(6..6)), 4..5);
+        assert_eq!(find_region(&operations, &(8..8)), 6..6);
+    }
+
+    #[test]
+    fn test_group_insertions() {
+        let tokens = |text: &str| {
+            text.split_inclusive(' ')
+                .map(Token::from)
+                .collect::<Vec<Token<String>>>()
+        };
+
+        assert_eq!(
+            group_insertions(["a b ", "c ", "a b ", "", "b d "].into_iter().map(tokens)),
+            (tokens("a b c d "), vec![0..4, 4..6, 6..6, 6..6, 6..8])
+        );
+    }
+
+    #[test]
+    fn test_move_cursors() {
+        let mut cursors = vec![
+            CursorPosition::new(0, 1),
+            CursorPosition::new(1, 4),
+            CursorPosition::new(2, 7),
+            CursorPosition::new(3, 9),
+        ];
+
+        let mut moved_cursors = Vec::new();
+
+        let mut kept_cursors = cursors.clone();
+        move_cursors(&mut kept_cursors, 2, 6, 3, None, &mut moved_cursors);
+        assert_eq!(
+            kept_cursors
+                .iter()
+                .map(CursorPosition::char_index)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 2, 6]
+        );
+        assert!(moved_cursors.is_empty());
+
+        move_cursors(&mut cursors, 2, 6, 0, Some(&(10..13)), &mut moved_cursors);
+        assert_eq!(
+            cursors
+                .iter()
+                .map(CursorPosition::char_index)
+                .collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+        assert_eq!(
+            moved_cursors
+                .iter()
+                .map(|(cursor, offset)| (cursor.id().clone(), *offset))
+                .collect::<Vec<_>>(),
+            vec![(CursorId::Number(1), 12), (CursorId::Number(2), 13)]
+        );
+    }
+}
@@ -0,0 +1,502 @@
+use std::{fmt::Debug, ops::Range};
+
+use crate::{
+    Token,
+    operation_transformation::{
+        Operation,
+        utils::{
+            find_moves::{Move, align_moved_block},
+            resolve_conflicts::{SideParts, advancing_length, move_cursors},
+        },
+    },
+    utils::{diff_budget::DiffBudget, edit::Edit},
+};
+
+/// Apply the edits which other sides made within a block that a side moved to
+/// the block's new location. Otherwise, these edits would stay behind at the
+/// source of the move, where the block is deleted.
+///
+/// `moves` holds the moves of each side, their operation indexes are kept up
+/// to date as the operations of the sides are rewritten. `text` is the
+/// original text.
+pub fn route_moved_edits<T>(text: &str, sides: &mut [SideParts<'_, T>], moves: &mut [Vec<Move<T>>])
+where
+    T: PartialEq + Clone + Debug,
+{
+    for mover in 0..sides.len() {
+        for move_index in 0..moves[mover].len() {
+            for other in (0..sides.len()).filter(|side| *side != mover) {
+                route(text, sides, moves, mover, move_index, other);
+            }
+        }
+    }
+}
+
+/// Move the edits of the `other` side within the block of a single move to
+/// every side which moved the block
+fn route<T>(
+    text: &str,
+    sides: &mut [SideParts<'_, T>],
+    moves: &mut [Vec<Move<T>>],
+    mover: usize,
+    move_index: usize,
+    other: usize,
+) where
+    T: PartialEq + Clone + Debug,
+{
+    let Some(block) = moved_block(sides[mover].0, &moves[mover][move_index]) else {
+        return;
+    };
+
+    // Sides moving the same block keep their moves, and get the edits of the
+    // rest of the sides
+    if find_move_of(sides[other].0, &moves[other], &block).is_some()
+        || crosses_block_boundary(sides[other].0, &block)
+    {
+        return;
+    }
+
+    keep_separators(text, sides[other].0, &mut moves[other], &block);
+
+    let other_operations = &sides[other].0;
+    let region = find_block_region(other_operations, &block, &moves[mover][move_index].source);
+    if other_operations[region.clone()]
+        .iter()
+        .all(|operation| matches!(operation, Operation::Equal { .. }))
+    {
+        return;
+    }
+
+    let edits = other_operations[region.clone()].to_vec();
+    let co_movers = (0..sides.len())
+        .filter(|side| *side != mover && *side != other)
+        .filter_map(|side| Some((side, find_move_of(sides[side].0, &moves[side], &block)?)))
+        .collect::<Vec<_>>();
+
+    let edited_length = edit_target(sides, &moves[mover][move_index], mover, block.start, &edits);
+    for (co_mover, co_move_index) in co_movers {
+        edit_target(
+            sides,
+            &moves[co_mover][co_move_index],
+            co_mover,
+            block.start,
+            &edits,
+        );
+    }
+
+    let reverted = edits
+        .iter()
+        .filter_map(Operation::revert)
+        .collect::<Vec<_>>();
+
+    let mut moved_cursors = Vec::new();
+    let (other_operations, other_cursors) = &mut sides[other];
+    move_cursors(
+        other_cursors,
+        advancing_length(&other_operations[..region.start]),
+        advancing_length(&other_operations[region.clone()]),
+        advancing_length(&reverted),
+        Some(&(0..edited_length)),
+        &mut moved_cursors,
+    );
+
+    let reverted_count = reverted.len();
+    other_operations.splice(region.clone(), reverted);
+    update_move_indexes(&mut moves[other], &region, reverted_count);
+
+    let (mover_operations, mover_cursors) = &mut sides[mover];
+    let target_start = advancing_length(&mover_operations[..moves[mover][move_index].insert_index]);
+    mover_cursors.extend(
+        moved_cursors
+            .into_iter()
+            .map(|(cursor, offset)| cursor.with_index(target_start + offset)),
+    );
+    mover_cursors.sort_by_key(|cursor| cursor.char_index);
+}
+
+/// The range of the original text which the move deletes, if its operations
+/// are still a delete and an insert
+fn moved_block<T>(operations: &[Operation<T>], moved: &Move<T>) -> Option<Range<usize>>
+where
+    T: PartialEq + Clone + Debug,
+{
+    match (
+        &operations[moved.delete_index],
+        &operations[moved.insert_index],
+    ) {
+        (
+            Operation::Delete {
+                order,
+                deleted_character_count,
+                ..
+            },
+            Operation::Insert { .. },
+        ) => Some(*order..order + deleted_character_count),
+        _ => None,
+    }
+}
+
+/// Index of the move among `moves` which moves `block`
+fn find_move_of<T>(
+    operations: &[Operation<T>],
+    moves: &[Move<T>],
+    block: &Range<usize>,
+) -> Option<usize>
+where
+    T: PartialEq + Clone + Debug,
+{
+    moves
+        .iter()
+        .position(|moved| moved_block(operations, moved).as_ref() == Some(block))
+}
+
+/// Apply `edits` made within the block to the target of `moved`, and move
+/// the cursors of the `mover` side within the target accordingly. Returns the
+/// length of the edited target.
+fn edit_target<T>(
+    sides: &mut [SideParts<'_, T>],
+    moved: &Move<T>,
+    mover: usize,
+    block_start: usize,
+    edits: &[Operation<T>],
+) -> usize
+where
+    T: PartialEq + Clone + Debug,
+{
+    let (mover_operations, mover_cursors) = &mut sides[mover];
+    let Operation::Insert {
+        order: target_order,
+        text: target,
+    } = &mover_operations[moved.insert_index]
+    else {
+        unreachable!("The target of a move is an insert");
+    };
+
+    let (edited_target, token_starts) = edit_moved_block(&moved.source, target, block_start, edits);
+    let target_length: usize = target.iter().map(Token::get_original_length).sum();
+    let edited_length: usize = token_starts.last().copied().unwrap_or_default();
+    let target_token_starts = token_start_offsets(target);
+
+    let target_start = advancing_length(&mover_operations[..moved.insert_index]);
+    mover_operations[moved.insert_index] = Operation::create_insert(*target_order, edited_target);
+
+    for cursor in mover_cursors.iter_mut() {
+        if cursor.char_index > target_start + target_length {
+            cursor.char_index = cursor.char_index - target_length + edited_length;
+        } else if cursor.char_index > target_start {
+            let offset = cursor.char_index - target_start;
+            let token_index = target_token_starts.partition_point(|start| *start <= offset) - 1;
+
+            cursor.char_index = target_start
+                + (token_starts[token_index] + offset - target_token_starts[token_index]).min(
+                    token_starts
+                        .get(token_index + 1)
+                        .copied()
+                        .unwrap_or(edited_length),
+                );
+        }
+    }
+
+    edited_length
+}
+
+/// Turn each delete at an edge of `block` followed by an insert starting with
+/// the deleted text into an equal and a shorter insert. Tokenizers which
+/// normalize whitespace with the next word (such as the word tokenizer)
+/// express inserting before a word as such a replacement of the whitespace,
+/// which would leave the whitespace deleted on one side of the edge and
+/// inserted on the other.
+fn keep_separators<T>(
+    text: &str,
+    operations: &mut Vec<Operation<T>>,
+    moves: &mut Vec<Move<T>>,
+    block: &Range<usize>,
+) where
+    T: PartialEq + Clone + Debug,
+{
+    // Going backwards keeps the indexes of the earlier operations valid
+    for index in (0..operations.len().saturating_sub(1)).rev() {
+        let (Operation::Delete { order, .. }, Operation::Insert { text: inserted, .. }) =
+            (&operations[index], &operations[index + 1])
+        else {
+            continue;
+        };
+
+        let end = order + operations[index].len();
+        let is_at_edge = [block.start, block.end]
+            .iter()
+            .any(|edge| (*order..=end).contains(edge));
+        let is_moved = moves.iter().any(|moved| {
+            [moved.delete_index, moved.insert_index]
+                .iter()
+                .any(|moved_index| *moved_index == index || *moved_index == index + 1)
+        });
+        if !is_at_edge || is_moved {
+            continue;
+        }
+
+        let deleted_text: String = text
+            .chars()
+            .skip(*order)
+            .take(operations[index].len())
+            .collect();
+        let Some(kept_count) = prefix_token_count(inserted, &deleted_text) else {
+            continue;
+        };
+
+        let rest = inserted[kept_count..].to_vec();
+        let replacement = operations[index]
+            .revert()
+            .into_iter()
+            .chain((!rest.is_empty()).then(|| Operation::create_insert(end, rest)))
+            .collect::<Vec<_>>();
+
+        let region = index..index + 2;
+        update_move_indexes(moves, &region, replacement.len());
+        operations.splice(region, replacement);
+    }
+}
+
+/// The number of leading `tokens` which make up exactly `prefix`
+fn prefix_token_count<T>(tokens: &[Token<T>], prefix: &str) -> Option<usize>
+where
+    T: PartialEq + Clone + Debug,
+{
+    let mut rest = prefix;
+    for (index, token) in tokens.iter().enumerate() {
+        if rest.is_empty() {
+            return Some(index);
+        }
+        rest = rest.strip_prefix(token.original())?;
+    }
+
+    rest.is_empty().then_some(tokens.len())
+}
+
+/// Whether any of the deletes only partially overlap `block`
+fn crosses_block_boundary<T>(operations: &[Operation<T>], block: &Range<usize>) -> bool
+where
+    T: PartialEq + Clone + Debug,
+{
+    operations.iter().any(|operation| {
+        let range = operation.order()..operation.order() + operation.len();
+
+        matches!(operation, Operation::Delete { .. })
+            && [block.start, block.end]
+                .iter()
+                .any(|boundary| range.start < *boundary && *boundary < range.end)
+    })
+}
+
+/// Indexes of the operations within `block`. Inserts at an edge of the block
+/// belong to it, unless the block has whitespace at that edge (such as the
+/// newline separating it from the next paragraph), as then they belong to the
+/// surrounding text.
+fn find_block_region<T>(
+    operations: &[Operation<T>],
+    block: &Range<usize>,
+    source: &[Token<T>],
+) -> Range<usize>
+where
+    T: PartialEq + Clone + Debug,
+{
+    let has_content_at = |edge: Option<char>| edge.is_some_and(|c| !c.is_whitespace());
+    let includes_start = has_content_at(source.first().and_then(|t| t.original().chars().next()));
+    let includes_end = has_content_at(source.last().and_then(|t| t.original().chars().next_back()));
+
+    let start = operations
+        .iter()
+        .position(|operation| {
+            operation.order() > block.start
+                || (operation.order() == block.start
+                    && (includes_start || !matches!(operation, Operation::Insert { .. })))
+        })
+        .unwrap_or(operations.len());
+
+    let end = operations[start..]
+        .iter()
+        .position(|operation| {
+            operation.order() > block.end
+                || (operation.order() == block.end
+                    && !(includes_end && matches!(operation, Operation::Insert { .. })))
+        })
+        .map_or(operations.len(), |offset| start + offset);
+
+    start..end
+}
+
+/// Character offset of each token and of the end of the last one
+fn token_start_offsets<T>(tokens: &[Token<T>]) -> Vec<usize>
+where
+    T: PartialEq + Clone + Debug,
+{
+    let mut offsets = Vec::with_capacity(tokens.len() + 1);
+    let mut offset = 0;
+    offsets.push(offset);
+
+    for token in tokens {
+        offset += token.get_original_length();
+        offsets.push(offset);
+    }
+
+    offsets
+}
+
+/// Apply `edits` (made to the `source` of a moved block starting at
+/// `block_start` in the original text) to the moved `target` block. The edits
+/// are mapped through the alignment of the source and target, so the block
+/// can be edited by the mover too. Deletes of text the mover has already
+/// changed are dropped.
+///
+/// Returns the edited target and the offset of each target token (and of the
+/// end) in it.
+fn edit_moved_block<T>(
+    source: &[Token<T>],
+    target: &[Token<T>],
+    block_start: usize,
+    edits: &[Operation<T>],
+) -> (Vec<Token<T>>, Vec<usize>)
+where
+    T: PartialEq + Clone + Debug,
+{
+    // For each boundary between source tokens, the index of the target token
+    // after it, and for each source token, the target token it's equal to
+    let mut target_index_after = vec![0; source.len() + 1];
+    let mut aligned_target_index = vec![None; source.len()];
+    let (mut source_index, mut target_index) = (0, 0);
+
+    for edit in align_moved_block(source, target, &DiffBudget::unlimited()) {
+        match edit {
+            Edit::Equal => {
+                aligned_target_index[source_index] = Some(target_index);
+                source_index += 1;
+                target_index += 1;
+                target_index_after[source_index] = target_index;
+            }
+            Edit::Delete => {
+                source_index += 1;
+                target_index_after[source_index] = target_index;
+            }
+            Edit::Insert => target_index += 1,
+        }
+    }
+
+    let source_offsets = token_start_offsets(source);
+    let source_token_at =
+        |offset: usize| source_offsets.partition_point(|start| *start <= offset - block_start) - 1;
+
+    let mut is_deleted = vec![false; target.len()];
+    let mut insertions: Vec<Vec<Token<T>>> = vec![Vec::new(); target.len() + 1];
+
+    for edit in edits {
+        match edit {
+            Operation::Equal { .. } => {}
+            Operation::Insert { order, text } => {
+                // Right before the target token the source token is aligned
+                // with, if any, so that inserts at the start of a block stay
+                // after a separator the target starts with
+                let source_index = source_token_at(*order);
+                let target_index = aligned_target_index
+                    .get(source_index)
+                    .copied()
+                    .flatten()
+                    .unwrap_or(target_index_after[source_index]);
+                insertions[target_index].extend_from_slice(text);
+            }
+            Operation::Delete {
+                order,
+                deleted_character_count,
+                ..
+            } => {
+                let end = source_offsets.partition_point(|start| {
+                    *start < order + deleted_character_count - block_start
+                });
+
+                for index in aligned_target_index[source_token_at(*order)..end]
+                    .iter()
+                    .flatten()
+                {
+                    is_deleted[*index] = true;
+                }
+            }
+        }
+    }
+
+    let mut edited = Vec::new();
+    let mut token_starts = Vec::with_capacity(target.len() + 1);
+    let mut length = 0;
+
+    for (index, insertion) in insertions.into_iter().enumerate() {
+        length += insertion
+            .iter()
+            .map(Token::get_original_length)
+            .sum::<usize>();
+        edited.extend(insertion);
+        token_starts.push(length);
+
+        if let Some(token) = target.get(index)
+            && !is_deleted[index]
+        {
+            length += token.get_original_length();
+            edited.push(token.clone());
+        }
+    }
+
+    (edited, token_starts)
+}
+
+/// Shift the operation indexes of `moves` after the operations in `region`
+/// got replaced by `replacement_count` others, and drop the moves which were
+/// within the region
+fn update_move_indexes<T>(moves: &mut Vec<Move<T>>, region: &Range<usize>, replacement_count: usize)
+where
+    T: PartialEq + Clone + Debug,
+{
+    moves.retain_mut(|m| {
+        if region.contains(&m.delete_index) || region.contains(&m.insert_index) {
+            return false;
+        }
+
+        for index in [&mut m.delete_index, &mut m.insert_index] {
+            if *index >= region.end {
+                *index = *index - region.len() + replacement_count;
+            }
+        }
+
+        true
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn tokens(text: &str) -> Vec<Token<String>> {
+        text.split_inclusive(' ').map(Token::from).collect()
+    }
+
+    fn text(tokens: &[Token<String>]) -> String {
+        tokens.iter().map(Token::original).collect()
+    }
+
+    #[test]
+    fn test_edit_moved_block() {
+        // "one two three four " moved from offset 10 and edited by the mover
+        let source = tokens("one two three four ");
+        let target = tokens("one 2 three four ");
+        let edits = [
+            Operation::create_equal(10, 4),
+            Operation::create_insert(14, vec!["and ".into()]),
+            Operation::create_equal(14, 4),
+            Operation::create_delete(18, 6),
+            Operation::create_equal(24, 5),
+        ];
+
+        let (edited, token_starts) = edit_moved_block(&source, &target, 10, &edits);
+
+        assert_eq!(text(&edited), "one and 2 four ");
+        assert_eq!(token_starts, vec![0, 8, 10, 10, 15]);
+    }
+}
@@ -0,0 +1,109 @@
+use std::fmt::Debug;
+
+use crate::{operation_transformation::Operation, types::side::Side};
+
+/// The character offsets in the original text where the operations start or
+/// end, in order
+pub fn operation_boundaries<T>(operations: &[Operation<T>]) -> impl Iterator<Item = usize> + '_
+where
+    T: PartialEq + Clone + Debug,
+{
+    operations.iter().flat_map(|operation| match operation {
+        Operation::Insert { order, .. } => [*order, *order],
+        _ => [operation.order(), operation.order() + operation.len()],
+    })
+}
+
+/// Split the retained spans of `operations` at the sorted `boundaries`, so that
+/// the insertions of other sides at these offsets are not moved after them
+/// when merging.
+///
+/// Returns the split operations with their sides, and the index of the first
+/// piece of each of the original operations.
+pub fn split_equal_operations<T>(
+    operations: &[Operation<T>],
+    sides: &[Side],
+    boundaries: &[usize],
+) -> (Vec<Operation<T>>, Vec<Side>, Vec<usize>)
+where
+    T: PartialEq + Clone + Debug,
+{
+    let mut split_operations = Vec::with_capacity(operations.len());
+    let mut split_sides = Vec::with_capacity(operations.len());
+    let mut first_pieces = Vec::with_capacity(operations.len());
+
+    for (operation, side) in operations.iter().zip(sides.iter().copied()) {
+        first_pieces.push(split_operations.len());
+
+        let Operation::Equal { order, length, .. } = operation else {
+            split_operations.push(operation.clone());
+            split_sides.push(side);
+            continue;
+        };
+
+        let end = order + length;
+        let first_boundary = boundaries.partition_point(|boundary| boundary <= order);
+        let mut start = *order;
+
+        for boundary in boundaries[first_boundary..]
+            .iter()
+            .take_while(|boundary| **boundary < end)
+            .chain(Some(&end))
+        {
+            split_operations.push(Operation::create_equal(start, boundary - start));
+            split_sides.push(side);
+            start = *boundary;
+        }
+    }
+
+    (split_operations, split_sides, first_pieces)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_split_equal_operations() {
+        let operations: Vec<Operation<String>> = vec![
+            Operation::create_equal(0, 4),
+            Operation::create_delete(4, 3),
+            Operation::create_insert(7, vec!["x".into()]),
+            Operation::create_equal(7, 2),
+        ];
+
+        let (split, sides, first_pieces) = split_equal_operations(
+            &operations,
+            &[Side::Left, Side::Right, Side::Left, Side::Right],
+            &[0, 2, 3, 5, 7, 8],
+        );
+
+        assert_eq!(
+            split,
+            vec![
+                Operation::create_equal(0, 2),
+                Operation::create_equal(2, 1),
+                Operation::create_equal(3, 1),
+                Operation::create_delete(4, 3),
+                Operation::create_insert(7, vec!["x".into()]),
+                Operation::create_equal(7, 1),
+                Operation::create_equal(8, 1),
+            ]
+        );
+        assert_eq!(
+            sides,
+            vec![
+                Side::Left,
+                Side::Left,
+                Side::Left,
+                Side::Right,
+                Side::Left,
+                Side::Right,
+                Side::Right
+            ]
+        );
+        assert_eq!(first_pieces, vec![0, 3, 4, 5]);
+    }
+}
@@ -1,6 +1,17 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, hash::Hash};
 
-use crate::{tokenizer::token::Token, utils::myers_diff::myers_diff};
+use crate::{
+    tokenizer::token::Token,
+    types::diff_options::DiffAlgorithm,
+    utils::{
+        diff_budget::DiffBudget,
+        edit::Edit,
+        histogram_diff::histogram_diff,
+        intern::intern,
+        myers_diff::{myers_diff, myers_diff_with_budget},
+        patience_diff::patience_diff,
+    },
+};
 
 /// Text editing operation containing the affected tokens.
 ///
@@ -20,8 +31,61 @@ impl<T> RawOperation<T>
 where
     T: PartialEq + Clone + Debug,
 {
-    pub fn vec_from(left: &[Token<T>], right: &[Token<T>]) -> Vec<Self> {
-        myers_diff(left, right)
+    /// Diff the tokens with Myers' algorithm, comparing the tokens themselves.
+    /// Prefer `vec_from_with_algorithm` for tokens which can be hashed.
+    pub fn vec_from(left: Vec<Token<T>>, right: Vec<Token<T>>) -> Vec<Self> {
+        let edits = myers_diff(&left, &right);
+        Self::vec_from_edits(left, right, &edits)
+    }
+
+    /// Diff the tokens with `algorithm`. The tokens are interned first, so
+    /// that the algorithms only have to compare integers, and then moved into
+    /// the operations.
+    pub fn vec_from_with_algorithm(
+        left: Vec<Token<T>>,
+        right: Vec<Token<T>>,
+        algorithm: DiffAlgorithm,
+        budget: &DiffBudget,
+    ) -> Vec<Self>
+    where
+        T: Eq + Hash,
+    {
+        let (left_ids, right_ids) = intern(&left, &right);
+        let edits = match algorithm {
+            DiffAlgorithm::Myers => myers_diff_with_budget(&left_ids, &right_ids, budget),
+            DiffAlgorithm::Patience => patience_diff(&left_ids, &right_ids, budget),
+            DiffAlgorithm::Histogram => histogram_diff(&left_ids, &right_ids, budget),
+        };
+
+        Self::vec_from_edits(left, right, &edits)
+    }
+
+    /// Turn the edit script of `left` and `right` into operations, each
+    /// wrapping a single token
+    ///
+    /// # Panics
+    ///
+    /// Panics if the edit script doesn't cover exactly the given tokens.
+    pub fn vec_from_edits(left: Vec<Token<T>>, right: Vec<Token<T>>, edits: &[Edit]) -> Vec<Self> {
+        let mut left = left.into_iter();
+        let mut right = right.into_iter();
+        let message = "The edit script must cover the tokens";
+
+        let result = edits
+            .iter()
+            .map(|edit| match edit {
+                Edit::Equal => {
+                    right.next().expect(message);
+                    RawOperation::Equal(vec![left.next().expect(message)])
+                }
+                Edit::Delete => RawOperation::Delete(vec![left.next().expect(message)]),
+                Edit::Insert => RawOperation::Insert(vec![right.next().expect(message)]),
+            })
+            .collect();
+
+        debug_assert!(left.next().is_none() && right.next().is_none(), "{message}");
+
+        result
     }
 
     pub fn tokens(&self) -> &[Token<T>] {
@@ -60,15 +124,19 @@ where
              calling this function."
         );
 
+        // Extending in place keeps joining long runs one token at a time linear
         match (self, other) {
-            (RawOperation::Insert(self_tokens), RawOperation::Insert(other_tokens)) => {
-                RawOperation::Insert(self_tokens.into_iter().chain(other_tokens).collect())
+            (RawOperation::Insert(mut tokens1), RawOperation::Insert(tokens2)) => {
+                tokens1.extend(tokens2);
+                RawOperation::Insert(tokens1)
             }
-            (RawOperation::Delete(tokens1), RawOperation::Delete(tokens2)) => {
-                RawOperation::Delete(tokens1.into_iter().chain(tokens2).collect())
+            (RawOperation::Delete(mut tokens1), RawOperation::Delete(tokens2)) => {
+                tokens1.extend(tokens2);
+                RawOperation::Delete(tokens1)
             }
-            (RawOperation::Equal(tokens1), RawOperation::Equal(tokens2)) => {
-                RawOperation::Equal(tokens1.into_iter().chain(tokens2).collect())
+            (RawOperation::Equal(mut tokens1), RawOperation::Equal(tokens2)) => {
+                tokens1.extend(tokens2);
+                RawOperation::Equal(tokens1)
             }
             _ => unreachable!("Only operations of the same type can be extended"),
         }
@@ -37,6 +37,26 @@ pub enum BuiltinTokenizer {
     Word,
 }
 
+impl BuiltinTokenizer {
+    /// A stable name of the tokenizer, for example, to record in a
+    /// `DiffEnvelope` which tokenizer a diff was made with
+    ///
+    /// # Panics
+    ///
+    /// Panics on the invalid variant generated for the WebAssembly bindings.
+    #[must_use]
+    pub fn id(self) -> &'static str {
+        match self {
+            BuiltinTokenizer::Character => "Character",
+            BuiltinTokenizer::Line => "Line",
+            BuiltinTokenizer::Markdown => "Markdown",
+            BuiltinTokenizer::Word => "Word",
+            #[cfg(feature = "wasm")]
+            BuiltinTokenizer::__Invalid => panic!("Unexpected tokenizer type"),
+        }
+    }
+}
+
 impl Deref for BuiltinTokenizer {
     type Target = Tokenizer<String>;
 
@@ -82,6 +82,8 @@ where
     }
 }
 
+impl<T> Eq for Token<T> where T: Eq + Clone + Debug {}
+
 /// Hashes based on the `normalized` field only, consistent with the
 /// [`PartialEq`] implementation.
 impl<T> Hash for Token<T>
@@ -1,6 +1,23 @@
+#[cfg(feature = "render")]
+pub mod ansi_renderer;
+pub mod authorship;
+pub mod conflict;
+pub mod conflict_markers;
+pub mod cursor_id;
 pub mod cursor_position;
+pub mod diff_envelope;
+pub mod diff_options;
+pub mod fuzzy_patch;
+pub mod gravity;
 pub mod history;
+#[cfg(feature = "render")]
+pub mod html_renderer;
+pub mod invertible_change;
+pub mod merge_report;
+pub mod merge_trace;
 pub mod number_or_text;
+pub mod selection;
 pub mod side;
 pub mod span_with_history;
 pub mod text_with_cursors;
+pub mod unified_diff;
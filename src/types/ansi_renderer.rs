@@ -0,0 +1,222 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::types::{history::History, span_with_history::SpanWithHistory};
+
+/// Options for rendering the provenance of a merge result for terminals, with
+/// the spans colored by ANSI escape codes according to their `History`, and
+/// removed text struck through and the spans of conflicting regions (see
+/// `SpanWithHistory::is_conflicting`) underlined. Control characters in the
+/// text, other than line breaks and tabs, are escaped.
+///
+/// ```
+/// use reconcile_text::{reconcile, AnsiRenderer, BuiltinTokenizer, History};
+///
+/// let parent = "Hello world";
+/// let left = "Hello big world";
+/// let right = "Hi world";
+///
+/// let result = reconcile(parent, &left.into(), &right.into(), &*BuiltinTokenizer::Word);
+/// let renderer = AnsiRenderer::default()
+///     .with_style(History::AddedFromLeft, "1")
+///     .with_conflict_style("");
+///
+/// assert_eq!(
+///     result.apply_with_ansi(&renderer),
+///     "\x1b[35;9mHello\x1b[0m\x1b[36mHi\x1b[0m\x1b[1m big\x1b[0m world"
+/// );
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnsiRenderer {
+    styles: Vec<(History, String)>,
+    conflict_style: String,
+    strikethrough: bool,
+}
+
+impl Default for AnsiRenderer {
+    /// Green and cyan for the text added by the left and right sides, red and
+    /// magenta for the text they removed, bold for text both sides changed
+    /// the same way, green and red for the text added and removed in a
+    /// two-way diff, and underlined conflicting regions
+    fn default() -> Self {
+        Self {
+            styles: vec![
+                (History::Unchanged, String::new()),
+                (History::AddedFromLeft, "32".to_owned()),
+                (History::AddedFromRight, "36".to_owned()),
+                (History::RemovedFromLeft, "31".to_owned()),
+                (History::RemovedFromRight, "35".to_owned()),
+                (History::AddedByBoth, "1;32".to_owned()),
+                (History::RemovedByBoth, "1;31".to_owned()),
+                (History::Added, "32".to_owned()),
+                (History::Removed, "31".to_owned()),
+            ],
+            conflict_style: "4".to_owned(),
+            strikethrough: true,
+        }
+    }
+}
+
+impl AnsiRenderer {
+    /// Set the style of the spans labelled `history` as the parameters of an
+    /// SGR escape code, such as `"1;32"` for bold green. An empty style leaves
+    /// the spans unstyled.
+    #[must_use]
+    pub fn with_style(mut self, history: History, style: impl Into<String>) -> Self {
+        let style = style.into();
+        match self.styles.iter_mut().find(|(label, _)| *label == history) {
+            Some((_, existing)) => *existing = style,
+            None => self.styles.push((history, style)),
+        }
+
+        self
+    }
+
+    /// Set the style added to the style of the spans of conflicting regions,
+    /// which may be empty to leave them as they are
+    #[must_use]
+    pub fn with_conflict_style(self, conflict_style: impl Into<String>) -> Self {
+        Self {
+            conflict_style: conflict_style.into(),
+            ..self
+        }
+    }
+
+    /// Set whether removed text is struck through on top of its style
+    #[must_use]
+    pub fn with_strikethrough(self, strikethrough: bool) -> Self {
+        Self {
+            strikethrough,
+            ..self
+        }
+    }
+
+    /// The style of the spans labelled `history`
+    #[must_use]
+    pub fn style(&self, history: History) -> &str {
+        self.styles
+            .iter()
+            .find(|(label, _)| *label == history)
+            .map_or("", |(_, style)| style)
+    }
+
+    /// Render the spans of a merge result, as returned by `history_spans`
+    pub(crate) fn render(&self, spans: impl Iterator<Item = SpanWithHistory>) -> String {
+        let mut result = String::new();
+
+        for span in spans {
+            let mut style = self.style(span.history()).to_owned();
+            if span.is_conflicting() {
+                push_parameter(&mut style, &self.conflict_style);
+            }
+            if self.strikethrough && span.is_removed() {
+                push_parameter(&mut style, "9");
+            }
+
+            if style.is_empty() {
+                escape_control_characters(&span.text(), &mut result);
+            } else {
+                result.push_str("\x1b[");
+                result.push_str(&style);
+                result.push('m');
+                escape_control_characters(&span.text(), &mut result);
+                result.push_str("\x1b[0m");
+            }
+        }
+
+        result
+    }
+}
+
+/// Append the SGR `parameter` to `style`, separated by a semicolon
+fn push_parameter(style: &mut String, parameter: &str) {
+    if parameter.is_empty() {
+        return;
+    }
+    if !style.is_empty() {
+        style.push(';');
+    }
+    style.push_str(parameter);
+}
+
+/// Append `text` to `result` with the control characters other than line
+/// breaks and tabs written as `\u{..}` escapes, so that the text can't inject
+/// escape sequences into the terminal
+fn escape_control_characters(text: &str, result: &mut String) {
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let is_line_break = c == '\n' || (c == '\r' && chars.peek() == Some(&'\n'));
+        if c.is_control() && !is_line_break && c != '\t' {
+            result.extend(c.escape_unicode());
+        } else {
+            result.push(c);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{BuiltinTokenizer, EditedText, reconcile};
+
+    #[test]
+    fn test_render_default_styles() {
+        let parent = "one two three";
+        let left = "one three four";
+        let right = "zero one two three";
+
+        let result = reconcile(
+            parent,
+            &left.into(),
+            &right.into(),
+            &*BuiltinTokenizer::Word,
+        );
+
+        assert_eq!(
+            result.apply_with_ansi(&AnsiRenderer::default()),
+            "\x1b[36mzero \x1b[0mone\x1b[31;9m two\x1b[0m three\x1b[32m four\x1b[0m"
+        );
+        assert_eq!(
+            result.apply_with_ansi(&AnsiRenderer::default().with_strikethrough(false)),
+            "\x1b[36mzero \x1b[0mone\x1b[31m two\x1b[0m three\x1b[32m four\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_render_escapes_control_characters() {
+        let parent = "safe\r\n";
+        let left = "safe\r\n\x1b[2J\ttext\u{9b}31m\r";
+
+        let result = EditedText::from_strings(parent, &left.into());
+
+        assert_eq!(
+            result.apply_with_ansi(&AnsiRenderer::default()),
+            concat!(
+                "safe\x1b[31;9m\r\n\x1b[0m",
+                "\x1b[32m\r\n\\u{1b}[2J\ttext\\u{9b}31m\\u{d}\x1b[0m"
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_conflicting_spans() {
+        let parent = "Meet at 5pm";
+        let left = "Meet at 6pm";
+        let right = "Meet at 7pm";
+
+        let result = reconcile(
+            parent,
+            &left.into(),
+            &right.into(),
+            &*BuiltinTokenizer::Word,
+        );
+
+        assert_eq!(
+            result.apply_with_ansi(&AnsiRenderer::default()),
+            "Meet at\x1b[1;31;4;9m 5pm\x1b[0m\x1b[32;4m 6pm\x1b[0m\x1b[36;4m 7pm\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::ops::Range;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A run of consecutive characters written by the same author
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorSpan {
+    pub(crate) author: Option<String>,
+    pub(crate) length: usize,
+}
+
+impl AuthorSpan {
+    /// The author of the characters, `None` if they were inserted by an
+    /// `EditedText` without an author
+    #[must_use]
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    /// The number of characters in the run
+    #[must_use]
+    pub fn length(&self) -> usize {
+        self.length
+    }
+}
+
+/// Who wrote each character of a text, like `git blame` for prose, stored as
+/// runs of characters with the same author
+///
+/// Give the authorship of the original text to `EditedText::with_authorship`
+/// and the author of each side's edits to `EditedText::with_author`, then
+/// `EditedText::authorship` returns the authorship of the merged text, which
+/// can be stored next to it (with the `serde` feature) for the next merge.
+///
+/// ```
+/// use reconcile_text::{Authorship, EditedText};
+///
+/// let parent = "Shopping list:";
+/// let authorship = Authorship::new("alice", 14);
+///
+/// let left = EditedText::from_strings(parent, &"Shopping list: milk".into())
+///     .with_author("bob")
+///     .with_authorship(authorship)
+///     .unwrap();
+/// let right = EditedText::from_strings(parent, &"Shopping list: eggs".into()).with_author("carol");
+///
+/// let merged = left.merge(right);
+/// assert_eq!(merged.apply().text(), "Shopping list: eggs milk");
+///
+/// let authorship = merged.authorship();
+/// assert_eq!(authorship.author_at(0), Some("alice"));
+/// assert_eq!(authorship.author_at(16), Some("carol"));
+/// assert_eq!(authorship.author_at(21), Some("bob"));
+/// assert_eq!(authorship.len(), 24);
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Authorship {
+    pub(crate) spans: Vec<AuthorSpan>,
+}
+
+impl Authorship {
+    /// Authorship of a text of `length` characters written by `author`
+    #[must_use]
+    pub fn new(author: impl Into<String>, length: usize) -> Self {
+        let mut authorship = Self::default();
+        authorship.push(Some(&author.into()), length);
+
+        authorship
+    }
+
+    /// Append a run of `length` characters written by `author`, joining it
+    /// with the last run if they have the same author
+    pub fn push(&mut self, author: Option<&str>, length: usize) {
+        if length == 0 {
+            return;
+        }
+
+        match self.spans.last_mut() {
+            Some(last) if last.author.as_deref() == author => last.length += length,
+            _ => self.spans.push(AuthorSpan {
+                author: author.map(ToOwned::to_owned),
+                length,
+            }),
+        }
+    }
+
+    /// The runs of characters with the same author, in order
+    #[must_use]
+    pub fn spans(&self) -> &[AuthorSpan] {
+        &self.spans
+    }
+
+    /// The number of characters covered
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.spans.iter().map(|span| span.length).sum()
+    }
+
+    /// Whether no characters are covered
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// The author of the character at `index`, `None` if it's unknown or
+    /// `index` is out of bounds
+    #[must_use]
+    pub fn author_at(&self, index: usize) -> Option<&str> {
+        let mut start = 0;
+        for span in &self.spans {
+            if index < start + span.length {
+                return span.author();
+            }
+            start += span.length;
+        }
+
+        None
+    }
+
+    /// The runs overlapping `range`, clipped to it
+    pub(crate) fn runs_in(&self, range: Range<usize>) -> Vec<(Range<usize>, Option<&str>)> {
+        let mut runs = Vec::new();
+        let mut start = 0;
+
+        for span in &self.spans {
+            let end = start + span.length;
+            let run = start.max(range.start)..end.min(range.end);
+            if !run.is_empty() {
+                runs.push((run, span.author()));
+            }
+            if end >= range.end {
+                break;
+            }
+            start = end;
+        }
+
+        runs
+    }
+
+    /// Append the authorship of `range` of `other`
+    pub(crate) fn extend_from(&mut self, other: &Self, range: Range<usize>) {
+        for (run, author) in other.runs_in(range) {
+            self.push(author, run.len());
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::ops::Range;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A region of the original text which more than one side edited in
+/// overlapping (or touching) spans, with each side's version of the region
+///
+/// The merge still resolves these regions automatically, conflicts are only
+/// reported so that they can be reviewed (or rendered with conflict markers).
+///
+/// The ranges count characters rather than bytes, so they can't be used to
+/// slice the text directly:
+///
+/// ```
+/// use reconcile_text::{reconcile, BuiltinTokenizer};
+///
+/// let parent = "Café at 5pm on Monday";
+/// let left = "Café at 6pm on Monday";
+/// let right = "Café at 7pm on Tuesday";
+///
+/// let result = reconcile(parent, &left.into(), &right.into(), &*BuiltinTokenizer::Word);
+/// let merged = result.apply().text();
+///
+/// let conflicts = result.conflicts();
+/// assert_eq!(conflicts.len(), 1);
+///
+/// let conflict = &conflicts[0];
+/// assert_eq!(conflict.base(), " 5pm");
+/// assert_eq!(conflict.left(), " 6pm");
+/// assert_eq!(conflict.right(), " 7pm");
+///
+/// let chars = |text: &str, range: std::ops::Range<usize>| -> String {
+///     text.chars().skip(range.start).take(range.len()).collect()
+/// };
+/// assert_eq!(chars(parent, conflict.parent_range()), " 5pm");
+/// assert_eq!(chars(&merged, conflict.merged_range()), " 6pm 7pm");
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "ConflictRepr"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// Character range of the region in the original text
+    pub(crate) parent_range: Range<usize>,
+
+    /// Character range of the region's resolution in the merged text
+    pub(crate) merged_range: Range<usize>,
+
+    /// The original text of the region
+    pub(crate) base: String,
+
+    /// Each side's version of the region, indexed by `Side::index`. There are
+    /// always at least two.
+    pub(crate) versions: Vec<String>,
+}
+
+impl Conflict {
+    /// Character range of the region in the original text
+    #[must_use]
+    pub fn parent_range(&self) -> Range<usize> {
+        self.parent_range.clone()
+    }
+
+    /// Character range of the region's resolution in the merged text
+    #[must_use]
+    pub fn merged_range(&self) -> Range<usize> {
+        self.merged_range.clone()
+    }
+
+    /// The original text of the region
+    #[must_use]
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// The left side's (`Side::Left`) replacement of the region
+    #[must_use]
+    pub fn left(&self) -> &str {
+        &self.versions[0]
+    }
+
+    /// The right side's (`Side::Right`) replacement of the region
+    #[must_use]
+    pub fn right(&self) -> &str {
+        &self.versions[1]
+    }
+
+    /// Every side's replacement of the region, indexed by `Side::index`.
+    /// Sides which didn't change the region have the original text.
+    #[must_use]
+    pub fn versions(&self) -> &[String] {
+        &self.versions
+    }
+}
+
+/// Serialized form of `Conflict`, which is checked to have a version for at
+/// least the left and the right sides when deserialized
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct ConflictRepr {
+    parent_range: Range<usize>,
+    merged_range: Range<usize>,
+    base: String,
+    versions: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<ConflictRepr> for Conflict {
+    type Error = String;
+
+    fn try_from(repr: ConflictRepr) -> Result<Self, Self::Error> {
+        if repr.versions.len() < 2 {
+            return Err(format!(
+                "a conflict needs at least 2 versions, got {}",
+                repr.versions.len()
+            ));
+        }
+
+        Ok(Conflict {
+            parent_range: repr.parent_range,
+            merged_range: repr.merged_range,
+            base: repr.base,
+            versions: repr.versions,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use pretty_assertions::{assert_eq, assert_ne};
+
+    use super::*;
+
+    #[test]
+    fn test_deserialization_needs_left_and_right_versions() {
+        let conflict = Conflict {
+            parent_range: 0..4,
+            merged_range: 0..8,
+            base: " 5pm".to_owned(),
+            versions: vec![" 6pm".to_owned(), " 7pm".to_owned()],
+        };
+
+        let serialized = serde_yaml::to_string(&conflict).unwrap();
+        let deserialized: Conflict = serde_yaml::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, conflict);
+        assert_eq!(deserialized.right(), " 7pm");
+
+        let truncated = serialized.replace("- ' 7pm'\n", "");
+        assert_ne!(truncated, serialized);
+        assert!(serde_yaml::from_str::<Conflict>(&truncated).is_err());
+    }
+}
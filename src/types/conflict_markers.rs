@@ -0,0 +1,526 @@
+use std::{iter, ops::Range};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::types::{conflict::Conflict, history::History, span_with_history::SpanWithHistory};
+
+/// Layout of the conflict regions, following the `merge.conflictStyle`
+/// options of git
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictStyle {
+    /// Each side's version separated by `=======`
+    #[default]
+    Merge,
+
+    /// Like `Merge`, with the original text after a `|||||||` marker
+    Diff3,
+
+    /// Like `Diff3`, but lines which are the same at the beginning and end of
+    /// every side's version are moved outside of the markers
+    Zdiff3,
+}
+
+/// Options for rendering a merge result with git-style conflict markers
+///
+/// ```
+/// use reconcile_text::{reconcile, BuiltinTokenizer, ConflictMarkers, ConflictStyle};
+///
+/// let parent = "name = demo\nversion = 1\n";
+/// let left = "name = demo\nversion = 2\n";
+/// let right = "name = demo\nversion = 3\n";
+///
+/// let result = reconcile(parent, &left.into(), &right.into(), &*BuiltinTokenizer::Line);
+/// let markers = ConflictMarkers::new(ConflictStyle::Diff3).with_labels("ours", "base", "theirs");
+///
+/// assert_eq!(
+///     result.apply_with_conflicts(&markers),
+///     concat!(
+///         "name = demo\n",
+///         "<<<<<<< ours\n",
+///         "version = 2\n",
+///         "||||||| base\n",
+///         "version = 1\n",
+///         "=======\n",
+///         "version = 3\n",
+///         ">>>>>>> theirs\n",
+///     )
+/// );
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConflictMarkers {
+    style: ConflictStyle,
+    left_label: String,
+    base_label: String,
+    right_label: String,
+}
+
+impl ConflictMarkers {
+    /// Create markers of the given style without labels
+    #[must_use]
+    pub fn new(style: ConflictStyle) -> Self {
+        Self {
+            style,
+            ..Self::default()
+        }
+    }
+
+    /// Set the labels printed after the `<<<<<<<`, `|||||||`, and `>>>>>>>`
+    /// markers. In N-way merges, the versions of the sides between the first
+    /// and the last one are only separated by `=======`.
+    #[must_use]
+    pub fn with_labels(
+        self,
+        left: impl Into<String>,
+        base: impl Into<String>,
+        right: impl Into<String>,
+    ) -> Self {
+        Self {
+            left_label: left.into(),
+            base_label: base.into(),
+            right_label: right.into(),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn style(&self) -> ConflictStyle {
+        self.style
+    }
+
+    /// Replace the conflicting regions of the merged text with marked-up
+    /// blocks. Regions are widened to whole lines of the merged text so that
+    /// the markers can be on their own lines, and regions sharing a line are
+    /// rendered as a single block.
+    ///
+    /// The base and each version of a block are widened to whole lines of
+    /// their own text, the original and each side's text, which are rebuilt
+    /// from `spans`, the provenance of the merged text.
+    pub(crate) fn render(
+        &self,
+        merged: &str,
+        conflicts: &[Conflict],
+        spans: impl Iterator<Item = SpanWithHistory>,
+    ) -> String {
+        let chars: Vec<char> = merged.chars().collect();
+        let groups = group_by_lines(&chars, conflicts);
+        let sections = Sections::new(conflicts, &groups, spans);
+        let mut result = String::with_capacity(merged.len());
+        let mut position = 0;
+
+        for (index, group) in groups.iter().enumerate() {
+            result.extend(&chars[position..group.lines.start]);
+
+            let lines = sections.lines(index, group);
+            self.render_block(&mut result, &lines[0], &lines[1..]);
+            position = group.lines.end;
+        }
+
+        result.extend(&chars[position..]);
+        result
+    }
+
+    fn render_block(&self, result: &mut String, base: &str, versions: &[String]) {
+        let mut versions: Vec<&str> = versions.iter().map(String::as_str).collect();
+        let mut common_suffix = "";
+
+        if self.style == ConflictStyle::Zdiff3 {
+            let prefix_length = common_line_prefix_length(&versions);
+            result.push_str(&versions[0][..prefix_length]);
+            for version in &mut versions {
+                *version = &version[prefix_length..];
+            }
+
+            let suffix_length = common_line_suffix_length(&versions);
+            common_suffix = &versions[0][versions[0].len() - suffix_length..];
+            for version in &mut versions {
+                *version = &version[..version.len() - suffix_length];
+            }
+        }
+
+        push_marker(result, "<<<<<<<", &self.left_label);
+        push_lines(result, versions[0]);
+
+        if self.style != ConflictStyle::Merge {
+            push_marker(result, "|||||||", &self.base_label);
+            push_lines(result, base);
+        }
+
+        for version in &versions[1..] {
+            push_marker(result, "=======", "");
+            push_lines(result, version);
+        }
+
+        push_marker(result, ">>>>>>>", &self.right_label);
+        result.push_str(common_suffix);
+    }
+}
+
+/// Conflicts (by index) which share lines of the merged text, together with
+/// the character range of those lines
+struct LineGroup {
+    conflicts: Range<usize>,
+    lines: Range<usize>,
+}
+
+/// The original text followed by each side's text, together with the
+/// character ranges of the conflicts' base and versions in them, and of the
+/// text they share with the lines of each group of conflicts in the merged text
+struct Sections {
+    texts: Vec<Vec<char>>,
+    conflict_ranges: Vec<Vec<Range<usize>>>,
+    group_ranges: Vec<Vec<Option<Range<usize>>>>,
+}
+
+impl Sections {
+    fn new(
+        conflicts: &[Conflict],
+        groups: &[LineGroup],
+        spans: impl Iterator<Item = SpanWithHistory>,
+    ) -> Self {
+        let text_count = conflicts[0].versions.len() + 1;
+        let mut sections = Self {
+            texts: vec![Vec::new(); text_count],
+            conflict_ranges: Vec::with_capacity(conflicts.len()),
+            group_ranges: vec![vec![None; text_count]; groups.len()],
+        };
+        let mut next_group = 0;
+
+        for span in spans {
+            while let Some(conflict) = conflicts.get(sections.conflict_ranges.len())
+                && (is_within(&span, conflict) || is_past(&span, conflict))
+            {
+                sections.push_conflict(conflict);
+            }
+
+            // The spans of a conflict are replaced by its base and versions
+            let is_conflicting = sections
+                .conflict_ranges
+                .len()
+                .checked_sub(1)
+                .is_some_and(|previous| is_within(&span, &conflicts[previous]));
+            if is_conflicting {
+                continue;
+            }
+
+            let merged_range = span.merged_range();
+            while groups
+                .get(next_group)
+                .is_some_and(|group| group.lines.end <= merged_range.start)
+            {
+                next_group += 1;
+            }
+
+            for (index, text) in sections.texts.iter().enumerate() {
+                if !has_text(&span, index) {
+                    continue;
+                }
+
+                for (group, ranges) in groups[next_group..]
+                    .iter()
+                    .zip(&mut sections.group_ranges[next_group..])
+                    .take_while(|(group, _)| group.lines.start < merged_range.end)
+                {
+                    let start = text.len() + group.lines.start.saturating_sub(merged_range.start);
+                    let end =
+                        text.len() + group.lines.end.min(merged_range.end) - merged_range.start;
+                    let range = ranges[index].get_or_insert(start..end);
+                    range.end = end;
+                }
+            }
+
+            for (index, text) in sections.texts.iter_mut().enumerate() {
+                if has_text(&span, index) {
+                    text.extend(span.as_str().chars());
+                }
+            }
+        }
+
+        for conflict in &conflicts[sections.conflict_ranges.len()..] {
+            sections.push_conflict(conflict);
+        }
+
+        sections
+    }
+
+    fn push_conflict(&mut self, conflict: &Conflict) {
+        let texts = iter::once(&conflict.base).chain(&conflict.versions);
+        let ranges = self
+            .texts
+            .iter_mut()
+            .zip(texts)
+            .map(|(section, text)| {
+                let start = section.len();
+                section.extend(text.chars());
+                start..section.len()
+            })
+            .collect();
+
+        self.conflict_ranges.push(ranges);
+    }
+
+    /// The whole lines of each text which contain the conflicts of the
+    /// `index`-th group and the text they share with the merged lines
+    fn lines(&self, index: usize, group: &LineGroup) -> Vec<String> {
+        self.texts
+            .iter()
+            .enumerate()
+            .map(|(text_index, text)| {
+                let mut start = self.conflict_ranges[group.conflicts.start][text_index].start;
+                let mut end = self.conflict_ranges[group.conflicts.end - 1][text_index].end;
+                if let Some(shared) = &self.group_ranges[index][text_index] {
+                    start = start.min(shared.start);
+                    end = end.max(shared.end);
+                }
+
+                text[line_range(text, start..end)].iter().collect()
+            })
+            .collect()
+    }
+}
+
+/// Whether the `index`-th text, the original or a side's text, has the text
+/// of `span`. Text which more than one side added or removed is treated as
+/// if every side had.
+fn has_text(span: &SpanWithHistory, index: usize) -> bool {
+    if span.history() == History::Unchanged {
+        return true;
+    }
+
+    let is_own = index
+        .checked_sub(1)
+        .is_some_and(|side| span.peer().is_none_or(|peer| peer == side));
+    is_own != span.is_removed()
+}
+
+/// Whether `span` is part of `conflict`
+fn is_within(span: &SpanWithHistory, conflict: &Conflict) -> bool {
+    if span.is_removed() {
+        let parent_range = span.parent_range();
+        parent_range.start < conflict.parent_range.end
+            && conflict.parent_range.start < parent_range.end
+    } else {
+        let merged_range = span.merged_range();
+        conflict.merged_range.start <= merged_range.start
+            && merged_range.end <= conflict.merged_range.end
+    }
+}
+
+/// Whether `span` comes after `conflict`
+fn is_past(span: &SpanWithHistory, conflict: &Conflict) -> bool {
+    span.merged_range().start >= conflict.merged_range.end
+        && (!span.is_removed() || span.parent_range().start >= conflict.parent_range.end)
+}
+
+/// `range` widened to the start of its first line and the end of its last
+/// line, including the line break
+fn line_range(chars: &[char], range: Range<usize>) -> Range<usize> {
+    let line_start = chars[..range.start]
+        .iter()
+        .rposition(|c| *c == '\n')
+        .map_or(0, |newline| newline + 1);
+
+    let is_line_end = range.end == 0 || chars[range.end - 1] == '\n';
+    let line_end = if is_line_end {
+        range.end
+    } else {
+        chars[range.end..]
+            .iter()
+            .position(|c| *c == '\n')
+            .map_or(chars.len(), |newline| range.end + newline + 1)
+    };
+
+    line_start..line_end
+}
+
+fn group_by_lines(chars: &[char], conflicts: &[Conflict]) -> Vec<LineGroup> {
+    let mut groups: Vec<LineGroup> = Vec::new();
+
+    for (index, conflict) in conflicts.iter().enumerate() {
+        let lines = line_range(chars, conflict.merged_range.clone());
+
+        if let Some(group) = groups.last_mut()
+            && lines.start < group.lines.end
+        {
+            group.conflicts.end = index + 1;
+            group.lines.end = group.lines.end.max(lines.end);
+        } else {
+            groups.push(LineGroup {
+                conflicts: index..index + 1,
+                lines,
+            });
+        }
+    }
+
+    groups
+}
+
+fn push_marker(result: &mut String, marker: &str, label: &str) {
+    result.push_str(marker);
+    if !label.is_empty() {
+        result.push(' ');
+        result.push_str(label);
+    }
+    result.push('\n');
+}
+
+fn push_lines(result: &mut String, text: &str) {
+    result.push_str(text);
+    if !text.is_empty() && !text.ends_with('\n') {
+        result.push('\n');
+    }
+}
+
+/// Byte length of the complete lines all `versions` start with
+fn common_line_prefix_length(versions: &[&str]) -> usize {
+    let mut length = 0;
+
+    for line in versions[0].split_inclusive('\n') {
+        let is_common = line.ends_with('\n')
+            && versions
+                .iter()
+                .all(|version| version[length..].starts_with(line));
+        if !is_common {
+            break;
+        }
+
+        length += line.len();
+    }
+
+    length
+}
+
+/// Byte length of the complete lines all `versions` end with
+fn common_line_suffix_length(versions: &[&str]) -> usize {
+    let mut length = 0;
+
+    for line in versions[0].split_inclusive('\n').rev() {
+        let remaining = &versions[0][..versions[0].len() - length];
+        let is_line_start = remaining.len() == line.len()
+            || remaining[..remaining.len() - line.len()].ends_with('\n');
+        let is_common = line.ends_with('\n')
+            && is_line_start
+            && versions.iter().all(|version| {
+                version.len() >= length + line.len()
+                    && version[..version.len() - length].ends_with(line)
+                    && (version.len() == length + line.len()
+                        || version[..version.len() - length - line.len()].ends_with('\n'))
+            });
+        if !is_common {
+            break;
+        }
+
+        length += line.len();
+    }
+
+    length
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{BuiltinTokenizer, Tokenizer, reconcile};
+
+    fn render(
+        markers: &ConflictMarkers,
+        parent: &str,
+        left: &str,
+        right: &str,
+        tokenizer: &Tokenizer<String>,
+    ) -> String {
+        reconcile(parent, &left.into(), &right.into(), tokenizer).apply_with_conflicts(markers)
+    }
+
+    #[test]
+    fn test_render_widens_to_lines() {
+        assert_eq!(
+            render(
+                &ConflictMarkers::new(ConflictStyle::Merge),
+                "title\nthe brown fox\nend",
+                "title\nthe red fox\nend",
+                "title\nthe blue fox\nend",
+                &*BuiltinTokenizer::Word,
+            ),
+            concat!(
+                "title\n",
+                "<<<<<<<\n",
+                "the red fox\n",
+                "=======\n",
+                "the blue fox\n",
+                ">>>>>>>\n",
+                "end"
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_widens_each_section_with_its_own_text() {
+        assert_eq!(
+            render(
+                &ConflictMarkers::new(ConflictStyle::Diff3).with_labels("L", "B", "R"),
+                "alpha beta gamma delta",
+                "ALPHA beta LEFT delta",
+                "alpha beta RIGHT delta",
+                &*BuiltinTokenizer::Word,
+            ),
+            concat!(
+                "<<<<<<< L\n",
+                "ALPHA beta LEFT delta\n",
+                "||||||| B\n",
+                "alpha beta gamma delta\n",
+                "=======\n",
+                "alpha beta RIGHT delta\n",
+                ">>>>>>> R\n",
+            )
+        );
+
+        // The line break which only the left removed is in the right's version
+        assert_eq!(
+            render(
+                &ConflictMarkers::new(ConflictStyle::Diff3).with_labels("L", "B", "R"),
+                "a\nb gamma c\nend\n",
+                "a b LEFT c\nend\n",
+                "a\nb RIGHT c\nend!\n",
+                &*BuiltinTokenizer::Word,
+            ),
+            concat!(
+                "<<<<<<< L\n",
+                "a b LEFT c\n",
+                "||||||| B\n",
+                "a\nb gamma c\n",
+                "=======\n",
+                "a\nb RIGHT c\n",
+                ">>>>>>> R\n",
+                "end!\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_zdiff3_moves_common_lines_out() {
+        assert_eq!(
+            render(
+                &ConflictMarkers::new(ConflictStyle::Zdiff3).with_labels("L", "B", "R"),
+                "x\nz\n",
+                "x\nc\ny\nz\n",
+                "x\nc\nw\nz\n",
+                &*BuiltinTokenizer::Line,
+            ),
+            concat!(
+                "x\n",
+                "c\n",
+                "<<<<<<< L\n",
+                "y\n",
+                "||||||| B\n",
+                "=======\n",
+                "w\n",
+                ">>>>>>> R\n",
+                "z\n",
+            )
+        );
+    }
+}
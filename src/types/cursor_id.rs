@@ -0,0 +1,71 @@
+use std::fmt::{self, Display};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// Identifier of a `CursorPosition`: either a number or a string, such as a
+/// user or session identifier
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CursorId {
+    Number(usize),
+    Text(String),
+}
+
+impl Default for CursorId {
+    fn default() -> Self {
+        CursorId::Number(0)
+    }
+}
+
+impl Display for CursorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CursorId::Number(number) => write!(f, "{number}"),
+            CursorId::Text(text) => write!(f, "{text:?}"),
+        }
+    }
+}
+
+impl PartialEq<usize> for CursorId {
+    fn eq(&self, other: &usize) -> bool {
+        *self == CursorId::Number(*other)
+    }
+}
+
+impl PartialEq<&str> for CursorId {
+    fn eq(&self, other: &&str) -> bool {
+        matches!(self, CursorId::Text(text) if text == other)
+    }
+}
+
+impl From<usize> for CursorId {
+    fn from(value: usize) -> Self {
+        CursorId::Number(value)
+    }
+}
+
+impl From<String> for CursorId {
+    fn from(value: String) -> Self {
+        CursorId::Text(value)
+    }
+}
+
+impl From<&str> for CursorId {
+    fn from(value: &str) -> Self {
+        CursorId::Text(value.to_owned())
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl From<CursorId> for JsValue {
+    fn from(value: CursorId) -> Self {
+        match value {
+            CursorId::Number(number) => JsValue::from(number),
+            CursorId::Text(text) => JsValue::from(text),
+        }
+    }
+}
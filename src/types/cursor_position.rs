@@ -3,15 +3,30 @@ use serde::{Deserialize, Serialize};
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
+use crate::types::{cursor_id::CursorId, gravity::Gravity};
+
 /// `CursorPosition` represents the position of an identifiable cursor in a text
 /// document based on its (UTF-8) character index
+///
+/// Its id is a number (`CursorPosition::new`) or a string
+/// (`CursorPosition::named`), such as a user or session identifier. Its
+/// `gravity` decides whether it ends up before or after the text another side
+/// inserts exactly at its position.
 #[allow(clippy::unsafe_derive_deserialize)]
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct CursorPosition {
-    pub(crate) id: usize,
+    pub(crate) id: CursorId,
     pub(crate) char_index: usize,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) gravity: Gravity,
+
+    /// Set for the cursors standing in for the ends of a selection while
+    /// merging, see `selections_to_cursors`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) selection_end: Option<usize>,
 }
 
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
@@ -19,20 +34,36 @@ impl CursorPosition {
     #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     #[must_use]
     pub fn new(id: usize, char_index: usize) -> Self {
-        Self { id, char_index }
+        Self {
+            id: CursorId::Number(id),
+            char_index,
+            gravity: Gravity::default(),
+            selection_end: None,
+        }
+    }
+
+    /// Create a cursor identified by a string instead of a number
+    #[must_use]
+    pub fn named(id: &str, char_index: usize) -> Self {
+        Self {
+            id: CursorId::from(id),
+            ..Self::new(0, char_index)
+        }
     }
 
     #[must_use]
     pub fn with_index(&self, index: usize) -> Self {
         CursorPosition {
-            id: self.id,
             char_index: index,
+            ..self.clone()
         }
     }
 
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = withGravity))]
     #[must_use]
-    pub fn id(&self) -> usize {
-        self.id
+    pub fn with_gravity(mut self, gravity: Gravity) -> Self {
+        self.gravity = gravity;
+        self
     }
 
     #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = characterIndex))]
@@ -40,4 +71,27 @@ impl CursorPosition {
     pub fn char_index(&self) -> usize {
         self.char_index
     }
+
+    #[must_use]
+    pub fn gravity(&self) -> Gravity {
+        self.gravity
+    }
+}
+
+impl CursorPosition {
+    #[must_use]
+    pub fn id(&self) -> &CursorId {
+        &self.id
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl CursorPosition {
+    /// The id of the cursor, either a number or a string
+    #[wasm_bindgen(js_name = id)]
+    #[must_use]
+    pub fn js_id(&self) -> JsValue {
+        self.id.clone().into()
+    }
 }
@@ -0,0 +1,87 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::NumberOrText;
+
+/// A compact diff (as returned by `EditedText::to_diff`) together with what's
+/// needed to check that it's applied to the right text with the right
+/// tokenizer: the format version, the id of the tokenizer, and the length and
+/// hash of the original text.
+///
+/// ```
+/// use reconcile_text::{BuiltinTokenizer, DiffError, EditedText};
+///
+/// let tokenizer = BuiltinTokenizer::Word;
+/// let edited = EditedText::from_strings("Hello world", &"Hello there".into());
+/// let envelope = edited.to_diff_envelope(tokenizer.id()).unwrap();
+///
+/// let received =
+///     EditedText::from_diff_envelope("Hello world", envelope.clone(), &*tokenizer, tokenizer.id())
+///         .unwrap();
+/// assert_eq!(received.apply().text(), "Hello there");
+///
+/// // The diff isn't silently applied to a different text
+/// assert!(matches!(
+///     EditedText::from_diff_envelope("Hello word", envelope, &*tokenizer, tokenizer.id()),
+///     Err(DiffError::BaseMismatch { .. })
+/// ));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffEnvelope {
+    /// The version of the envelope's format
+    pub(crate) version: u32,
+
+    /// The id of the tokenizer the diff was made with
+    pub(crate) tokenizer: String,
+
+    /// The length of the original text in characters
+    pub(crate) base_length: usize,
+
+    /// The 64-bit FNV-1a hash of the original text's UTF-8 bytes
+    pub(crate) base_hash: u64,
+
+    pub(crate) diff: Vec<NumberOrText>,
+}
+
+impl DiffEnvelope {
+    /// The format version written by `EditedText::to_diff_envelope`, and the
+    /// only one `EditedText::from_diff_envelope` accepts
+    pub const VERSION: u32 = 1;
+
+    /// The version of the envelope's format
+    #[must_use]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// The id of the tokenizer the diff was made with
+    #[must_use]
+    pub fn tokenizer(&self) -> &str {
+        &self.tokenizer
+    }
+
+    /// The length of the original text in characters
+    #[must_use]
+    pub fn base_length(&self) -> usize {
+        self.base_length
+    }
+
+    /// The 64-bit FNV-1a hash of the original text's UTF-8 bytes
+    #[must_use]
+    pub fn base_hash(&self) -> u64 {
+        self.base_hash
+    }
+
+    /// The compact diff, in the format of `EditedText::to_diff`
+    #[must_use]
+    pub fn diff(&self) -> &[NumberOrText] {
+        &self.diff
+    }
+
+    /// Take the compact diff out of the envelope without any checks
+    #[must_use]
+    pub fn into_diff(self) -> Vec<NumberOrText> {
+        self.diff
+    }
+}
@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The algorithm used for diffing the tokens of the original and the updated
+/// texts. They all produce the same kind of operations, they only differ in
+/// which tokens they consider to be retained when there are multiple
+/// options.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DiffAlgorithm {
+    /// Myers' algorithm finding the shortest edit script, fast on similar
+    /// texts
+    #[default]
+    Myers,
+
+    /// Only aligns on tokens which are unique on both sides, so repeated
+    /// tokens (such as blank lines or list bullets) don't get matched up
+    /// with the wrong occurrence
+    Patience,
+
+    /// Like `Patience`, but aligns on the least frequent tokens even when
+    /// they're not unique, as in git's `--diff-algorithm=histogram`
+    Histogram,
+}
+
+/// What to do when diffing exceeds the budget set by `DiffOptions`
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DiffFallback {
+    /// Diff the texts line by line, then diff only the changed lines with
+    /// the tokenizer. Parts which still exceed the budget are treated as
+    /// `CapEditDistance` does.
+    #[default]
+    LineGranularity,
+
+    /// Stop searching once the budget is exceeded (like git's heuristic for
+    /// expensive diffs), and treat the rest of the differing range as
+    /// entirely deleted and reinserted
+    CapEditDistance,
+}
+
+/// A shortcut taken because diffing exceeded its budget. The diff is still
+/// correct, it just might not be minimal.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Degradation {
+    /// The texts were diffed line by line and only the changed lines were
+    /// diffed with the tokenizer
+    LineGranularity,
+
+    /// Some ranges weren't diffed in full, they were deleted and reinserted
+    /// as a whole instead
+    CappedEditDistance,
+
+    /// Looking for moved blocks ran out of time, so some moves might be
+    /// merged as a deletion and an unrelated insertion
+    IncompleteMoveDetection,
+}
+
+/// Options for diffing the original and the updated texts
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use reconcile_text::{DiffAlgorithm, DiffFallback, DiffOptions};
+///
+/// let options = DiffOptions::default();
+/// assert_eq!(options.algorithm(), DiffAlgorithm::Myers);
+/// assert_eq!(options.timeout(), None);
+///
+/// let options = options
+///     .with_algorithm(DiffAlgorithm::Histogram)
+///     .with_timeout(Duration::from_millis(50))
+///     .with_fallback(DiffFallback::CapEditDistance);
+/// assert_eq!(options.algorithm(), DiffAlgorithm::Histogram);
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DiffOptions {
+    algorithm: DiffAlgorithm,
+    timeout: Option<Duration>,
+    max_edit_distance: Option<usize>,
+    fallback: DiffFallback,
+}
+
+impl DiffOptions {
+    /// Set the diff algorithm, `DiffAlgorithm::Myers` by default
+    #[must_use]
+    pub fn with_algorithm(self, algorithm: DiffAlgorithm) -> Self {
+        Self { algorithm, ..self }
+    }
+
+    /// Limit the time spent diffing. The fallback gets the same amount of
+    /// time again, so the total can be up to twice the timeout. Looking for
+    /// moved blocks stops at the same deadline as the diff it follows.
+    ///
+    /// The timeout is ignored on `wasm32-unknown-unknown`, which has no clock
+    /// (`std::time::Instant` panics there), so limit the edit distance with
+    /// `with_max_edit_distance` to bound the work in the browser.
+    #[must_use]
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Limit the number of edits Myers' algorithm searches for at once
+    /// before falling back. Patience and histogram diffing fall back once
+    /// the edits they've found so far, plus the fewest edits the range
+    /// they're aligning could need, exceed it. They use Myers' algorithm for
+    /// the ranges they can't align themselves.
+    #[must_use]
+    pub fn with_max_edit_distance(self, max_edit_distance: usize) -> Self {
+        Self {
+            max_edit_distance: Some(max_edit_distance),
+            ..self
+        }
+    }
+
+    /// Set what happens when the timeout or the maximum edit distance is
+    /// exceeded, `DiffFallback::LineGranularity` by default
+    #[must_use]
+    pub fn with_fallback(self, fallback: DiffFallback) -> Self {
+        Self { fallback, ..self }
+    }
+
+    #[must_use]
+    pub fn algorithm(&self) -> DiffAlgorithm {
+        self.algorithm
+    }
+
+    #[must_use]
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    #[must_use]
+    pub fn max_edit_distance(&self) -> Option<usize> {
+        self.max_edit_distance
+    }
+
+    #[must_use]
+    pub fn fallback(&self) -> DiffFallback {
+        self.fallback
+    }
+}
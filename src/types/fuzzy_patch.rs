@@ -0,0 +1,130 @@
+use std::ops::Range;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How a hunk of a patch was applied by `apply_diff_fuzzy`
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkStatus {
+    /// The text the hunk replaces was found together with all of its context
+    Clean,
+
+    /// The text the hunk replaces was found unchanged, but only part of its
+    /// context was, so the hunk was merged with the changes around it
+    Fuzzed,
+
+    /// The text the hunk replaces, or most of its context, couldn't be found,
+    /// so the hunk was left out
+    Rejected,
+}
+
+/// A run of changes between retained text together with some of the retained
+/// text around it, which `apply_diff_fuzzy` uses to find where the changes
+/// belong in a text which has drifted from the original
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchHunk {
+    /// Character range of the text the hunk replaces in the original
+    pub(crate) base_range: Range<usize>,
+
+    /// The original text right before the hunk
+    pub(crate) before: String,
+
+    /// The text the hunk deletes
+    pub(crate) deleted: String,
+
+    /// The text the hunk inserts
+    pub(crate) inserted: String,
+
+    /// The original text right after the hunk
+    pub(crate) after: String,
+}
+
+impl PatchHunk {
+    /// Character range of the text the hunk replaces in the original
+    #[must_use]
+    pub fn base_range(&self) -> Range<usize> {
+        self.base_range.clone()
+    }
+
+    /// The original text right before the hunk
+    #[must_use]
+    pub fn before(&self) -> &str {
+        &self.before
+    }
+
+    /// The text the hunk deletes
+    #[must_use]
+    pub fn deleted(&self) -> &str {
+        &self.deleted
+    }
+
+    /// The text the hunk inserts
+    #[must_use]
+    pub fn inserted(&self) -> &str {
+        &self.inserted
+    }
+
+    /// The original text right after the hunk
+    #[must_use]
+    pub fn after(&self) -> &str {
+        &self.after
+    }
+
+    /// The original text the hunk covers, including its context
+    pub(crate) fn pattern(&self) -> String {
+        format!("{}{}{}", self.before, self.deleted, self.after)
+    }
+}
+
+/// The result of applying a patch to a text which drifted from the original
+/// with `apply_diff_fuzzy`
+///
+/// ```
+/// use reconcile_text::{BuiltinTokenizer, EditedText, HunkStatus, apply_diff_fuzzy};
+///
+/// let original = "Dear Ann,\nsee you on Monday at 5.\nBest wishes,\nBob";
+/// let patch = EditedText::from_strings(
+///     original,
+///     &"Hi Ann,\nsee you on Friday at 5.\nBest wishes,\nBobby".into(),
+/// )
+/// .to_patch();
+///
+/// let current = "Dear Ann,\nsee you on Tuesday at 5.\nBest regards,\nBob";
+/// let patched = apply_diff_fuzzy(current, &patch, &*BuiltinTokenizer::Word);
+/// assert_eq!(
+///     patched.text(),
+///     "Hi Ann,\nsee you on Tuesday at 5.\nBest regards,\nBobby"
+/// );
+/// assert_eq!(
+///     patched.statuses(),
+///     &[HunkStatus::Clean, HunkStatus::Rejected, HunkStatus::Fuzzed]
+/// );
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyPatch {
+    pub(crate) text: String,
+    pub(crate) statuses: Vec<HunkStatus>,
+}
+
+impl FuzzyPatch {
+    /// The current text with the hunks which weren't rejected applied
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// How each hunk of the patch was applied, in the order of the patch
+    #[must_use]
+    pub fn statuses(&self) -> &[HunkStatus] {
+        &self.statuses
+    }
+
+    /// Whether every hunk was applied, with or without fuzz
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        !self.statuses.contains(&HunkStatus::Rejected)
+    }
+}
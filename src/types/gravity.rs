@@ -0,0 +1,35 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// Which side of a concurrent insertion a cursor sticks to when the other
+/// side inserts text exactly at the cursor's position
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Gravity {
+    /// Stay before the inserted text, sticking to the character before the
+    /// cursor
+    Before,
+
+    /// Move after the inserted text
+    After,
+
+    /// Like `Before`, except that a cursor at the very start of the text moves
+    /// after the text inserted there, as cursors did before they had a
+    /// gravity
+    #[default]
+    Legacy,
+}
+
+impl Gravity {
+    /// Whether a cursor at `index` stays before the text inserted there
+    pub(crate) fn stays_before_insertion_at(self, index: usize) -> bool {
+        match self {
+            Gravity::Before => true,
+            Gravity::After => false,
+            Gravity::Legacy => index > 0,
+        }
+    }
+}
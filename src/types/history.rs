@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
+use crate::types::side::Side;
+
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,9 +15,24 @@ pub enum History {
     AddedFromRight = "AddedFromRight",
     RemovedFromLeft = "RemovedFromLeft",
     RemovedFromRight = "RemovedFromRight",
+    AddedByBoth = "AddedByBoth",
+    RemovedByBoth = "RemovedByBoth",
+    Added = "Added",
+    Removed = "Removed",
 }
 
 /// Provenance label for each span returned by `apply_with_history`
+///
+/// In N-way merges, `*FromLeft` marks the first fork and `*FromRight` any of
+/// the others; `SpanWithHistory::peer` tells them apart.
+///
+/// Text which more than one side inserted or deleted the same way is
+/// `AddedByBoth` or `RemovedByBoth`. Text which one side inserted or deleted
+/// in a region that another side edited differently (see `Conflict`) keeps
+/// its side's label, and `SpanWithHistory::is_conflicting` flags it.
+///
+/// The spans of a two-way diff (see `diff_with_history`) are `Unchanged`,
+/// `Added` or `Removed`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg(not(feature = "wasm"))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -25,4 +42,46 @@ pub enum History {
     AddedFromRight,
     RemovedFromLeft,
     RemovedFromRight,
+    AddedByBoth,
+    RemovedByBoth,
+    Added,
+    Removed,
+}
+
+impl History {
+    /// Label for text inserted by `side`. In N-way merges, every fork after
+    /// the first one is reported as `AddedFromRight`.
+    pub(crate) fn added_by(side: Side) -> Self {
+        if side == Side::Left {
+            History::AddedFromLeft
+        } else {
+            History::AddedFromRight
+        }
+    }
+
+    /// Label for text deleted by `side`. In N-way merges, every fork after
+    /// the first one is reported as `RemovedFromRight`.
+    pub(crate) fn removed_by(side: Side) -> Self {
+        if side == Side::Left {
+            History::RemovedFromLeft
+        } else {
+            History::RemovedFromRight
+        }
+    }
+
+    /// The side implied by the label, `None` for unchanged text and text
+    /// more than one side edited
+    pub(crate) fn side(self) -> Option<Side> {
+        match self {
+            History::AddedFromLeft | History::RemovedFromLeft => Some(Side::Left),
+            History::AddedFromRight | History::RemovedFromRight => Some(Side::Right),
+            History::Unchanged
+            | History::AddedByBoth
+            | History::RemovedByBoth
+            | History::Added
+            | History::Removed => None,
+            #[cfg(feature = "wasm")]
+            History::__Invalid => None,
+        }
+    }
 }
@@ -0,0 +1,214 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::types::{history::History, span_with_history::SpanWithHistory};
+
+/// Options for rendering the provenance of a merge result as HTML, with
+/// added text in `<ins>` and removed text in `<del>` elements, each with the
+/// CSS class of its `History`, plus an extra class for the spans of
+/// conflicting regions (see `SpanWithHistory::is_conflicting`). Unchanged
+/// text is only wrapped in a `<span>` if its class isn't empty.
+///
+/// ```
+/// use reconcile_text::{reconcile, BuiltinTokenizer, History, HtmlRenderer};
+///
+/// let parent = "Fish & chips";
+/// let left = "Fish & chips & peas";
+/// let right = "Cod & chips";
+///
+/// let result = reconcile(parent, &left.into(), &right.into(), &*BuiltinTokenizer::Word);
+/// let renderer = HtmlRenderer::default().with_class(History::AddedFromLeft, "theirs");
+///
+/// assert_eq!(
+///     result.apply_with_html(&renderer),
+///     concat!(
+///         r#"<del class="removed-right">Fish</del><ins class="added-right">Cod</ins>"#,
+///         r#" &amp; chips<ins class="theirs"> &amp; peas</ins>"#,
+///     )
+/// );
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtmlRenderer {
+    classes: Vec<(History, String)>,
+    conflict_class: String,
+}
+
+impl Default for HtmlRenderer {
+    fn default() -> Self {
+        Self {
+            classes: vec![
+                (History::Unchanged, String::new()),
+                (History::AddedFromLeft, "added-left".to_owned()),
+                (History::AddedFromRight, "added-right".to_owned()),
+                (History::RemovedFromLeft, "removed-left".to_owned()),
+                (History::RemovedFromRight, "removed-right".to_owned()),
+                (History::AddedByBoth, "added-both".to_owned()),
+                (History::RemovedByBoth, "removed-both".to_owned()),
+                (History::Added, "added".to_owned()),
+                (History::Removed, "removed".to_owned()),
+            ],
+            conflict_class: "conflicting".to_owned(),
+        }
+    }
+}
+
+impl HtmlRenderer {
+    /// Set the CSS class of the spans labelled `history`, which may be empty
+    /// for no class
+    #[must_use]
+    pub fn with_class(mut self, history: History, class: impl Into<String>) -> Self {
+        let class = class.into();
+        match self.classes.iter_mut().find(|(label, _)| *label == history) {
+            Some((_, existing)) => *existing = class,
+            None => self.classes.push((history, class)),
+        }
+
+        self
+    }
+
+    /// Set the extra CSS class of the spans of conflicting regions, which may
+    /// be empty for no extra class
+    #[must_use]
+    pub fn with_conflict_class(self, conflict_class: impl Into<String>) -> Self {
+        Self {
+            conflict_class: conflict_class.into(),
+            ..self
+        }
+    }
+
+    /// The CSS class of the spans labelled `history`
+    #[must_use]
+    pub fn class(&self, history: History) -> &str {
+        self.classes
+            .iter()
+            .find(|(label, _)| *label == history)
+            .map_or("", |(_, class)| class)
+    }
+
+    /// Render the spans of a merge result, as returned by `history_spans`
+    pub(crate) fn render(&self, spans: impl Iterator<Item = SpanWithHistory>) -> String {
+        let mut result = String::new();
+
+        for span in spans {
+            let mut class = self.class(span.history()).to_owned();
+            if span.is_conflicting() && !self.conflict_class.is_empty() {
+                if !class.is_empty() {
+                    class.push(' ');
+                }
+                class.push_str(&self.conflict_class);
+            }
+
+            let tag = if span.is_removed() {
+                "del"
+            } else if span.history() == History::Unchanged {
+                if class.is_empty() {
+                    escape_html(&span.text(), &mut result);
+                    continue;
+                }
+
+                "span"
+            } else {
+                "ins"
+            };
+
+            result.push('<');
+            result.push_str(tag);
+            if !class.is_empty() {
+                result.push_str(" class=\"");
+                escape_html(&class, &mut result);
+                result.push('"');
+            }
+            result.push('>');
+            escape_html(&span.text(), &mut result);
+            result.push_str("</");
+            result.push_str(tag);
+            result.push('>');
+        }
+
+        result
+    }
+}
+
+/// Append `text` to `result` with the characters which are special in HTML
+/// text and attribute values escaped
+fn escape_html(text: &str, result: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            _ => result.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{BuiltinTokenizer, reconcile};
+
+    #[test]
+    fn test_render_escapes_and_classes() {
+        let parent = "<b>bold</b> text";
+        let left = "<b>bold</b> \"quoted\" text";
+        let right = "<b>bold</b> text";
+
+        let result = reconcile(
+            parent,
+            &left.into(),
+            &right.into(),
+            &*BuiltinTokenizer::Word,
+        );
+        let renderer = HtmlRenderer::default()
+            .with_class(History::Unchanged, "same")
+            .with_class(History::AddedFromLeft, "a<b");
+
+        assert_eq!(
+            result.apply_with_html(&renderer),
+            concat!(
+                r#"<span class="same">&lt;b&gt;bold&lt;/b&gt;</span>"#,
+                r#"<ins class="a&lt;b"> &quot;quoted&quot;</ins>"#,
+                r#"<span class="same"> </span>"#,
+                r#"<span class="same">text</span>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_conflicting_spans() {
+        let parent = "Meet at 5pm";
+        let left = "Meet at 6pm";
+        let right = "Meet at 7pm";
+
+        let result = reconcile(
+            parent,
+            &left.into(),
+            &right.into(),
+            &*BuiltinTokenizer::Word,
+        );
+
+        assert_eq!(
+            result.apply_with_html(&HtmlRenderer::default()),
+            concat!(
+                "Meet at",
+                r#"<del class="removed-both conflicting"> 5pm</del>"#,
+                r#"<ins class="added-left conflicting"> 6pm</ins>"#,
+                r#"<ins class="added-right conflicting"> 7pm</ins>"#,
+            )
+        );
+        assert_eq!(
+            result.apply_with_html(&HtmlRenderer::default().with_conflict_class("")),
+            concat!(
+                "Meet at",
+                r#"<del class="removed-both"> 5pm</del>"#,
+                r#"<ins class="added-left"> 6pm</ins>"#,
+                r#"<ins class="added-right"> 7pm</ins>"#,
+            )
+        );
+    }
+}
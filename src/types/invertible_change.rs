@@ -0,0 +1,45 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// One step of an invertible diff. Unlike the compact diff of `to_diff`,
+/// deletes keep the removed text, so the diff can be inverted without the
+/// original text at hand.
+///
+/// ```
+/// use reconcile_text::{EditedText, InvertibleChange};
+///
+/// let edited = EditedText::from_strings("Hello world", &"Hello there".into());
+/// assert_eq!(
+///     edited.to_invertible_diff(),
+///     vec![
+///         InvertibleChange::Retain(5),
+///         InvertibleChange::Delete(" world".to_owned()),
+///         InvertibleChange::Insert(" there".to_owned()),
+///     ]
+/// );
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvertibleChange {
+    /// Keep the given number of characters
+    Retain(usize),
+
+    /// Insert the text
+    Insert(String),
+
+    /// Delete the text, which must match the next characters
+    Delete(String),
+}
+
+impl InvertibleChange {
+    /// The change undoing this one: inserts become deletes and vice versa
+    #[must_use]
+    pub fn invert(self) -> Self {
+        match self {
+            InvertibleChange::Retain(length) => InvertibleChange::Retain(length),
+            InvertibleChange::Insert(text) => InvertibleChange::Delete(text),
+            InvertibleChange::Delete(text) => InvertibleChange::Insert(text),
+        }
+    }
+}
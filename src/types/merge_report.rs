@@ -0,0 +1,108 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::Side;
+
+/// What one side contributed to a merge result
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SideReport {
+    pub(crate) inserted_characters: usize,
+    pub(crate) inserted_tokens: usize,
+    pub(crate) deleted_characters: usize,
+    pub(crate) deleted_tokens: usize,
+}
+
+impl SideReport {
+    /// The number of characters the side inserted into the result
+    #[must_use]
+    pub fn inserted_characters(&self) -> usize {
+        self.inserted_characters
+    }
+
+    /// The number of tokens the side inserted into the result
+    #[must_use]
+    pub fn inserted_tokens(&self) -> usize {
+        self.inserted_tokens
+    }
+
+    /// The number of characters of the original text the side deleted. Text
+    /// which more than one side deleted is only counted for one of them.
+    #[must_use]
+    pub fn deleted_characters(&self) -> usize {
+        self.deleted_characters
+    }
+
+    /// The number of tokens of the original text the side deleted, counted
+    /// like `deleted_characters`
+    #[must_use]
+    pub fn deleted_tokens(&self) -> usize {
+        self.deleted_tokens
+    }
+}
+
+/// Statistics of a merge for telemetry and for deciding whether the result
+/// needs human review, as returned by `EditedText::merge_report`
+///
+/// ```
+/// use reconcile_text::{reconcile, BuiltinTokenizer, Side};
+///
+/// let parent = "Meet at 5pm on Monday";
+/// let left = "Meet at 6pm on Monday";
+/// let right = "Meet at 7pm on Monday sharp";
+///
+/// let result = reconcile(parent, &left.into(), &right.into(), &*BuiltinTokenizer::Word);
+/// let report = result.merge_report(&*BuiltinTokenizer::Word);
+///
+/// assert_eq!(report.side(Side::Left).inserted_characters(), 4);
+/// assert_eq!(report.side(Side::Right).inserted_characters(), 10);
+/// // Both sides deleted " 5pm", it is only counted once
+/// assert_eq!(report.side(Side::Right).deleted_characters(), 4);
+/// assert_eq!(report.side(Side::Right).deleted_tokens(), 2);
+/// assert_eq!(report.conflict_count(), 1);
+/// assert!(report.risk_score() > 0.5);
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MergeReport {
+    pub(crate) sides: Vec<SideReport>,
+    pub(crate) conflict_count: usize,
+    pub(crate) deduplicated_insertions: usize,
+    pub(crate) risk_score: f64,
+}
+
+impl MergeReport {
+    /// What each side contributed, indexed by `Side::index`
+    #[must_use]
+    pub fn sides(&self) -> &[SideReport] {
+        &self.sides
+    }
+
+    /// What `side` contributed, which is empty for sides not in the merge
+    #[must_use]
+    pub fn side(&self, side: Side) -> SideReport {
+        self.sides.get(side.index()).copied().unwrap_or_default()
+    }
+
+    /// The number of regions of the original text which more than one side
+    /// edited differently, see `EditedText::conflicts`
+    #[must_use]
+    pub fn conflict_count(&self) -> usize {
+        self.conflict_count
+    }
+
+    /// The number of insertions which were trimmed because another side
+    /// inserted the same text at the same place
+    #[must_use]
+    pub fn deduplicated_insertions(&self) -> usize {
+        self.deduplicated_insertions
+    }
+
+    /// A heuristic between 0 and 1 of how likely the result needs review:
+    /// the share of the changed characters in conflicting regions, raised to
+    /// at least 0.5 if diffing took a shortcut (see `Degradation`)
+    #[must_use]
+    pub fn risk_score(&self) -> f64 {
+        self.risk_score
+    }
+}
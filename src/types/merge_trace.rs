@@ -0,0 +1,220 @@
+use std::fmt::{self, Debug, Display};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{CursorId, CursorPosition, Side, operation_transformation::Operation};
+
+/// A cursor moved from its position in one side's text to the merged text
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorShift {
+    pub(crate) id: CursorId,
+    pub(crate) from: usize,
+    pub(crate) to: usize,
+}
+
+impl CursorShift {
+    /// The id of the moved cursor
+    #[must_use]
+    pub fn id(&self) -> &CursorId {
+        &self.id
+    }
+
+    /// Character index of the cursor in its side's text
+    #[must_use]
+    pub fn from(&self) -> usize {
+        self.from
+    }
+
+    /// Character index of the cursor in the merged text
+    #[must_use]
+    pub fn to(&self) -> usize {
+        self.to
+    }
+}
+
+/// One step of weaving the sides' operations together: the operation picked
+/// (by `Operation::cmp_priority`) from the next operations of the sides, what
+/// `Operation::merge_operations` trimmed from it because of the other sides'
+/// last operations, and the cursors it moved. Operations are rendered with
+/// their `Display` implementation, such as `<insert ' big' at 5>`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeStep {
+    pub(crate) side: Side,
+    pub(crate) operation: String,
+    pub(crate) passed_over: Vec<(Side, String)>,
+    pub(crate) result: Option<String>,
+    pub(crate) trimmed_length: usize,
+    pub(crate) cursor_shifts: Vec<CursorShift>,
+}
+
+impl MergeStep {
+    /// Begin a step picking the next operation of `side` over the next
+    /// operations of the other sides
+    pub(crate) fn new<'a, T>(
+        side: Side,
+        next_operations: impl Iterator<Item = (Side, &'a Operation<T>)>,
+    ) -> Self
+    where
+        T: PartialEq + Clone + Debug + 'a,
+    {
+        let mut step = Self {
+            side,
+            operation: String::new(),
+            passed_over: Vec::new(),
+            result: None,
+            trimmed_length: 0,
+            cursor_shifts: Vec::new(),
+        };
+
+        for (next_side, operation) in next_operations {
+            if next_side == side {
+                step.operation = operation.to_string();
+                step.trimmed_length = operation.len();
+            } else {
+                step.passed_over.push((next_side, operation.to_string()));
+            }
+        }
+
+        step
+    }
+
+    /// Finish the step with the operation the picked one was trimmed to and
+    /// the cursors it moved from the given indexes of the side's text
+    pub(crate) fn finish<T>(
+        self,
+        result: &Operation<T>,
+        moved_cursors: &[CursorPosition],
+        origins: Vec<usize>,
+    ) -> Self
+    where
+        T: PartialEq + Clone + Debug,
+    {
+        Self {
+            result: (result.len() > 0).then(|| result.to_string()),
+            trimmed_length: self.trimmed_length - result.len(),
+            cursor_shifts: moved_cursors
+                .iter()
+                .zip(origins)
+                .map(|(cursor, from)| CursorShift {
+                    id: cursor.id.clone(),
+                    from,
+                    to: cursor.char_index,
+                })
+                .collect(),
+            ..self
+        }
+    }
+
+    /// The side whose operation was picked
+    #[must_use]
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    /// The picked operation, as it was in its side's operations
+    #[must_use]
+    pub fn operation(&self) -> &str {
+        &self.operation
+    }
+
+    /// The next operations of the other sides, which were ranked after the
+    /// picked one
+    #[must_use]
+    pub fn passed_over(&self) -> &[(Side, String)] {
+        &self.passed_over
+    }
+
+    /// The picked operation after trimming what the other sides already
+    /// inserted or deleted, or `None` if nothing was left of it
+    #[must_use]
+    pub fn result(&self) -> Option<&str> {
+        self.result.as_deref()
+    }
+
+    /// The number of characters trimmed from the picked operation
+    #[must_use]
+    pub fn trimmed_length(&self) -> usize {
+        self.trimmed_length
+    }
+
+    /// The cursors of the picked side moved into the merged text
+    #[must_use]
+    pub fn cursor_shifts(&self) -> &[CursorShift] {
+        &self.cursor_shifts
+    }
+}
+
+impl Display for MergeStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: picked {}", self.side, self.operation)?;
+        for (side, operation) in &self.passed_over {
+            write!(f, " over {side} {operation}")?;
+        }
+
+        match &self.result {
+            None => write!(f, ", dropped")?,
+            Some(result) if self.trimmed_length > 0 => write!(f, ", trimmed to {result}")?,
+            Some(_) => {}
+        }
+
+        for shift in &self.cursor_shifts {
+            write!(
+                f,
+                ", cursor {} moved from {} to {}",
+                shift.id, shift.from, shift.to
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The decisions of a merge, as returned by `EditedText::merge_with_trace`,
+/// for explaining why the merged text came out the way it did
+///
+/// ```
+/// use reconcile_text::{EditedText, Side};
+///
+/// let parent = "Hello world";
+/// let left = EditedText::from_strings(parent, &"Hello big world".into());
+/// let right = EditedText::from_strings(parent, &"Well, Hello big world".into());
+///
+/// let (merged, trace) = left.merge_with_trace(right);
+/// assert_eq!(merged.apply().text(), "Well, Hello big world");
+///
+/// // The right side's insertion of " big" was dropped as a duplicate
+/// let step = &trace.steps()[4];
+/// assert_eq!(step.side(), Side::Right);
+/// assert_eq!(step.operation(), "<insert ' big' at 5>");
+/// assert_eq!(step.result(), None);
+/// assert_eq!(
+///     step.to_string(),
+///     "Right: picked <insert ' big' at 5> over Left <equal ' ' from 5>, dropped"
+/// );
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MergeTrace {
+    pub(crate) steps: Vec<MergeStep>,
+}
+
+impl MergeTrace {
+    /// The steps in the order the operations were merged
+    #[must_use]
+    pub fn steps(&self) -> &[MergeStep] {
+        &self.steps
+    }
+}
+
+impl Display for MergeTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{step}")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,220 @@
+use std::ops::Range;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::types::{cursor_id::CursorId, cursor_position::CursorPosition, gravity::Gravity};
+
+/// `Selection` represents an identifiable selected range of a text document,
+/// spanning from its `anchor` (where the selection started) to its `head`
+/// (where the caret is). Both are (UTF-8) character indexes and the head can
+/// be before the anchor. A selection with `anchor == head` is a plain caret.
+///
+/// Like `CursorPosition`, selections are identified by a `CursorId`: a string
+/// (`Selection::new`), such as a user or session identifier, or a number
+/// (`Selection::numbered`).
+///
+/// ```
+/// use reconcile_text::{BuiltinTokenizer, Gravity, Selection, TextWithCursors, reconcile};
+///
+/// let parent = "Hello world";
+/// let left = TextWithCursors::from("Hello world").with_selections(vec![
+///     Selection::new("alice", 0, 5),
+///     Selection::caret("bob", 5).with_gravity(Gravity::After),
+/// ]);
+/// let right = "Hello dear world";
+///
+/// let merged = reconcile(parent, &left, &right.into(), &*BuiltinTokenizer::Word).apply();
+/// assert_eq!(merged.text(), "Hello dear world");
+///
+/// // Both selections ended at the insertion, but only bob's follows it
+/// assert_eq!(
+///     merged.selections(),
+///     vec![
+///         Selection::new("alice", 0, 5),
+///         Selection::caret("bob", 10).with_gravity(Gravity::After),
+///     ]
+/// );
+/// ```
+#[allow(clippy::unsafe_derive_deserialize)]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Selection {
+    pub(crate) id: CursorId,
+    pub(crate) anchor: usize,
+    pub(crate) head: usize,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) gravity: Gravity,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Selection {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    #[must_use]
+    pub fn new(id: &str, anchor: usize, head: usize) -> Self {
+        Self {
+            id: CursorId::from(id),
+            anchor,
+            head,
+            gravity: Gravity::default(),
+        }
+    }
+
+    /// Create a selection identified by a number instead of a string
+    #[must_use]
+    pub fn numbered(id: usize, anchor: usize, head: usize) -> Self {
+        Self {
+            id: CursorId::Number(id),
+            ..Self::new("", anchor, head)
+        }
+    }
+
+    /// Create an empty selection, that is, a caret at `char_index`
+    #[must_use]
+    pub fn caret(id: &str, char_index: usize) -> Self {
+        Self::new(id, char_index, char_index)
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = withGravity))]
+    #[must_use]
+    pub fn with_gravity(mut self, gravity: Gravity) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    #[must_use]
+    pub fn anchor(&self) -> usize {
+        self.anchor
+    }
+
+    #[must_use]
+    pub fn head(&self) -> usize {
+        self.head
+    }
+
+    #[must_use]
+    pub fn gravity(&self) -> Gravity {
+        self.gravity
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = isCollapsed))]
+    #[must_use]
+    pub fn is_collapsed(&self) -> bool {
+        self.anchor == self.head
+    }
+}
+
+impl Selection {
+    #[must_use]
+    pub fn id(&self) -> &CursorId {
+        &self.id
+    }
+
+    /// The selected character range, regardless of the selection's direction
+    #[must_use]
+    pub fn range(&self) -> Range<usize> {
+        self.anchor.min(self.head)..self.anchor.max(self.head)
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Selection {
+    /// The id of the selection, either a number or a string
+    #[wasm_bindgen(js_name = id)]
+    #[must_use]
+    pub fn js_id(&self) -> JsValue {
+        self.id.clone().into()
+    }
+}
+
+/// Turn the ends of `selections` into cursors, so that they can be moved
+/// along with the other cursors. `first_index` is the index of the first of
+/// `selections` among all the selections being merged, see
+/// `cursors_to_selections` for the reverse.
+pub(crate) fn selections_to_cursors(
+    selections: &[Selection],
+    first_index: usize,
+) -> impl Iterator<Item = CursorPosition> {
+    selections
+        .iter()
+        .enumerate()
+        .flat_map(move |(index, selection)| {
+            let end = 2 * (first_index + index);
+
+            [(end, selection.anchor), (end + 1, selection.head)].map(|(end, char_index)| {
+                CursorPosition {
+                    selection_end: Some(end),
+                    id: selection.id.clone(),
+                    ..CursorPosition::new(0, char_index).with_gravity(selection.gravity)
+                }
+            })
+        })
+}
+
+/// Move the selections to the positions of the cursors that
+/// `selections_to_cursors` created from them, which are removed from
+/// `cursors`
+pub(crate) fn cursors_to_selections(
+    cursors: &mut Vec<CursorPosition>,
+    selections: &mut [Selection],
+) {
+    cursors.retain(|cursor| {
+        let Some(end) = cursor.selection_end else {
+            return true;
+        };
+
+        let selection = &mut selections[end / 2];
+        if end % 2 == 0 {
+            selection.anchor = cursor.char_index;
+        } else {
+            selection.head = cursor.char_index;
+        }
+
+        false
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_selections_round_trip_through_cursors() {
+        let mut selections = vec![
+            Selection::new("a", 1, 4),
+            Selection::caret("b", 2).with_gravity(Gravity::After),
+            Selection::numbered(0, 3, 2),
+        ];
+
+        let mut cursors = vec![CursorPosition::new(0, 3), CursorPosition::named("a", 5)];
+        cursors.extend(selections_to_cursors(&selections, 0).map(|cursor| {
+            let index = cursor.char_index;
+            cursor.with_index(index * 10)
+        }));
+        assert_eq!(cursors[5].gravity(), Gravity::After);
+        assert_eq!(cursors[7].id(), &CursorId::Number(0));
+
+        cursors_to_selections(&mut cursors, &mut selections);
+
+        assert_eq!(
+            cursors,
+            vec![CursorPosition::new(0, 3), CursorPosition::named("a", 5)]
+        );
+        assert_eq!(
+            selections,
+            vec![
+                Selection::new("a", 10, 40),
+                Selection::caret("b", 20).with_gravity(Gravity::After),
+                Selection::numbered(0, 30, 20),
+            ]
+        );
+        assert_eq!(selections[0].range(), 10..40);
+    }
+}
@@ -1,22 +1,146 @@
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// Pretty-printable flag to tell which conflicting edit (side)
 /// an operation is associated with
+///
+/// Sides are identified by the index of their fork in the merge, so that more
+/// than two edits can be woven together. `Side::Left` and `Side::Right` are
+/// the first two forks, the others are `Side::Peer`. Each fork has exactly
+/// one `Side`, as a `PeerIndex` can only be created by `Side::new` for the
+/// forks after the first two.
+///
+/// With the `serde` feature, they are serialized as `"Left"` and `"Right"` as
+/// before N-way merges existed, and the other forks by their index.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", serde(into = "SideRepr", try_from = "SideRepr"))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub enum Side {
+    #[default]
     Left,
     Right,
+    Peer(PeerIndex),
+}
+
+/// Index of a fork after the first two, so always at least 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PeerIndex(usize);
+
+impl PeerIndex {
+    #[must_use]
+    pub fn get(self) -> usize {
+        self.0
+    }
+}
+
+impl Side {
+    #[must_use]
+    pub fn new(index: usize) -> Self {
+        match index {
+            0 => Side::Left,
+            1 => Side::Right,
+            index => Side::Peer(PeerIndex(index)),
+        }
+    }
+
+    /// Index of the fork (peer) this side refers to
+    #[must_use]
+    pub fn index(self) -> usize {
+        match self {
+            Side::Left => 0,
+            Side::Right => 1,
+            Side::Peer(index) => index.get(),
+        }
+    }
 }
 
 impl Display for Side {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Side::Left => write!(f, "Left"),
-            Side::Right => write!(f, "Right"),
+        match self.index() {
+            0 => write!(f, "Left"),
+            1 => write!(f, "Right"),
+            index => write!(f, "Peer {index}"),
+        }
+    }
+}
+
+impl Debug for Side {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+/// Serialized form of `Side`, naming the first two forks
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum SideRepr {
+    Index(usize),
+    Name(String),
+}
+
+#[cfg(feature = "serde")]
+impl From<Side> for SideRepr {
+    fn from(side: Side) -> Self {
+        match side.index() {
+            0 | 1 => SideRepr::Name(side.to_string()),
+            index => SideRepr::Index(index),
         }
     }
 }
+
+#[cfg(feature = "serde")]
+impl TryFrom<SideRepr> for Side {
+    type Error = String;
+
+    fn try_from(repr: SideRepr) -> Result<Self, Self::Error> {
+        match repr {
+            SideRepr::Index(index) => Ok(Side::new(index)),
+            SideRepr::Name(name) => match name.as_str() {
+                "Left" => Ok(Side::Left),
+                "Right" => Ok(Side::Right),
+                _ => Err(format!(
+                    "unknown side `{name}`, expected `Left`, `Right` or an index"
+                )),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_each_index_has_one_side() {
+        assert_eq!(Side::new(0), Side::Left);
+        assert_eq!(Side::new(1), Side::Right);
+        assert!(matches!(Side::new(2), Side::Peer(index) if index.get() == 2));
+
+        assert!(Side::Right < Side::new(2));
+        assert!(Side::new(2) < Side::new(10));
+        assert_eq!(Side::new(3).index(), 3);
+        assert_eq!(Side::new(3).to_string(), "Peer 3");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialization_names_left_and_right() {
+        let sides = vec![Side::Left, Side::Right, Side::new(2)];
+
+        let serialized = serde_yaml::to_string(&sides).unwrap();
+        assert_eq!(serialized, "- Left\n- Right\n- 2\n");
+
+        let deserialized: Vec<Side> = serde_yaml::from_str("- Left\n- Right\n- 1\n- 2\n").unwrap();
+        assert_eq!(
+            deserialized,
+            vec![Side::Left, Side::Right, Side::Right, Side::new(2)]
+        );
+
+        assert!(serde_yaml::from_str::<Side>("Middle").is_err());
+    }
+}
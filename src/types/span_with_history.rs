@@ -1,9 +1,11 @@
+use std::ops::Range;
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-use crate::types::history::History;
+use crate::types::{authorship::Authorship, history::History, side::Side};
 
 /// A text span annotated with its origin in a merge result
 #[allow(clippy::unsafe_derive_deserialize)]
@@ -13,13 +15,32 @@ use crate::types::history::History;
 pub struct SpanWithHistory {
     text: String,
     history: History,
+    peer: Option<usize>,
+    parent_range: Range<usize>,
+    author: Option<String>,
+    merged_range: Range<usize>,
+    merged_byte_range: Range<usize>,
+    merged_utf16_range: Range<usize>,
+    side_range: Option<Range<usize>>,
+    is_conflicting: bool,
 }
 
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl SpanWithHistory {
     #[must_use]
     pub fn new(text: String, history: History) -> Self {
-        SpanWithHistory { text, history }
+        SpanWithHistory {
+            text,
+            history,
+            peer: history.side().map(Side::index),
+            parent_range: 0..0,
+            author: None,
+            merged_range: 0..0,
+            merged_byte_range: 0..0,
+            merged_utf16_range: 0..0,
+            side_range: None,
+            is_conflicting: false,
+        }
     }
 
     #[must_use]
@@ -31,4 +52,220 @@ impl SpanWithHistory {
     pub fn text(&self) -> String {
         self.text.clone()
     }
+
+    /// Index of the fork that added or removed the span, `None` for unchanged
+    /// text. Tells apart the forks of an N-way merge, which are all labelled
+    /// as `*FromRight` after the first one.
+    #[must_use]
+    pub fn peer(&self) -> Option<usize> {
+        self.peer
+    }
+
+    /// Who wrote the span's text, if `EditedText::with_author` and
+    /// `EditedText::with_authorship` were used: the side's author for added
+    /// text, the original author for unchanged and removed text
+    #[must_use]
+    pub fn author(&self) -> Option<String> {
+        self.author.clone()
+    }
+
+    /// Whether the span was added or removed in a region which another side
+    /// edited differently (see `Conflict`), so it may need reviewing
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = isConflicting))]
+    #[must_use]
+    pub fn is_conflicting(&self) -> bool {
+        self.is_conflicting
+    }
+
+    /// Start of the span in the merged text in UTF-16 code units, the unit of
+    /// JavaScript string indices
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = mergedStart))]
+    #[must_use]
+    pub fn merged_start(&self) -> usize {
+        self.merged_utf16_range.start
+    }
+
+    /// End of the span in the merged text in UTF-16 code units, equal to the
+    /// start for removed text
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = mergedEnd))]
+    #[must_use]
+    pub fn merged_end(&self) -> usize {
+        self.merged_utf16_range.end
+    }
+}
+
+impl SpanWithHistory {
+    pub(crate) fn with_side(text: String, history: History, side: Side) -> Self {
+        SpanWithHistory {
+            text,
+            history,
+            peer: Some(side.index()),
+            parent_range: 0..0,
+            author: None,
+            merged_range: 0..0,
+            merged_byte_range: 0..0,
+            merged_utf16_range: 0..0,
+            side_range: None,
+            is_conflicting: false,
+        }
+    }
+
+    /// Set the character range of the original text the span came from
+    #[must_use]
+    pub fn with_parent_range(self, parent_range: Range<usize>) -> Self {
+        SpanWithHistory {
+            parent_range,
+            ..self
+        }
+    }
+
+    pub(crate) fn with_conflicting(self, is_conflicting: bool) -> Self {
+        SpanWithHistory {
+            is_conflicting,
+            ..self
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub(crate) fn with_author(self, author: Option<&str>) -> Self {
+        SpanWithHistory {
+            author: author.map(ToOwned::to_owned),
+            ..self
+        }
+    }
+
+    /// Relabel a span of a single, unmerged side's history as a span of a
+    /// two-way diff, where there are no sides to tell apart
+    pub(crate) fn into_two_way(self) -> Self {
+        let history = match self.history {
+            History::AddedFromLeft => History::Added,
+            History::RemovedFromLeft => History::Removed,
+            history => history,
+        };
+
+        SpanWithHistory {
+            history,
+            peer: None,
+            side_range: None,
+            ..self
+        }
+    }
+
+    /// Split the span of the original text into one span for each run of
+    /// `authorship` within its parent range
+    pub(crate) fn split_by_authors(self, authorship: &Authorship) -> Vec<Self> {
+        let mut remaining = self.text.as_str();
+
+        authorship
+            .runs_in(self.parent_range.clone())
+            .into_iter()
+            .map(|(run, author)| {
+                let byte_length = remaining
+                    .char_indices()
+                    .nth(run.len())
+                    .map_or(remaining.len(), |(index, _)| index);
+                let (text, rest) = remaining.split_at(byte_length);
+                remaining = rest;
+
+                SpanWithHistory {
+                    text: text.to_owned(),
+                    parent_range: run,
+                    author: author.map(ToOwned::to_owned),
+                    ..self.clone()
+                }
+            })
+            .collect()
+    }
+
+    /// Set the span's ranges in the merged text starting at `position`, and
+    /// move `position` past it if it's in the merged text. The span starts
+    /// at `side_start` in the updated text of the side which changed it.
+    pub(crate) fn with_merged_offsets(
+        self,
+        position: &mut TextOffset,
+        is_in_result: bool,
+        side_start: usize,
+    ) -> Self {
+        let start = *position;
+        if is_in_result {
+            position.advance(&self.text);
+        }
+        let length = position.chars - start.chars;
+
+        SpanWithHistory {
+            merged_range: start.chars..position.chars,
+            merged_byte_range: start.bytes..position.bytes,
+            merged_utf16_range: start.utf16..position.utf16,
+            side_range: self.peer.map(|_| side_start..side_start + length),
+            ..self
+        }
+    }
+
+    /// Whether the spans have the same text, history, and peer, regardless of
+    /// their ranges, author, and conflict flag. Useful for comparing them
+    /// with spans created by `SpanWithHistory::new`.
+    #[must_use]
+    pub fn same_content(&self, other: &Self) -> bool {
+        self.text == other.text && self.history == other.history && self.peer == other.peer
+    }
+
+    /// Character range of the original text the span came from. It's empty
+    /// for inserted text, at the position it was inserted at.
+    #[must_use]
+    pub fn parent_range(&self) -> Range<usize> {
+        self.parent_range.clone()
+    }
+
+    /// Whether the span is text of the original which isn't in the merged text
+    pub(crate) fn is_removed(&self) -> bool {
+        self.merged_range.is_empty() && !self.parent_range.is_empty()
+    }
+
+    /// Character range of the span in the merged text. It's empty for
+    /// removed text, at the position it was removed from.
+    #[must_use]
+    pub fn merged_range(&self) -> Range<usize> {
+        self.merged_range.clone()
+    }
+
+    /// Byte range of the span in the merged text
+    #[must_use]
+    pub fn merged_byte_range(&self) -> Range<usize> {
+        self.merged_byte_range.clone()
+    }
+
+    /// Range of the span in the merged text in UTF-16 code units, as used by
+    /// JavaScript and many editors
+    #[must_use]
+    pub fn merged_utf16_range(&self) -> Range<usize> {
+        self.merged_utf16_range.clone()
+    }
+
+    /// Character range of the span in the updated text of the side which
+    /// added or removed it (see `peer`). It's empty for removed text, and
+    /// `None` for unchanged text and text more than one side changed the
+    /// same way.
+    #[must_use]
+    pub fn side_range(&self) -> Option<Range<usize>> {
+        self.side_range.clone()
+    }
+}
+
+/// An offset into a text in characters, UTF-8 bytes and UTF-16 code units
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct TextOffset {
+    pub(crate) chars: usize,
+    pub(crate) bytes: usize,
+    pub(crate) utf16: usize,
+}
+
+impl TextOffset {
+    pub(crate) fn advance(&mut self, text: &str) {
+        self.chars += text.chars().count();
+        self.bytes += text.len();
+        self.utf16 += text.encode_utf16().count();
+    }
 }
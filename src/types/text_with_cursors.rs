@@ -1,13 +1,14 @@
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-use crate::types::cursor_position::CursorPosition;
+use crate::types::{cursor_position::CursorPosition, selection::Selection};
 
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct TextWithCursors {
     text: String, // wasm-pack doesn't support generics so we can't use Cow here
     cursors: Vec<CursorPosition>,
+    selections: Vec<Selection>,
 }
 
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
@@ -20,16 +21,33 @@ impl TextWithCursors {
     pub fn new(text: String, cursors: Vec<CursorPosition>) -> Self {
         let length = text.chars().count();
         for cursor in &cursors {
-            assert!(
-                cursor.char_index <= length,
-                // cursor.char_index == length means that the cursor is at the end
-                "Cursor positions ({}) must be contained within the text (of length {length}) or \
-                 just after the end",
-                cursor.char_index
-            );
+            assert_within_text(cursor.char_index, length);
         }
 
-        Self { text, cursors }
+        Self {
+            text,
+            cursors,
+            selections: Vec::new(),
+        }
+    }
+
+    /// Attach selections to the text, replacing any previous ones
+    ///
+    /// # Panics
+    ///
+    /// Panics if the anchor or head of any selection exceeds the text's
+    /// character length.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = withSelections))]
+    #[must_use]
+    pub fn with_selections(mut self, selections: Vec<Selection>) -> Self {
+        let length = self.text.chars().count();
+        for selection in &selections {
+            assert_within_text(selection.anchor, length);
+            assert_within_text(selection.head, length);
+        }
+
+        self.selections = selections;
+        self
     }
 
     #[must_use]
@@ -41,6 +59,11 @@ impl TextWithCursors {
     pub fn cursors(&self) -> Vec<CursorPosition> {
         self.cursors.clone()
     }
+
+    #[must_use]
+    pub fn selections(&self) -> Vec<Selection> {
+        self.selections.clone()
+    }
 }
 
 impl TextWithCursors {
@@ -55,6 +78,7 @@ impl<'a> From<&'a str> for TextWithCursors {
         Self {
             text: text.into(),
             cursors: Vec::new(),
+            selections: Vec::new(),
         }
     }
 }
@@ -64,6 +88,7 @@ impl From<&String> for TextWithCursors {
         Self {
             text: text.to_owned(),
             cursors: Vec::new(),
+            selections: Vec::new(),
         }
     }
 }
@@ -73,6 +98,16 @@ impl From<String> for TextWithCursors {
         Self {
             text,
             cursors: Vec::new(),
+            selections: Vec::new(),
         }
     }
 }
+
+fn assert_within_text(char_index: usize, length: usize) {
+    assert!(
+        char_index <= length,
+        // char_index == length means that the cursor is at the end
+        "Cursor positions ({char_index}) must be contained within the text (of length {length}) \
+         or just after the end",
+    );
+}
@@ -0,0 +1,454 @@
+use std::fmt::Write;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    DiffError, NumberOrText,
+    utils::{edit::Edit, myers_diff::myers_diff},
+};
+
+const NO_NEWLINE_MARKER: &str = "\\ No newline at end of file\n";
+
+/// Options for rendering an edit as a unified diff (the format of `diff -u`,
+/// `git diff`, and GNU `patch`)
+///
+/// ```
+/// use reconcile_text::{BuiltinTokenizer, EditedText, UnifiedDiff};
+///
+/// let original = "one\ntwo\nthree\nfour\n";
+/// let edited = EditedText::from_strings(original, &"one\n2\nthree\nfour\n".into());
+///
+/// let options = UnifiedDiff::new(1).with_file_names("a/n.txt", "b/n.txt");
+/// let patch = edited.to_unified_diff(&options);
+/// assert_eq!(
+///     patch,
+///     concat!(
+///         "--- a/n.txt\n",
+///         "+++ b/n.txt\n",
+///         "@@ -1,3 +1,3 @@\n",
+///         " one\n",
+///         "-two\n",
+///         "+2\n",
+///         " three\n",
+///     )
+/// );
+///
+/// let parsed =
+///     EditedText::from_unified_diff(original, &patch, &*BuiltinTokenizer::Line).unwrap();
+/// assert_eq!(parsed.apply().text(), "one\n2\nthree\nfour\n");
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnifiedDiff {
+    context_lines: usize,
+    old_name: String,
+    new_name: String,
+}
+
+impl Default for UnifiedDiff {
+    /// Three lines of context, like `diff -u`
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+impl UnifiedDiff {
+    /// Render diffs with the given number of unchanged lines around each change
+    #[must_use]
+    pub fn new(context_lines: usize) -> Self {
+        Self {
+            context_lines,
+            old_name: "a".to_owned(),
+            new_name: "b".to_owned(),
+        }
+    }
+
+    /// Set the file names printed after the `---` and `+++` markers
+    #[must_use]
+    pub fn with_file_names(self, old: impl Into<String>, new: impl Into<String>) -> Self {
+        Self {
+            old_name: old.into(),
+            new_name: new.into(),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn context_lines(&self) -> usize {
+        self.context_lines
+    }
+
+    /// Render the line-by-line differences of the texts. Returns an empty
+    /// string if the texts are the same.
+    pub(crate) fn render(&self, original: &str, updated: &str) -> String {
+        let old_lines: Vec<&str> = original.split_inclusive('\n').collect();
+        let new_lines: Vec<&str> = updated.split_inclusive('\n').collect();
+
+        // Every line of both texts in the order of the diff
+        let mut lines: Vec<(Edit, &str)> = Vec::with_capacity(old_lines.len() + new_lines.len());
+        let (mut old_index, mut new_index) = (0, 0);
+        for edit in myers_diff(&old_lines, &new_lines) {
+            match edit {
+                Edit::Equal => {
+                    lines.push((edit, old_lines[old_index]));
+                    old_index += 1;
+                    new_index += 1;
+                }
+                Edit::Delete => {
+                    lines.push((edit, old_lines[old_index]));
+                    old_index += 1;
+                }
+                Edit::Insert => {
+                    lines.push((edit, new_lines[new_index]));
+                    new_index += 1;
+                }
+            }
+        }
+
+        // Deleted lines come before the added ones they're replaced with
+        for run in lines.chunk_by_mut(|(a, _), (b, _)| (*a == Edit::Equal) == (*b == Edit::Equal)) {
+            run.sort_by_key(|(edit, _)| *edit == Edit::Insert);
+        }
+
+        let mut result = String::new();
+        let (mut old_line, mut new_line) = (0, 0);
+        let mut position = 0;
+
+        for hunk in self.hunks(&lines) {
+            for (edit, _) in &lines[position..hunk.start] {
+                old_line += usize::from(*edit != Edit::Insert);
+                new_line += usize::from(*edit != Edit::Delete);
+            }
+
+            let hunk_lines = &lines[hunk.clone()];
+            let old_count = hunk_lines
+                .iter()
+                .filter(|(e, _)| *e != Edit::Insert)
+                .count();
+            let new_count = hunk_lines
+                .iter()
+                .filter(|(e, _)| *e != Edit::Delete)
+                .count();
+
+            if result.is_empty() {
+                let _ = write!(result, "--- {}\n+++ {}\n", self.old_name, self.new_name);
+            }
+            let _ = writeln!(
+                result,
+                "@@ -{} +{} @@",
+                hunk_range(old_line, old_count),
+                hunk_range(new_line, new_count)
+            );
+
+            for (edit, line) in hunk_lines {
+                result.push(match edit {
+                    Edit::Equal => ' ',
+                    Edit::Delete => '-',
+                    Edit::Insert => '+',
+                });
+                result.push_str(line);
+                if !line.ends_with('\n') {
+                    result.push('\n');
+                    result.push_str(NO_NEWLINE_MARKER);
+                }
+            }
+
+            old_line += old_count;
+            new_line += new_count;
+            position = hunk.end;
+        }
+
+        result
+    }
+
+    /// The ranges of `lines` making up the hunks: the changed lines with
+    /// `context_lines` around them, joined when their contexts would overlap
+    fn hunks(&self, lines: &[(Edit, &str)]) -> Vec<std::ops::Range<usize>> {
+        let mut hunks: Vec<std::ops::Range<usize>> = Vec::new();
+
+        for (index, _) in lines
+            .iter()
+            .enumerate()
+            .filter(|(_, (edit, _))| *edit != Edit::Equal)
+        {
+            let start = index.saturating_sub(self.context_lines);
+            let end = (index + 1 + self.context_lines).min(lines.len());
+
+            match hunks.last_mut() {
+                Some(last) if start <= last.end => last.end = end,
+                _ => hunks.push(start..end),
+            }
+        }
+
+        hunks
+    }
+
+    /// Turn a unified diff of `original` into a compact diff (in the format of
+    /// `EditedText::to_diff`), checking that its context and deleted lines
+    /// match `original`. Lines outside of hunks (such as the file names) are
+    /// ignored.
+    pub(crate) fn parse(original: &str, patch: &str) -> Result<Vec<NumberOrText>, DiffError> {
+        let old_lines: Vec<&str> = original.split_inclusive('\n').collect();
+        let mut diff = CompactDiff::default();
+        let mut old_line = 0;
+        let mut position = 0;
+
+        let mut patch_lines = patch.split_inclusive('\n').enumerate().peekable();
+        while let Some((index, line)) = patch_lines.next() {
+            if !line.starts_with("@@") {
+                continue;
+            }
+
+            let invalid = |reason: &str| DiffError::InvalidPatch {
+                line: index + 1,
+                reason: reason.to_owned(),
+            };
+
+            let (start, mut old_remaining, mut new_remaining) =
+                parse_hunk_header(line).ok_or_else(|| invalid("invalid hunk header"))?;
+            let hunk_start = if old_remaining == 0 {
+                start
+            } else {
+                start.saturating_sub(1)
+            };
+            if hunk_start < old_line || hunk_start > old_lines.len() {
+                return Err(invalid("hunk is out of order or beyond the original text"));
+            }
+
+            for skipped in &old_lines[old_line..hunk_start] {
+                let length = skipped.chars().count();
+                diff.retain(length)?;
+                position += length;
+            }
+            old_line = hunk_start;
+
+            while old_remaining > 0 || new_remaining > 0 {
+                let Some((index, line)) = patch_lines.next() else {
+                    return Err(DiffError::InvalidPatch {
+                        line: index + 1,
+                        reason: "hunk is shorter than its header".to_owned(),
+                    });
+                };
+                let invalid = |reason: &str| DiffError::InvalidPatch {
+                    line: index + 1,
+                    reason: reason.to_owned(),
+                };
+
+                // Some tools strip the space of empty context lines
+                let (kind, mut text) = match line.chars().next() {
+                    Some(kind @ (' ' | '-' | '+')) => (kind, &line[1..]),
+                    Some('\n') => (' ', line),
+                    _ => return Err(invalid("expected a context, deleted, or added line")),
+                };
+                if patch_lines
+                    .next_if(|(_, next)| next.starts_with('\\'))
+                    .is_some()
+                {
+                    text = text.strip_suffix('\n').unwrap_or(text);
+                }
+
+                if kind == '+' {
+                    new_remaining = new_remaining
+                        .checked_sub(1)
+                        .ok_or_else(|| invalid("hunk is longer than its header"))?;
+                    diff.insert(text);
+                    continue;
+                }
+
+                old_remaining = old_remaining
+                    .checked_sub(1)
+                    .ok_or_else(|| invalid("hunk is longer than its header"))?;
+                let actual = old_lines.get(old_line).copied().unwrap_or_default();
+                let length = actual.chars().count();
+
+                if kind == '-' {
+                    if text != actual {
+                        return Err(DiffError::DeletedTextMismatch {
+                            position,
+                            expected: text.to_owned(),
+                            actual: actual.to_owned(),
+                        });
+                    }
+                    diff.delete(length)?;
+                } else {
+                    new_remaining = new_remaining
+                        .checked_sub(1)
+                        .ok_or_else(|| invalid("hunk is longer than its header"))?;
+                    if text != actual {
+                        return Err(invalid("context doesn't match the original text"));
+                    }
+                    diff.retain(length)?;
+                }
+
+                old_line += 1;
+                position += length;
+            }
+        }
+
+        let rest = original.chars().count() - position;
+        diff.retain(rest)?;
+
+        Ok(diff.items)
+    }
+}
+
+/// The `start,count` range of a hunk header, with 1-based line numbers. Empty
+/// ranges start at the line before them, and the count of 1 is omitted.
+fn hunk_range(lines_before: usize, count: usize) -> String {
+    match count {
+        0 => format!("{lines_before},0"),
+        1 => format!("{}", lines_before + 1),
+        _ => format!("{},{count}", lines_before + 1),
+    }
+}
+
+/// Parse `@@ -start[,count] +start[,count] @@` into the start of the original
+/// range and the line counts of both ranges
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize)> {
+    let mut parts = line.strip_prefix("@@ -")?.split_whitespace();
+    let parse_range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+
+    let (old_start, old_count) = parse_range(parts.next()?)?;
+    let (_, new_count) = parse_range(parts.next()?.strip_prefix('+')?)?;
+    if parts.next()? != "@@" {
+        return None;
+    }
+
+    Some((old_start, old_count, new_count))
+}
+
+/// A compact diff being built, with adjacent retains and deletes joined
+#[derive(Default)]
+struct CompactDiff {
+    items: Vec<NumberOrText>,
+}
+
+impl CompactDiff {
+    fn retain(&mut self, length: usize) -> Result<(), DiffError> {
+        self.push_number(length, 1)
+    }
+
+    fn delete(&mut self, length: usize) -> Result<(), DiffError> {
+        self.push_number(length, -1)
+    }
+
+    fn push_number(&mut self, length: usize, sign: i64) -> Result<(), DiffError> {
+        if length == 0 {
+            return Ok(());
+        }
+
+        let value =
+            i64::try_from(length).map_err(|_| DiffError::IntegerOverflow { value: length })? * sign;
+        match self.items.last_mut() {
+            Some(NumberOrText::Number(last)) if last.signum() == sign => {
+                *last = last
+                    .checked_add(value)
+                    .ok_or(DiffError::IntegerOverflow { value: length })?;
+            }
+            _ => self.items.push(NumberOrText::Number(value)),
+        }
+
+        Ok(())
+    }
+
+    fn insert(&mut self, text: &str) {
+        match self.items.last_mut() {
+            Some(NumberOrText::Text(last)) => last.push_str(text),
+            _ => self.items.push(NumberOrText::Text(text.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{BuiltinTokenizer, EditedText};
+
+    #[test]
+    fn test_render() {
+        let original = "1\n2\n3\n4\n5\n6\n7\n8\n9";
+        let updated = "0\n1\n2\n3\n4\n5\n6\n7\n8\n9!";
+
+        assert_eq!(
+            UnifiedDiff::new(1).render(original, updated),
+            concat!(
+                "--- a\n",
+                "+++ b\n",
+                "@@ -1 +1,2 @@\n",
+                "+0\n",
+                " 1\n",
+                "@@ -8,2 +9,2 @@\n",
+                " 8\n",
+                "-9\n",
+                "\\ No newline at end of file\n",
+                "+9!\n",
+                "\\ No newline at end of file\n",
+            )
+        );
+        assert_eq!(
+            UnifiedDiff::new(0).render("1\n2\n", "0\n1\n2\n"),
+            "--- a\n+++ b\n@@ -0,0 +1 @@\n+0\n"
+        );
+        assert_eq!(UnifiedDiff::default().render(original, original), "");
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let original = "1\n2\n3\n4\n5\n6\n7\n8\n9";
+        for updated in ["0\n1\n2\n3\n4\n5\n6\n7\n8\n9!", "", "1\n\n3\n9\n", original] {
+            for context_lines in [0, 1, 3] {
+                let patch = UnifiedDiff::new(context_lines).render(original, updated);
+                let diff = UnifiedDiff::parse(original, &patch).unwrap();
+                let result = EditedText::from_diff(original, diff, &*BuiltinTokenizer::Line)
+                    .unwrap()
+                    .apply()
+                    .text();
+
+                assert_eq!(result, updated);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let original = "one\ntwo\n";
+
+        assert_eq!(
+            UnifiedDiff::parse(original, "@@ -1,2 +1,2 @@\n one\n-three\n+3\n"),
+            Err(DiffError::DeletedTextMismatch {
+                position: 4,
+                expected: "three\n".to_owned(),
+                actual: "two\n".to_owned(),
+            })
+        );
+        assert_eq!(
+            UnifiedDiff::parse(original, "@@ -1,2 +1,2 @@\n uno\n-two\n+2\n"),
+            Err(DiffError::InvalidPatch {
+                line: 2,
+                reason: "context doesn't match the original text".to_owned(),
+            })
+        );
+        assert_eq!(
+            UnifiedDiff::parse(original, "--- a\n+++ b\n@@ -1,2 +1,2 @@\n one\n"),
+            Err(DiffError::InvalidPatch {
+                line: 3,
+                reason: "hunk is shorter than its header".to_owned(),
+            })
+        );
+        assert_eq!(
+            UnifiedDiff::parse(original, "@@ -1 +x @@\n"),
+            Err(DiffError::InvalidPatch {
+                line: 1,
+                reason: "invalid hunk header".to_owned(),
+            })
+        );
+    }
+}
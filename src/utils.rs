@@ -1,5 +1,15 @@
 pub mod common_prefix_len;
 pub mod common_suffix_len;
+pub mod diff_budget;
+pub mod diff_codec;
+pub mod edit;
 pub mod find_longest_prefix_contained_within;
+pub mod fnv1a_hash;
+pub mod histogram_diff;
+pub mod intern;
+pub mod line_granularity_diff;
+pub mod min_edit_distance;
 pub mod myers_diff;
+pub mod patience_diff;
+pub mod split_at_ranges;
 pub mod string_builder;
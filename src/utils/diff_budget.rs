@@ -0,0 +1,99 @@
+use std::{cell::Cell, time::Instant};
+
+use crate::types::diff_options::DiffOptions;
+
+/// Whether `Instant::now` works on the target, as it panics on
+/// `wasm32-unknown-unknown`, which has no clock
+const HAS_CLOCK: bool = !cfg!(all(target_arch = "wasm32", target_os = "unknown"));
+
+/// Limits on the work of a diff, set by `DiffOptions`. Remembers whether they
+/// were ever exceeded, so that the shortcut can be reported.
+#[derive(Debug, Default)]
+pub struct DiffBudget {
+    deadline: Option<Instant>,
+    max_edit_distance: Option<usize>,
+    is_exceeded: Cell<bool>,
+}
+
+impl DiffBudget {
+    /// A budget which is never exceeded
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// A budget starting now. The timeout is ignored on targets without a
+    /// clock.
+    pub fn new(options: &DiffOptions) -> Self {
+        Self {
+            deadline: options
+                .timeout()
+                .filter(|_| HAS_CLOCK)
+                .map(|timeout| Instant::now() + timeout),
+            max_edit_distance: options.max_edit_distance(),
+            is_exceeded: Cell::new(false),
+        }
+    }
+
+    /// A budget with the same deadline, but without a limit on the edit
+    /// distance. Exceeding it isn't reported by `self`.
+    pub fn with_same_deadline(&self) -> Self {
+        Self {
+            deadline: self.deadline,
+            ..Self::default()
+        }
+    }
+
+    /// A budget with the same deadline, which also limits the edit distance
+    /// to `max_edit_distance`. Exceeding it isn't reported by `self`.
+    pub fn with_max_edit_distance(&self, max_edit_distance: usize) -> Self {
+        Self {
+            max_edit_distance: Some(max_edit_distance),
+            ..self.with_same_deadline()
+        }
+    }
+
+    /// Whether searching for an edit script with `edit_distance` edits can
+    /// go on
+    pub fn allows(&self, edit_distance: usize) -> bool {
+        let is_allowed = self
+            .max_edit_distance
+            .is_none_or(|max| edit_distance <= max)
+            && self
+                .deadline
+                .is_none_or(|deadline| Instant::now() < deadline);
+
+        if !is_allowed {
+            self.is_exceeded.set(true);
+        }
+
+        is_allowed
+    }
+
+    pub fn is_exceeded(&self) -> bool {
+        self.is_exceeded.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_budget() {
+        let budget = DiffBudget::unlimited();
+        assert!(budget.allows(usize::MAX));
+        assert!(!budget.is_exceeded());
+
+        let budget = DiffBudget::new(&DiffOptions::default().with_max_edit_distance(10));
+        assert!(budget.allows(10));
+        assert!(!budget.is_exceeded());
+        assert!(!budget.allows(11));
+        assert!(budget.is_exceeded());
+
+        let budget = DiffBudget::new(&DiffOptions::default().with_timeout(Duration::ZERO));
+        assert!(!budget.allows(0));
+        assert!(budget.is_exceeded());
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{DiffError, NumberOrText};
+
+/// The kinds of diff items, stored in the low bits of each item's header
+const RETAIN: u128 = 0;
+const DELETE: u128 = 1;
+const INSERT: u128 = 2;
+const KIND_BITS: u32 = 2;
+
+/// A header holds at most 65 bits (a count of up to 2^63 and the kind), which
+/// fit in 10 bytes of 7 bits each
+const MAX_VARINT_LENGTH: usize = 10;
+
+/// Encode a compact diff into bytes. Each item starts with a LEB128 varint
+/// holding its kind in the low two bits and its count (or the byte length of
+/// the inserted text) in the rest, and inserts are followed by their UTF-8
+/// bytes.
+pub fn encode_diff(diff: &[NumberOrText]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(diff.len() * 2);
+
+    for item in diff {
+        match item {
+            NumberOrText::Number(length) if *length >= 0 => {
+                write_varint(
+                    &mut bytes,
+                    u128::from(length.unsigned_abs()) << KIND_BITS | RETAIN,
+                );
+            }
+            NumberOrText::Number(length) => {
+                write_varint(
+                    &mut bytes,
+                    u128::from(length.unsigned_abs()) << KIND_BITS | DELETE,
+                );
+            }
+            NumberOrText::Text(text) => {
+                write_varint(&mut bytes, (text.len() as u128) << KIND_BITS | INSERT);
+                bytes.extend_from_slice(text.as_bytes());
+            }
+        }
+    }
+
+    bytes
+}
+
+/// Decode the bytes written by `encode_diff`
+pub fn decode_diff(bytes: &[u8]) -> Result<Vec<NumberOrText>, DiffError> {
+    let mut diff = Vec::new();
+    let mut position = 0;
+
+    while position < bytes.len() {
+        let item_position = position;
+        let header = read_varint(bytes, &mut position)?;
+        let value = header >> KIND_BITS;
+
+        let corrupted = |reason: &str| DiffError::CorruptedBytes {
+            position: item_position,
+            reason: reason.to_owned(),
+        };
+
+        match header & ((1 << KIND_BITS) - 1) {
+            RETAIN => diff.push(NumberOrText::Number(
+                i64::try_from(value).map_err(|_| corrupted("retained length overflows i64"))?,
+            )),
+            DELETE => diff.push(NumberOrText::Number(
+                0i64.checked_sub_unsigned(
+                    u64::try_from(value).map_err(|_| corrupted("deleted length overflows i64"))?,
+                )
+                .ok_or_else(|| corrupted("deleted length overflows i64"))?,
+            )),
+            INSERT => {
+                let length =
+                    usize::try_from(value).map_err(|_| corrupted("inserted length overflows"))?;
+                let end = position
+                    .checked_add(length)
+                    .filter(|end| *end <= bytes.len())
+                    .ok_or(DiffError::TruncatedBytes {
+                        position: bytes.len(),
+                    })?;
+
+                let text = std::str::from_utf8(&bytes[position..end])
+                    .map_err(|_| corrupted("inserted text is not valid UTF-8"))?;
+                diff.push(NumberOrText::Text(text.to_owned()));
+                position = end;
+            }
+            _ => return Err(corrupted("unknown item kind")),
+        }
+    }
+
+    Ok(diff)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u128) {
+    while value >= 0x80 {
+        #[allow(clippy::cast_possible_truncation)]
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], position: &mut usize) -> Result<u128, DiffError> {
+    let start = *position;
+    let mut value: u128 = 0;
+
+    for (index, byte) in bytes[start..].iter().enumerate() {
+        if index == MAX_VARINT_LENGTH {
+            break;
+        }
+
+        value |= u128::from(byte & 0x7f) << (7 * index);
+        if byte & 0x80 == 0 {
+            *position = start + index + 1;
+            return Ok(value);
+        }
+    }
+
+    if bytes.len() - start < MAX_VARINT_LENGTH {
+        Err(DiffError::TruncatedBytes {
+            position: bytes.len(),
+        })
+    } else {
+        Err(DiffError::CorruptedBytes {
+            position: start,
+            reason: "varint is too long".to_owned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let diff = vec![
+            NumberOrText::Number(5),
+            NumberOrText::Number(-3),
+            NumberOrText::Text("héllo 👋".to_owned()),
+            NumberOrText::Number(0),
+            NumberOrText::Text(String::new()),
+            NumberOrText::Number(i64::MAX),
+            NumberOrText::Number(i64::MIN),
+        ];
+
+        assert_eq!(decode_diff(&encode_diff(&diff)), Ok(diff));
+    }
+
+    #[test]
+    fn test_encoding() {
+        let diff = vec![
+            NumberOrText::Number(5),
+            NumberOrText::Number(-40),
+            NumberOrText::Text("hi".to_owned()),
+        ];
+
+        assert_eq!(
+            encode_diff(&diff),
+            vec![0b1_0100, 0b1010_0001, 0b1, 0b1010, b'h', b'i']
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert_eq!(
+            decode_diff(&[0b1010, b'h']),
+            Err(DiffError::TruncatedBytes { position: 2 })
+        );
+        assert_eq!(
+            decode_diff(&[0b100, 0x80]),
+            Err(DiffError::TruncatedBytes { position: 2 })
+        );
+        assert_eq!(
+            decode_diff(&[0b100, 0b11]),
+            Err(DiffError::CorruptedBytes {
+                position: 1,
+                reason: "unknown item kind".to_owned()
+            })
+        );
+        assert_eq!(
+            decode_diff(&[0b110, 0xff]),
+            Err(DiffError::CorruptedBytes {
+                position: 0,
+                reason: "inserted text is not valid UTF-8".to_owned()
+            })
+        );
+        assert_eq!(
+            decode_diff(&[0xff; 11]),
+            Err(DiffError::CorruptedBytes {
+                position: 0,
+                reason: "varint is too long".to_owned()
+            })
+        );
+    }
+}
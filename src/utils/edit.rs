@@ -0,0 +1,13 @@
+/// A step of an edit script between two sequences, covering a single item
+/// of one or both of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    /// The next items of both sequences are equal
+    Equal,
+
+    /// The next item of the old sequence is deleted
+    Delete,
+
+    /// The next item of the new sequence is inserted
+    Insert,
+}
@@ -0,0 +1,25 @@
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// 64-bit FNV-1a hash of the UTF-8 bytes of `text`. Unlike `DefaultHasher`,
+/// it's stable across platforms and Rust versions, so it can be sent over the
+/// network.
+pub fn fnv1a_hash(text: &str) -> u64 {
+    text.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_fnv1a_hash() {
+        assert_eq!(fnv1a_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a_hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a_hash("foobar"), 0x8594_4171_f739_67e8);
+    }
+}
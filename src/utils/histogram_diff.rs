@@ -0,0 +1,240 @@
+//! Histogram diff algorithm, as found in git and `JGit`.
+//!
+//! An extension of the patience algorithm: instead of only anchoring on
+//! unique tokens, the longest common run containing the least frequent
+//! tokens is matched up, and the ranges before and after it are diffed
+//! recursively. Ranges where every common token is too frequent are diffed
+//! with Myers' algorithm.
+
+use std::{collections::HashMap, hash::Hash, iter, ops::Range};
+
+use crate::utils::{
+    common_prefix_len::common_prefix_len, common_suffix_len::common_suffix_len,
+    diff_budget::DiffBudget, edit::Edit, min_edit_distance::min_edit_distance,
+    myers_diff::myers_diff_with_budget,
+};
+
+/// Tokens occurring more often than this are not used as anchors, same as in
+/// git
+const MAX_OCCURRENCES: usize = 64;
+
+/// A common run of tokens in the old and new ranges
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Region {
+    old_start: usize,
+    new_start: usize,
+    length: usize,
+
+    /// The lowest number of occurrences in the old range of the region's
+    /// tokens
+    occurrences: usize,
+}
+
+/// A part of the edit script which is yet to be written
+enum Task {
+    /// Diff the ranges of the old and new tokens
+    Diff(Range<usize>, Range<usize>),
+
+    /// The next tokens of both sides are equal
+    Equal(usize),
+}
+
+/// Histogram diff algorithm.
+///
+/// Like `patience_diff`, each `Edit` covers a single token. When `budget` runs
+/// out while looking for the best region, the best one found so far is used,
+/// and the ranges around it that are left are deleted and reinserted without
+/// diffing them.
+pub fn histogram_diff<E>(old: &[E], new: &[E], budget: &DiffBudget) -> Vec<Edit>
+where
+    E: Eq + Hash,
+{
+    let mut result = Vec::with_capacity(old.len() + new.len());
+    let mut edit_count = 0;
+
+    // Taken from the back, so the parts are written to the result in order.
+    // Recursing instead could nest as deep as the number of tokens.
+    let mut tasks = vec![Task::Diff(0..old.len(), 0..new.len())];
+    while let Some(task) = tasks.pop() {
+        let (mut old_range, mut new_range) = match task {
+            Task::Equal(length) => {
+                result.extend(iter::repeat_n(Edit::Equal, length));
+                continue;
+            }
+            Task::Diff(old_range, new_range) => (old_range, new_range),
+        };
+
+        let prefix_len = common_prefix_len(old, old_range.clone(), new, new_range.clone());
+        result.extend(iter::repeat_n(Edit::Equal, prefix_len));
+        old_range.start += prefix_len;
+        new_range.start += prefix_len;
+
+        let suffix_len = common_suffix_len(old, old_range.clone(), new, new_range.clone());
+        old_range.end -= suffix_len;
+        new_range.end -= suffix_len;
+        tasks.push(Task::Equal(suffix_len));
+
+        // Give up on the range once even the fewest edits it could need are
+        // over budget
+        let min_range_edit_distance =
+            min_edit_distance(&old[old_range.clone()], &new[new_range.clone()]);
+        if !budget.allows(edit_count + min_range_edit_distance) {
+            result.extend(iter::repeat_n(Edit::Delete, old_range.len()));
+            result.extend(iter::repeat_n(Edit::Insert, new_range.len()));
+            edit_count += old_range.len() + new_range.len();
+            continue;
+        }
+
+        if let Some(region) = find_region(old, old_range.clone(), new, new_range.clone(), budget) {
+            tasks.push(Task::Diff(
+                region.old_start + region.length..old_range.end,
+                region.new_start + region.length..new_range.end,
+            ));
+            tasks.push(Task::Equal(region.length));
+            tasks.push(Task::Diff(
+                old_range.start..region.old_start,
+                new_range.start..region.new_start,
+            ));
+        } else {
+            let edits = myers_diff_with_budget(&old[old_range], &new[new_range], budget);
+            edit_count += edits.iter().filter(|edit| **edit != Edit::Equal).count();
+            result.extend(edits);
+        }
+    }
+
+    result
+}
+
+/// Find the longest common run of tokens among those containing the least
+/// frequent tokens of the old range. Once the deadline of `budget` has
+/// passed, the best region found so far is returned.
+fn find_region<E>(
+    old: &[E],
+    old_range: Range<usize>,
+    new: &[E],
+    new_range: Range<usize>,
+    budget: &DiffBudget,
+) -> Option<Region>
+where
+    E: Eq + Hash,
+{
+    let mut positions: HashMap<&E, Vec<usize>> = HashMap::new();
+    for index in old_range.clone() {
+        positions.entry(&old[index]).or_default().push(index);
+    }
+
+    let mut best: Option<Region> = None;
+    let mut new_index = new_range.start;
+
+    // Checking the edit distance isn't needed here, only the deadline
+    while new_index < new_range.end && budget.allows(0) {
+        let mut next_new_index = new_index + 1;
+
+        let candidates = positions
+            .get(&new[new_index])
+            .filter(|candidates| candidates.len() <= MAX_OCCURRENCES);
+
+        for old_index in candidates.into_iter().flatten() {
+            let region = extend_region(
+                old, &old_range, *old_index, new, &new_range, new_index, &positions,
+            );
+            next_new_index = next_new_index.max(region.new_start + region.length);
+
+            let is_better = best.as_ref().is_none_or(|best| {
+                region.occurrences < best.occurrences
+                    || (region.occurrences == best.occurrences && region.length > best.length)
+            });
+            if is_better {
+                best = Some(region);
+            }
+        }
+
+        new_index = next_new_index;
+    }
+
+    best
+}
+
+/// Grow the region from the matching tokens at `old_index` and `new_index`
+/// in both directions while the tokens keep matching
+fn extend_region<E>(
+    old: &[E],
+    old_range: &Range<usize>,
+    old_index: usize,
+    new: &[E],
+    new_range: &Range<usize>,
+    new_index: usize,
+    positions: &HashMap<&E, Vec<usize>>,
+) -> Region
+where
+    E: Eq + Hash,
+{
+    let before = common_suffix_len(
+        old,
+        old_range.start..old_index,
+        new,
+        new_range.start..new_index,
+    );
+    let after = common_prefix_len(old, old_index..old_range.end, new, new_index..new_range.end);
+
+    let old_start = old_index - before;
+    let length = before + after;
+
+    Region {
+        old_start,
+        new_start: new_index - before,
+        length,
+        occurrences: old[old_start..old_start + length]
+            .iter()
+            .map(|token| positions[token].len())
+            .min()
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{Token, raw_operation::RawOperation};
+
+    #[test]
+    fn test_find_region_prefers_rare_tokens() {
+        let old: Vec<Token<String>> = ["-", "-", "a", "-", "b"].map(Token::from).to_vec();
+        let new: Vec<Token<String>> = ["b", "-", "-", "a", "x"].map(Token::from).to_vec();
+
+        assert_eq!(
+            find_region(&old, 0..5, &new, 0..5, &DiffBudget::unlimited()),
+            Some(Region {
+                old_start: 0,
+                new_start: 1,
+                length: 3,
+                occurrences: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_histogram_diff() {
+        let old: Vec<Token<String>> = ["-", "a", "-", "b", "-"].map(Token::from).to_vec();
+        let new: Vec<Token<String>> = ["-", "b", "-", "a", "-"].map(Token::from).to_vec();
+
+        assert_eq!(
+            RawOperation::vec_from_edits(
+                old.clone(),
+                new.clone(),
+                &histogram_diff(&old, &new, &DiffBudget::unlimited())
+            ),
+            vec![
+                RawOperation::Equal(vec!["-".into()]),
+                RawOperation::Delete(vec!["a".into()]),
+                RawOperation::Delete(vec!["-".into()]),
+                RawOperation::Equal(vec!["b".into()]),
+                RawOperation::Insert(vec!["-".into()]),
+                RawOperation::Insert(vec!["a".into()]),
+                RawOperation::Equal(vec!["-".into()]),
+            ]
+        );
+    }
+}
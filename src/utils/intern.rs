@@ -0,0 +1,46 @@
+use std::{collections::HashMap, fmt::Debug, hash::Hash};
+
+use crate::Token;
+
+/// Map the normalized form of each token to a small integer id, so that equal
+/// tokens get the same id. Diffing the ids avoids comparing the normalized
+/// forms (usually strings) over and over again.
+///
+/// # Panics
+///
+/// Panics if there are more than `u32::MAX` distinct tokens.
+pub fn intern<'a, T>(old: &'a [Token<T>], new: &'a [Token<T>]) -> (Vec<u32>, Vec<u32>)
+where
+    T: Eq + Hash + Clone + Debug,
+{
+    let mut ids: HashMap<&'a T, u32> = HashMap::with_capacity(old.len().max(new.len()));
+    let mut intern_all = |tokens: &'a [Token<T>]| -> Vec<u32> {
+        tokens
+            .iter()
+            .map(|token| {
+                let next_id = u32::try_from(ids.len()).expect("Too many distinct tokens");
+                *ids.entry(token.normalized()).or_insert(next_id)
+            })
+            .collect()
+    };
+
+    let old_ids = intern_all(old);
+    let new_ids = intern_all(new);
+
+    (old_ids, new_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let old: Vec<Token<String>> = ["a", "b", "a"].map(Token::from).to_vec();
+        let new: Vec<Token<String>> = ["b", "c", "a"].map(Token::from).to_vec();
+
+        assert_eq!(intern(&old, &new), (vec![0, 1, 0], vec![1, 2, 0]));
+    }
+}
@@ -0,0 +1,206 @@
+use std::{fmt::Debug, hash::Hash, iter, ops::Range};
+
+use crate::{
+    BuiltinTokenizer, raw_operation::RawOperation, tokenizer::Tokenizer,
+    types::diff_options::DiffAlgorithm, utils::diff_budget::DiffBudget,
+};
+
+/// Diff the texts line by line, then diff only the changed runs of lines with
+/// `tokenizer`. This is much cheaper than diffing every token of long texts,
+/// but tokens can't be aligned across unchanged lines.
+///
+/// The returned `RawOperations` each wrap a single token, just like the ones
+/// of `myers_diff`. The original text keeps the tokens `tokenizer` splits it
+/// into as a whole, because merging relies on every side's operations
+/// splitting the original text alike.
+pub fn line_granularity_diff<T>(
+    original: &str,
+    updated: &str,
+    tokenizer: &Tokenizer<T>,
+    algorithm: DiffAlgorithm,
+    budget: &DiffBudget,
+) -> Vec<RawOperation<T>>
+where
+    T: Eq + Hash + Clone + Debug,
+{
+    let line_diff = RawOperation::vec_from_with_algorithm(
+        (*BuiltinTokenizer::Line)(original),
+        (*BuiltinTokenizer::Line)(updated),
+        algorithm,
+        budget,
+    );
+
+    let original_tokens = tokenizer(original);
+    // Byte offset of the start of each original token, and of the end of the
+    // text
+    let token_starts = iter::once(0)
+        .chain(original_tokens.iter().scan(0, |offset, token| {
+            *offset += token.original().len();
+            Some(*offset)
+        }))
+        .collect::<Vec<_>>();
+
+    // Widen the hunks to whole original tokens, the bytes they're widened by
+    // are unchanged so they're the same in the updated text. Hunks widened
+    // into each other are diffed together.
+    let mut widened_hunks: Vec<(Range<usize>, Range<usize>)> = Vec::new();
+    for hunk in changed_hunks(&line_diff) {
+        let start = token_starts.partition_point(|offset| *offset <= hunk.original.start) - 1;
+        let end = token_starts.partition_point(|offset| *offset < hunk.original.end);
+        let updated_end = hunk.updated.end + token_starts[end] - hunk.original.end;
+
+        match widened_hunks.last_mut() {
+            Some((tokens, updated)) if start < tokens.end => {
+                tokens.end = end;
+                updated.end = updated_end;
+            }
+            _ => widened_hunks.push((
+                start..end,
+                hunk.updated.start - (hunk.original.start - token_starts[start])..updated_end,
+            )),
+        }
+    }
+
+    let mut result = Vec::new();
+    let mut original_tokens = original_tokens.into_iter();
+    let mut token_index = 0;
+
+    for (tokens, updated_range) in widened_hunks {
+        let equal_tokens = original_tokens.by_ref().take(tokens.start - token_index);
+        result.extend(equal_tokens.map(|token| RawOperation::Equal(vec![token])));
+
+        result.extend(RawOperation::vec_from_with_algorithm(
+            original_tokens.by_ref().take(tokens.len()).collect(),
+            tokenizer(&updated[updated_range]),
+            algorithm,
+            budget,
+        ));
+        token_index = tokens.end;
+    }
+
+    result.extend(original_tokens.map(|token| RawOperation::Equal(vec![token])));
+
+    result
+}
+
+/// The byte ranges of the original and updated texts changed by a run of
+/// inserted and deleted lines
+struct Hunk {
+    original: Range<usize>,
+    updated: Range<usize>,
+}
+
+fn changed_hunks<T>(line_diff: &[RawOperation<T>]) -> Vec<Hunk>
+where
+    T: PartialEq + Clone + Debug,
+{
+    let mut hunks = Vec::new();
+    let (mut original_offset, mut updated_offset) = (0, 0);
+
+    for run in line_diff.chunk_by(|a, b| is_equal(a) == is_equal(b)) {
+        let length_of = |is_kept: fn(&RawOperation<T>) -> bool| -> usize {
+            run.iter()
+                .filter(|operation| is_kept(operation))
+                .flat_map(RawOperation::tokens)
+                .map(|token| token.original().len())
+                .sum()
+        };
+        let original_length = length_of(|operation| !matches!(operation, RawOperation::Insert(..)));
+        let updated_length = length_of(|operation| !matches!(operation, RawOperation::Delete(..)));
+
+        if !is_equal(&run[0]) {
+            hunks.push(Hunk {
+                original: original_offset..original_offset + original_length,
+                updated: updated_offset..updated_offset + updated_length,
+            });
+        }
+
+        original_offset += original_length;
+        updated_offset += updated_length;
+    }
+
+    hunks
+}
+
+fn is_equal<T>(operation: &RawOperation<T>) -> bool
+where
+    T: PartialEq + Clone + Debug,
+{
+    matches!(operation, RawOperation::Equal(..))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_line_granularity_diff() {
+        let result = line_granularity_diff(
+            "first line\nsecond line\nthird line",
+            "first line\nsecond row\nthird line",
+            &*BuiltinTokenizer::Word,
+            DiffAlgorithm::Myers,
+            &DiffBudget::unlimited(),
+        );
+
+        let text_of = |is_kept: fn(&RawOperation<String>) -> bool| -> String {
+            result
+                .iter()
+                .filter(|operation| is_kept(operation))
+                .map(RawOperation::get_original_text)
+                .collect()
+        };
+
+        assert_eq!(
+            text_of(|operation| matches!(operation, RawOperation::Delete(..))),
+            " line"
+        );
+        assert_eq!(
+            text_of(|operation| matches!(operation, RawOperation::Insert(..))),
+            " row"
+        );
+        assert_eq!(
+            text_of(|operation| !matches!(operation, RawOperation::Insert(..))),
+            "first line\nsecond line\nthird line"
+        );
+    }
+
+    #[test]
+    fn test_line_granularity_diff_keeps_original_tokens() {
+        let original = "\nfirst line\nsecond line\n\nthird line.";
+        let updated = "third line.\n\nsecond line\nfirst line\n";
+        let tokenizer = &*BuiltinTokenizer::Word;
+
+        let result = line_granularity_diff(
+            original,
+            updated,
+            tokenizer,
+            DiffAlgorithm::Myers,
+            &DiffBudget::unlimited(),
+        );
+
+        let original_tokens = result
+            .iter()
+            .filter(|operation| !matches!(operation, RawOperation::Insert(..)))
+            .flat_map(RawOperation::tokens)
+            .map(|token| token.original().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            original_tokens,
+            tokenizer(original)
+                .iter()
+                .map(|token| token.original().to_owned())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            result
+                .iter()
+                .filter(|operation| !matches!(operation, RawOperation::Delete(..)))
+                .map(RawOperation::get_original_text)
+                .collect::<String>(),
+            updated
+        );
+    }
+}
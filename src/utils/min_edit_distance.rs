@@ -0,0 +1,41 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// A lower bound of the number of edits between `old` and `new`: the items of
+/// one without a counterpart in the other have to be deleted or inserted.
+pub fn min_edit_distance<E>(old: &[E], new: &[E]) -> usize
+where
+    E: Eq + Hash,
+{
+    let mut counts: HashMap<&E, usize> = HashMap::new();
+    for item in old {
+        *counts.entry(item).or_default() += 1;
+    }
+
+    let common_count = new
+        .iter()
+        .filter(|item| match counts.get_mut(item) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        })
+        .count();
+
+    old.len() + new.len() - 2 * common_count
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_min_edit_distance() {
+        assert_eq!(min_edit_distance::<u8>(b"", b""), 0);
+        assert_eq!(min_edit_distance(b"abc", b"cba"), 0);
+        assert_eq!(min_edit_distance(b"aab", b"abb"), 2);
+        assert_eq!(min_edit_distance(b"abc", b"x"), 4);
+    }
+}
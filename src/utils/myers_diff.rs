@@ -18,15 +18,14 @@
 //! subproblems small for typical text.
 
 use std::{
-    fmt::Debug,
+    iter,
     ops::{Index, IndexMut, Range},
     vec,
 };
 
-use crate::{
-    raw_operation::RawOperation,
-    tokenizer::token::Token,
-    utils::{common_prefix_len::common_prefix_len, common_suffix_len::common_suffix_len},
+use crate::utils::{
+    common_prefix_len::common_prefix_len, common_suffix_len::common_suffix_len,
+    diff_budget::DiffBudget, edit::Edit,
 };
 
 /// Myers' diff algorithm.
@@ -34,10 +33,22 @@ use crate::{
 /// Diff `old`, between indices `old_range` and `new` between indices
 /// `new_range`.
 ///
-/// The returned `RawOperations` each wrap a single token.
-pub fn myers_diff<T>(old: &[Token<T>], new: &[Token<T>]) -> Vec<RawOperation<T>>
+/// Returns one `Edit` per item of `old` and `new`, see
+/// `RawOperation::vec_from_edits`. Diffing interned tokens (see `intern`) is
+/// much faster than diffing the tokens themselves.
+pub fn myers_diff<E>(old: &[E], new: &[E]) -> Vec<Edit>
 where
-    T: PartialEq + Clone + Debug,
+    E: PartialEq,
+{
+    myers_diff_with_budget(old, new, &DiffBudget::unlimited())
+}
+
+/// Myers' diff algorithm which gives up on finding the shortest edit script
+/// for a range once `budget` is exceeded, and deletes and reinserts the range
+/// as a whole instead.
+pub fn myers_diff_with_budget<E>(old: &[E], new: &[E], budget: &DiffBudget) -> Vec<Edit>
+where
+    E: PartialEq,
 {
     let max_edit_distance = (old.len() + new.len()).div_ceil(2) + 1;
     let mut backward_endpoints = FurthestEndpoints::new(max_edit_distance);
@@ -51,6 +62,7 @@ where
         0..new.len(),
         &mut forward_endpoints,
         &mut backward_endpoints,
+        budget,
         &mut result,
     );
 
@@ -145,16 +157,17 @@ fn align_upper_bound(upper: isize, edit_distance: isize) -> isize {
 /// simultaneously run the basic algorithm in both the forward and reverse
 /// directions until furthest reaching forward and reverse paths starting at
 /// opposing corners 'overlap'.
-fn find_middle_snake<T>(
-    old: &[Token<T>],
+fn find_middle_snake<E>(
+    old: &[E],
     old_range: Range<usize>,
-    new: &[Token<T>],
+    new: &[E],
     new_range: Range<usize>,
     forward_endpoints: &mut FurthestEndpoints,
     backward_endpoints: &mut FurthestEndpoints,
+    budget: &DiffBudget,
 ) -> Option<(usize, usize)>
 where
-    T: PartialEq + Clone + Debug,
+    E: PartialEq,
 {
     let old_len = old_range.len();
     let new_len = new_range.len();
@@ -180,6 +193,10 @@ where
         isize::try_from(max_edit_distance).expect("max_edit_distance must fit in isize");
 
     for edit_distance in 0..max_edit_distance_signed {
+        if !budget.allows(edit_distance.unsigned_abs()) {
+            return None;
+        }
+
         // Tighter diagonal bounds: on diagonal k = x - y the constraints
         // 0 <= x <= old_len and 0 <= y <= new_len give k in [-new_len, old_len].
         // Intersect with the algorithm's [-edit_distance, edit_distance]
@@ -292,49 +309,36 @@ where
     None
 }
 
-fn conquer<T>(
-    old: &[Token<T>],
+#[allow(clippy::too_many_arguments)]
+fn conquer<E>(
+    old: &[E],
     mut old_range: Range<usize>,
-    new: &[Token<T>],
+    new: &[E],
     mut new_range: Range<usize>,
     forward_endpoints: &mut FurthestEndpoints,
     backward_endpoints: &mut FurthestEndpoints,
-    result: &mut Vec<RawOperation<T>>,
+    budget: &DiffBudget,
+    result: &mut Vec<Edit>,
 ) where
-    T: PartialEq + Clone + Debug,
+    E: PartialEq,
 {
     // Check for common prefix
     let prefix_len = common_prefix_len(old, old_range.clone(), new, new_range.clone());
-    if prefix_len > 0 {
-        result.extend(
-            old[old_range.start..old_range.start + prefix_len]
-                .iter()
-                .map(|token| RawOperation::Equal(vec![token.clone()])),
-        );
-    }
+    result.extend(iter::repeat_n(Edit::Equal, prefix_len));
     old_range.start += prefix_len;
     new_range.start += prefix_len;
 
     // Check for common suffix
     let suffix_len = common_suffix_len(old, old_range.clone(), new, new_range.clone());
-    let suffix_start = old_range.end - suffix_len;
     old_range.end -= suffix_len;
     new_range.end -= suffix_len;
 
     if old_range.is_empty() && new_range.is_empty() {
         // do nothing
     } else if new_range.is_empty() {
-        result.extend(
-            old[old_range.start..old_range.end]
-                .iter()
-                .map(|token| RawOperation::Delete(vec![token.clone()])),
-        );
+        result.extend(iter::repeat_n(Edit::Delete, old_range.len()));
     } else if old_range.is_empty() {
-        result.extend(
-            new[new_range.start..new_range.end]
-                .iter()
-                .map(|token| RawOperation::Insert(vec![token.clone()])),
-        );
+        result.extend(iter::repeat_n(Edit::Insert, new_range.len()));
     } else if let Some((split_old, split_new)) = find_middle_snake(
         old,
         old_range.clone(),
@@ -342,6 +346,7 @@ fn conquer<T>(
         new_range.clone(),
         forward_endpoints,
         backward_endpoints,
+        budget,
     ) {
         let (old_before, old_after) = split_at(old_range, split_old);
         let (new_before, new_after) = split_at(new_range, split_new);
@@ -352,6 +357,7 @@ fn conquer<T>(
             new_before,
             forward_endpoints,
             backward_endpoints,
+            budget,
             result,
         );
         conquer(
@@ -361,28 +367,16 @@ fn conquer<T>(
             new_after,
             forward_endpoints,
             backward_endpoints,
+            budget,
             result,
         );
     } else {
-        result.extend(
-            old[old_range.start..old_range.end]
-                .iter()
-                .map(|token| RawOperation::Delete(vec![token.clone()])),
-        );
-        result.extend(
-            new[new_range.start..new_range.end]
-                .iter()
-                .map(|token| RawOperation::Insert(vec![token.clone()])),
-        );
+        // Out of budget
+        result.extend(iter::repeat_n(Edit::Delete, old_range.len()));
+        result.extend(iter::repeat_n(Edit::Insert, new_range.len()));
     }
 
-    if suffix_len > 0 {
-        result.extend(
-            old[suffix_start..suffix_start + suffix_len]
-                .iter()
-                .map(|token| RawOperation::Equal(vec![token.clone()])),
-        );
-    }
+    result.extend(iter::repeat_n(Edit::Equal, suffix_len));
 }
 
 #[cfg(test)]
@@ -390,19 +384,24 @@ mod tests {
     use insta::assert_debug_snapshot;
 
     use super::*;
+    use crate::{Token, raw_operation::RawOperation};
+
+    fn diff(old: &[Token<String>], new: &[Token<String>]) -> Vec<RawOperation<String>> {
+        RawOperation::vec_from_edits(old.to_vec(), new.to_vec(), &myers_diff(old, new))
+    }
 
     #[test]
     fn test_empty_diff() {
         let old: Vec<Token<String>> = vec![];
         let new: Vec<Token<String>> = vec![];
-        let result = myers_diff(&old, &new);
+        let result = diff(&old, &new);
         assert_eq!(result.len(), 0);
     }
 
     #[test]
     fn test_identical_content() {
         let content = vec!["a".into(), "b".into(), "c".into()];
-        let result = myers_diff(&content, &content);
+        let result = diff(&content, &content);
         assert_debug_snapshot!(result);
     }
 
@@ -410,7 +409,7 @@ mod tests {
     fn test_insert_only() {
         let old: Vec<Token<String>> = vec![];
         let new: Vec<Token<String>> = vec!["a".into(), "b".into()];
-        let result = myers_diff(&old, &new);
+        let result = diff(&old, &new);
         assert_debug_snapshot!(result);
     }
 
@@ -418,7 +417,7 @@ mod tests {
     fn test_delete_only() {
         let old = vec!["a".into(), "b".into()];
         let new: Vec<Token<String>> = vec![];
-        let result = myers_diff(&old, &new);
+        let result = diff(&old, &new);
         assert_debug_snapshot!(result);
     }
 
@@ -426,7 +425,7 @@ mod tests {
     fn test_prefix_and_suffix() {
         let old = vec!["a".into(), "b".into(), "c".into(), "d".into()];
         let new = vec!["a".into(), "x".into(), "d".into()];
-        let result = myers_diff(&old, &new);
+        let result = diff(&old, &new);
         assert_debug_snapshot!(result);
     }
 
@@ -434,7 +433,7 @@ mod tests {
     fn test_complex_diff() {
         let old = vec!["a".into(), "b".into(), "c".into(), "d".into()];
         let new = vec!["a".into(), "x".into(), "c".into(), "y".into()];
-        let result = myers_diff(&old, &new);
+        let result = diff(&old, &new);
         assert_debug_snapshot!(result);
     }
 }
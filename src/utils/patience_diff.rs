@@ -0,0 +1,204 @@
+//! Patience diff algorithm.
+//!
+//! Only tokens occurring exactly once on both sides are used as anchors, so
+//! frequent tokens (like blank lines or list bullets) can't misalign the
+//! diff. The anchors are matched up via the longest increasing subsequence of
+//! their positions, and the gaps between them are diffed recursively. Ranges
+//! without unique common tokens are diffed with Myers' algorithm.
+//!
+//! See [Bram Cohen's description](https://bramcohen.livejournal.com/73318.html)
+//! of the algorithm.
+
+use std::{collections::HashMap, hash::Hash, iter, ops::Range};
+
+use crate::utils::{
+    common_prefix_len::common_prefix_len, common_suffix_len::common_suffix_len,
+    diff_budget::DiffBudget, edit::Edit, min_edit_distance::min_edit_distance,
+    myers_diff::myers_diff_with_budget,
+};
+
+/// A part of the edit script which is yet to be written
+enum Task {
+    /// Diff the ranges of the old and new tokens
+    Diff(Range<usize>, Range<usize>),
+
+    /// The next tokens of both sides are equal
+    Equal(usize),
+}
+
+/// Patience diff algorithm.
+///
+/// Returns one `Edit` per token, just like `myers_diff`. Once `budget` is
+/// exceeded, the gaps between anchors which are still to be diffed are
+/// deleted and reinserted as a whole.
+pub fn patience_diff<E>(old: &[E], new: &[E], budget: &DiffBudget) -> Vec<Edit>
+where
+    E: Eq + Hash,
+{
+    let mut result = Vec::with_capacity(old.len() + new.len());
+    let mut edit_count = 0;
+
+    // Taken from the back, so the parts are written to the result in order.
+    // Recursing instead could nest as deep as the number of tokens.
+    let mut tasks = vec![Task::Diff(0..old.len(), 0..new.len())];
+    while let Some(task) = tasks.pop() {
+        let (mut old_range, mut new_range) = match task {
+            Task::Equal(length) => {
+                result.extend(iter::repeat_n(Edit::Equal, length));
+                continue;
+            }
+            Task::Diff(old_range, new_range) => (old_range, new_range),
+        };
+
+        let prefix_len = common_prefix_len(old, old_range.clone(), new, new_range.clone());
+        result.extend(iter::repeat_n(Edit::Equal, prefix_len));
+        old_range.start += prefix_len;
+        new_range.start += prefix_len;
+
+        let suffix_len = common_suffix_len(old, old_range.clone(), new, new_range.clone());
+        old_range.end -= suffix_len;
+        new_range.end -= suffix_len;
+        tasks.push(Task::Equal(suffix_len));
+
+        // Give up on the range once even the fewest edits it could need are
+        // over budget
+        let min_range_edit_distance =
+            min_edit_distance(&old[old_range.clone()], &new[new_range.clone()]);
+        if !budget.allows(edit_count + min_range_edit_distance) {
+            result.extend(iter::repeat_n(Edit::Delete, old_range.len()));
+            result.extend(iter::repeat_n(Edit::Insert, new_range.len()));
+            edit_count += old_range.len() + new_range.len();
+            continue;
+        }
+
+        let anchors = find_anchors(old, old_range.clone(), new, new_range.clone());
+        if anchors.is_empty() {
+            let edits = myers_diff_with_budget(&old[old_range], &new[new_range], budget);
+            edit_count += edits.iter().filter(|edit| **edit != Edit::Equal).count();
+            result.extend(edits);
+        } else {
+            let (mut old_end, mut new_end) = (old_range.end, new_range.end);
+            for (old_index, new_index) in anchors.into_iter().rev() {
+                tasks.push(Task::Diff(old_index + 1..old_end, new_index + 1..new_end));
+                tasks.push(Task::Equal(1));
+                (old_end, new_end) = (old_index, new_index);
+            }
+            tasks.push(Task::Diff(
+                old_range.start..old_end,
+                new_range.start..new_end,
+            ));
+        }
+    }
+
+    result
+}
+
+/// Positions of the tokens unique to both ranges which can be matched up
+/// without crossing each other, in order
+fn find_anchors<E>(
+    old: &[E],
+    old_range: Range<usize>,
+    new: &[E],
+    new_range: Range<usize>,
+) -> Vec<(usize, usize)>
+where
+    E: Eq + Hash,
+{
+    // For each token: the number of occurrences and the last position in old,
+    // and the same for new
+    let mut occurrences: HashMap<&E, (usize, usize, usize, usize)> = HashMap::new();
+    for index in old_range {
+        let entry = occurrences.entry(&old[index]).or_default();
+        entry.0 += 1;
+        entry.1 = index;
+    }
+    for index in new_range {
+        if let Some(entry) = occurrences.get_mut(&new[index]) {
+            entry.2 += 1;
+            entry.3 = index;
+        }
+    }
+
+    let mut unique_pairs = occurrences
+        .into_values()
+        .filter(|(old_count, _, new_count, _)| *old_count == 1 && *new_count == 1)
+        .map(|(_, old_index, _, new_index)| (old_index, new_index))
+        .collect::<Vec<_>>();
+    unique_pairs.sort_unstable();
+
+    longest_increasing_subsequence(&unique_pairs)
+}
+
+/// The longest subsequence of `pairs` (sorted by their first item) in which
+/// the second items are increasing too, found via patience sorting
+fn longest_increasing_subsequence(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // The index of the pair on the top of each pile
+    let mut pile_tops: Vec<usize> = Vec::new();
+    // The index of the pair on the top of the previous pile when each pair
+    // was placed
+    let mut predecessors: Vec<Option<usize>> = Vec::with_capacity(pairs.len());
+
+    for (index, (_, new_index)) in pairs.iter().enumerate() {
+        let pile = pile_tops.partition_point(|top| pairs[*top].1 < *new_index);
+        predecessors.push(pile.checked_sub(1).map(|previous| pile_tops[previous]));
+
+        if pile == pile_tops.len() {
+            pile_tops.push(index);
+        } else {
+            pile_tops[pile] = index;
+        }
+    }
+
+    let mut result = Vec::with_capacity(pile_tops.len());
+    let mut current = pile_tops.last().copied();
+    while let Some(index) = current {
+        result.push(pairs[index]);
+        current = predecessors[index];
+    }
+    result.reverse();
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{Token, raw_operation::RawOperation};
+
+    #[test]
+    fn test_longest_increasing_subsequence() {
+        assert_eq!(longest_increasing_subsequence(&[]), vec![]);
+        assert_eq!(
+            longest_increasing_subsequence(&[(0, 3), (1, 0), (2, 4), (3, 1), (4, 2), (5, 5)]),
+            vec![(1, 0), (3, 1), (4, 2), (5, 5)]
+        );
+    }
+
+    #[test]
+    fn test_anchors_on_unique_tokens() {
+        let old: Vec<Token<String>> = ["#", "a", "-", "b", "-", "c"].map(Token::from).to_vec();
+        let new: Vec<Token<String>> = ["#", "-", "c", "-", "a", "-"].map(Token::from).to_vec();
+
+        assert_eq!(find_anchors(&old, 1..6, &new, 1..6), vec![(5, 2)]);
+        assert_eq!(
+            RawOperation::vec_from_edits(
+                old.clone(),
+                new.clone(),
+                &patience_diff(&old, &new, &DiffBudget::unlimited())
+            ),
+            vec![
+                RawOperation::Equal(vec!["#".into()]),
+                RawOperation::Delete(vec!["a".into()]),
+                RawOperation::Delete(vec!["-".into()]),
+                RawOperation::Delete(vec!["b".into()]),
+                RawOperation::Equal(vec!["-".into()]),
+                RawOperation::Equal(vec!["c".into()]),
+                RawOperation::Insert(vec!["-".into()]),
+                RawOperation::Insert(vec!["a".into()]),
+                RawOperation::Insert(vec!["-".into()]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use std::ops::Range;
+
+/// Split `text`, which covers the characters from `start` onwards, at the
+/// boundaries of `ranges` (sorted by their start). Returns the character
+/// range, the text and whether it's within one of `ranges` for each piece.
+pub fn split_at_ranges<'a>(
+    text: &'a str,
+    start: usize,
+    ranges: &[Range<usize>],
+) -> Vec<(Range<usize>, &'a str, bool)> {
+    let end = start + text.chars().count();
+    let mut pieces = Vec::new();
+    let mut position = start;
+    let mut remaining = text;
+
+    let mut take = |range: Range<usize>, is_within: bool, remaining: &mut &'a str| {
+        let byte_length = remaining
+            .char_indices()
+            .nth(range.len())
+            .map_or(remaining.len(), |(index, _)| index);
+        let (piece, rest) = remaining.split_at(byte_length);
+        *remaining = rest;
+        pieces.push((range, piece, is_within));
+    };
+
+    let candidates = &ranges[..ranges.partition_point(|range| range.start < end)];
+    for range in candidates {
+        let range_start = range.start.max(position);
+        let range_end = range.end.min(end);
+        if range_start >= range_end {
+            continue;
+        }
+
+        if position < range_start {
+            take(position..range_start, false, &mut remaining);
+        }
+        take(range_start..range_end, true, &mut remaining);
+        position = range_end;
+    }
+
+    if position < end || position == start {
+        take(position..end, false, &mut remaining);
+    }
+
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_split_at_ranges() {
+        assert_eq!(
+            split_at_ranges("hello wörld", 10, &[0..12, 15..17, 19..30]),
+            vec![
+                (10..12, "he", true),
+                (12..15, "llo", false),
+                (15..17, " w", true),
+                (17..19, "ör", false),
+                (19..21, "ld", true),
+            ]
+        );
+
+        assert_eq!(
+            split_at_ranges("hello", 0, &[7..9, 12..15]),
+            vec![(0..5, "hello", false)]
+        );
+        assert_eq!(
+            split_at_ranges("", 3, &[0..2, 5..9]),
+            vec![(3..3, "", false)]
+        );
+    }
+}